@@ -0,0 +1,508 @@
+use clap::Parser;
+use std::ffi::OsString;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+/// Hidden flag that passes content piped to the launching process on to a copy
+pub const STDIN_FILE_FLAG: &str = "--stdin-file";
+
+/// Command-line interface of the GUI binary
+#[derive(Debug, Clone, Default, Parser)]
+#[command(
+    name = "mark-lens",
+    version,
+    about = "A cross-platform Markdown viewer and editor"
+)]
+pub struct Cli {
+    /// Files to open (`notes.md:120:4` jumps to line 120, column 4), a folder
+    /// to open as a workspace, or `-` to read from stdin
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Open the files read-only
+    #[arg(long)]
+    pub readonly: bool,
+
+    /// Open the files in a new window instead of the focused one
+    #[arg(long)]
+    pub new_window: bool,
+
+    /// Wait until the window is closed before returning, e.g. for `GIT_EDITOR`
+    #[arg(long)]
+    pub wait: bool,
+
+    /// Name of the document read from stdin, also suggested when saving it
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Wait request served by this launch, set by the waiting process
+    #[arg(long, hide = true, value_name = "ID")]
+    pub wait_id: Option<String>,
+
+    /// Content piped to the launching process, saved to a temporary file
+    #[arg(long, hide = true, value_name = "FILE")]
+    pub stdin_file: Option<PathBuf>,
+
+    /// Start a separate instance instead of handing the files to the running one
+    #[arg(long)]
+    pub new_instance: bool,
+}
+
+/// Something to open, named on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// File, optionally with a position to jump to
+    File {
+        path: PathBuf,
+        line: Option<u32>,
+        column: Option<u32>,
+    },
+    /// Folder to open as a workspace
+    Directory(PathBuf),
+    /// Content read from stdin
+    Stdin,
+    /// Content read from stdin by the launching process, see [`STDIN_FILE_FLAG`]
+    Piped(PathBuf),
+}
+
+/// Parsed command line with paths resolved against the caller's working directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchRequest {
+    pub targets: Vec<Target>,
+    pub readonly: bool,
+    pub new_window: bool,
+    pub wait: bool,
+    pub wait_id: Option<String>,
+    pub title: Option<String>,
+}
+
+impl Cli {
+    /// Converts parsed arguments into a launch request.
+    ///
+    /// Deep links are skipped, since the deep link plugin handles them.
+    pub fn into_request(self, cwd: &Path) -> LaunchRequest {
+        let mut targets: Vec<Target> = self
+            .paths
+            .iter()
+            .filter(|arg| !crate::deep_link::is_deep_link(arg))
+            .map(|arg| resolve_target(arg, cwd, |path| path.exists()))
+            .collect();
+        if let Some(file) = self.stdin_file {
+            targets.push(Target::Piped(file));
+        }
+
+        LaunchRequest {
+            targets,
+            readonly: self.readonly,
+            new_window: self.new_window,
+            wait: self.wait,
+            wait_id: self.wait_id,
+            title: self.title,
+        }
+    }
+
+    /// Checks whether this launch takes a document from stdin.
+    ///
+    /// That is the case for an explicit `-`, or for a pipe or file on stdin
+    /// when no paths are given.
+    fn reads_stdin(&self, stdin_is_terminal: bool) -> bool {
+        self.paths.iter().any(|arg| arg == "-") || (self.paths.is_empty() && !stdin_is_terminal)
+    }
+}
+
+/// Returns a name that is unique across processes, for temporary files.
+pub fn unique_id() -> String {
+    let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("{}-{}", std::process::id(), started)
+}
+
+/// Returns a private directory of the current user for files shared between launches.
+///
+/// Lives in `$XDG_RUNTIME_DIR` when set, or else in the temp directory under a
+/// per-user name. The directory is created with mode 0700, and an existing one
+/// is only used if no other user can access it.
+pub fn private_dir(name: &str) -> Result<PathBuf, String> {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime) if !runtime.is_empty() => {
+            PathBuf::from(runtime).join(format!("mark-lens-{}", name))
+        }
+        _ => std::env::temp_dir().join(format!("mark-lens-{}-{}", name, user_suffix())),
+    };
+    create_private_dir(&dir)?;
+    Ok(dir)
+}
+
+/// Identifies the current user in names of shared temporary directories.
+fn user_suffix() -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let Ok(metadata) = std::fs::metadata("/proc/self") {
+            return metadata.uid().to_string();
+        }
+    }
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Creates `dir` accessible only to its owner, or checks that an existing one is.
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    match builder.create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => return Err(e.to_string()),
+        Err(_) => {}
+    }
+
+    let metadata = std::fs::symlink_metadata(dir).map_err(|e| e.to_string())?;
+    if !metadata.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(format!(
+                "Directory is shared with other users: {}",
+                dir.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Directory for content piped to launching processes
+fn stdin_dir() -> Result<PathBuf, String> {
+    private_dir("stdin")
+}
+
+/// Starts a copy of this executable with `args`, detached from stdin.
+pub fn spawn_copy(args: Vec<OsString>) -> std::io::Result<Child> {
+    Command::new(std::env::current_exe()?)
+        .args(args)
+        .stdin(Stdio::null())
+        .spawn()
+}
+
+/// Hands the launch to a copy of this process when it can't be forwarded as is.
+///
+/// The single-instance plugin forwards only arguments, so stdin is saved to
+/// a temporary file first, and `--wait` needs a process that stays behind.
+/// Returns the exit status for this process, or `None` to carry on here.
+pub fn relaunch_if_needed(cli: &Cli) -> Option<i32> {
+    if cli.wait_id.is_some() || cli.stdin_file.is_some() {
+        return None;
+    }
+
+    let mut args: Vec<OsString> = Vec::new();
+    if cli.reads_stdin(std::io::stdin().is_terminal()) {
+        let mut content = Vec::new();
+        if let Err(e) = std::io::stdin().read_to_end(&mut content) {
+            eprintln!("mark-lens: failed to read stdin: {}", e);
+            return Some(1);
+        }
+        // An empty stdin that wasn't asked for is most likely not a pipe
+        if !content.is_empty() || cli.paths.iter().any(|arg| arg == "-") {
+            let content = String::from_utf8_lossy(&content);
+            match stdin_dir().and_then(|dir| save_piped(&dir, &content)) {
+                Ok(file) => {
+                    args.push(STDIN_FILE_FLAG.into());
+                    args.push(file.into());
+                }
+                Err(e) => {
+                    eprintln!("mark-lens: failed to save stdin: {}", e);
+                    return Some(1);
+                }
+            }
+        }
+    }
+    if args.is_empty() && !cli.wait {
+        return None;
+    }
+
+    args.extend(std::env::args_os().skip(1).filter(|arg| arg != "-"));
+    if cli.wait {
+        return Some(crate::wait::launch_and_wait(args));
+    }
+    match spawn_copy(args) {
+        Ok(_) => Some(0),
+        Err(e) => {
+            eprintln!("mark-lens: failed to launch: {}", e);
+            Some(1)
+        }
+    }
+}
+
+/// Saves piped content to a new file in `dir`.
+fn save_piped(dir: &Path, content: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let file = dir.join(format!("{}.md", unique_id()));
+    std::fs::write(&file, content).map_err(|e| e.to_string())?;
+    Ok(file)
+}
+
+/// Reads and removes a file saved by [`relaunch_if_needed`].
+pub fn take_piped(file: &Path) -> Result<String, String> {
+    take_piped_from(&stdin_dir()?, file)
+}
+
+/// Reads and removes a file of piped content.
+///
+/// Only files directly inside `dir` are accepted, so the hidden flag can't
+/// be used to delete other files.
+fn take_piped_from(dir: &Path, file: &Path) -> Result<String, String> {
+    if file.parent() != Some(dir) {
+        return Err(format!("Not a piped content file: {}", file.display()));
+    }
+    let content = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(file);
+    Ok(content)
+}
+
+/// Parses the arguments of a launch, including the program name.
+pub fn parse_request<I, T>(args: I, cwd: &Path) -> Result<LaunchRequest, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    Ok(Cli::try_parse_from(args)?.into_request(cwd))
+}
+
+/// Resolves a path argument to a target.
+///
+/// Relative paths are joined to `cwd`. A `:line` or `:line:column` suffix is
+/// split off unless the whole argument names an existing file.
+fn resolve_target(arg: &str, cwd: &Path, exists: impl Fn(&Path) -> bool) -> Target {
+    if arg == "-" {
+        return Target::Stdin;
+    }
+
+    let resolve = |path: &str| {
+        let path = crate::extract_file_path(path);
+        if path.is_absolute() {
+            path
+        } else {
+            cwd.join(path)
+        }
+    };
+
+    let whole = resolve(arg);
+    let (path, line, column) = if exists(&whole) {
+        (whole, None, None)
+    } else {
+        let (path, line, column) = split_location(arg);
+        (resolve(path), line, column)
+    };
+
+    if path.is_dir() {
+        Target::Directory(path)
+    } else {
+        Target::File { path, line, column }
+    }
+}
+
+/// Splits a `path:line` or `path:line:column` argument.
+///
+/// Only trailing numeric parts are taken, so Windows drive letters
+/// (`C:\notes.md:12`) are kept in the path.
+fn split_location(arg: &str) -> (&str, Option<u32>, Option<u32>) {
+    let number = |s: &str| s.parse::<u32>().ok().filter(|n| *n > 0);
+
+    if let Some((rest, last)) = arg.rsplit_once(':') {
+        if let Some(last) = number(last) {
+            if let Some((path, line)) = rest.rsplit_once(':') {
+                if let Some(line) = number(line) {
+                    if !path.is_empty() {
+                        return (path, Some(line), Some(last));
+                    }
+                }
+            }
+            if !rest.is_empty() {
+                return (rest, Some(last), None);
+            }
+        }
+    }
+    (arg, None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn file(path: PathBuf, line: Option<u32>, column: Option<u32>) -> Target {
+        Target::File { path, line, column }
+    }
+
+    #[test]
+    fn test_create_private_dir() {
+        let dir = tempdir().unwrap();
+        let private = dir.path().join("private");
+        create_private_dir(&private).unwrap();
+        // Reusing the directory is fine
+        create_private_dir(&private).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&private).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+
+            let shared = dir.path().join("shared");
+            std::fs::create_dir(&shared).unwrap();
+            std::fs::set_permissions(&shared, std::fs::Permissions::from_mode(0o777)).unwrap();
+            assert!(create_private_dir(&shared).is_err());
+        }
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(create_private_dir(&file).is_err());
+    }
+
+    #[test]
+    fn test_split_location() {
+        assert_eq!(split_location("notes.md"), ("notes.md", None, None));
+        assert_eq!(
+            split_location("notes.md:120"),
+            ("notes.md", Some(120), None)
+        );
+        assert_eq!(
+            split_location("notes.md:120:4"),
+            ("notes.md", Some(120), Some(4))
+        );
+        assert_eq!(
+            split_location("C:\\notes.md:12"),
+            ("C:\\notes.md", Some(12), None)
+        );
+        assert_eq!(split_location("C:\\notes.md"), ("C:\\notes.md", None, None));
+        assert_eq!(split_location("notes.md:abc"), ("notes.md:abc", None, None));
+        assert_eq!(split_location("notes.md:0"), ("notes.md:0", None, None));
+        assert_eq!(split_location(":12"), (":12", None, None));
+    }
+
+    #[test]
+    fn test_resolve_target_relative_to_cwd() {
+        let cwd = Path::new("/home/me");
+        assert_eq!(
+            resolve_target("notes.md:120:4", cwd, |_| false),
+            file(cwd.join("notes.md"), Some(120), Some(4))
+        );
+        assert_eq!(resolve_target("-", cwd, |_| false), Target::Stdin);
+    }
+
+    #[test]
+    fn test_resolve_target_prefers_existing_file() {
+        let cwd = Path::new("/home/me");
+        assert_eq!(
+            resolve_target("odd:12", cwd, |path| path == cwd.join("odd:12")),
+            file(cwd.join("odd:12"), None, None)
+        );
+    }
+
+    #[test]
+    fn test_resolve_target_directory() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        assert_eq!(
+            resolve_target("docs", dir.path(), |path| path.exists()),
+            Target::Directory(dir.path().join("docs"))
+        );
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let cwd = Path::new("/work");
+        let request = parse_request(
+            [
+                "mark-lens",
+                "--readonly",
+                "--new-window",
+                "--wait",
+                "a.md:3",
+                "-",
+                "marklens://open?path=/b.md",
+            ],
+            cwd,
+        )
+        .unwrap();
+        assert!(request.readonly && request.new_window && request.wait);
+        assert_eq!(
+            request.targets,
+            vec![file(cwd.join("a.md"), Some(3), None), Target::Stdin]
+        );
+    }
+
+    #[test]
+    fn test_parse_request_wait_id() {
+        let request = parse_request(
+            [
+                "mark-lens",
+                "--wait",
+                "msg.md",
+                crate::wait::WAIT_ID_FLAG,
+                "7-1",
+            ],
+            Path::new("/work"),
+        )
+        .unwrap();
+        assert_eq!(request.wait_id.as_deref(), Some("7-1"));
+        assert_eq!(request.targets.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_request_stdin_file() {
+        let request = parse_request(
+            [
+                "mark-lens",
+                STDIN_FILE_FLAG,
+                "/tmp/1-1.md",
+                "--title",
+                "CHANGELOG",
+            ],
+            Path::new("/work"),
+        )
+        .unwrap();
+        assert_eq!(
+            request.targets,
+            vec![Target::Piped(PathBuf::from("/tmp/1-1.md"))]
+        );
+        assert_eq!(request.title.as_deref(), Some("CHANGELOG"));
+    }
+
+    #[test]
+    fn test_reads_stdin() {
+        let cli = |args: &[&str]| Cli::try_parse_from(args).unwrap();
+        assert!(cli(&["mark-lens", "-"]).reads_stdin(true));
+        assert!(cli(&["mark-lens"]).reads_stdin(false));
+        assert!(!cli(&["mark-lens"]).reads_stdin(true));
+        assert!(!cli(&["mark-lens", "a.md"]).reads_stdin(false));
+    }
+
+    #[test]
+    fn test_take_piped() {
+        let dir = tempdir().unwrap();
+        let file = save_piped(dir.path(), "# Piped").unwrap();
+        assert_eq!(take_piped_from(dir.path(), &file).unwrap(), "# Piped");
+        assert!(!file.exists());
+
+        let other = dir.path().join("keep.md");
+        std::fs::write(&other, "keep").unwrap();
+        assert!(take_piped_from(&dir.path().join("sub"), &other).is_err());
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn test_parse_request_rejects_unknown_flags() {
+        assert!(parse_request(["mark-lens", "--frobnicate"], Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use url::Url;
+
+/// URL scheme registered for deep links, e.g. `marklens://open?path=/notes/a.md&line=42`
+pub const SCHEME: &str = "marklens";
+
+/// Directory inside the app config dir that holds templates for `marklens://new`
+const TEMPLATES_DIR: &str = "templates";
+
+/// Action requested by a deep link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLinkAction {
+    /// `marklens://open?path=<path>[&line=<n>][&heading=<text>]`
+    Open {
+        path: PathBuf,
+        line: Option<u32>,
+        heading: Option<String>,
+    },
+    /// `marklens://new[?template=<name>]`
+    New { template: Option<String> },
+    /// `marklens://search?q=<query>[&path=<dir>]`
+    Search {
+        query: String,
+        path: Option<PathBuf>,
+    },
+}
+
+/// Payload of the `search-requested` event
+#[derive(Debug, Clone, Serialize)]
+struct SearchRequest {
+    query: String,
+    path: Option<String>,
+}
+
+/// Checks whether a command-line argument is a deep link.
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(scheme, _)| scheme.eq_ignore_ascii_case(SCHEME))
+}
+
+/// Parses and validates a deep link.
+///
+/// The action is the URL host (`marklens://open`) or path (`marklens:open`).
+/// Unknown actions, unknown or repeated parameters and invalid values are errors.
+pub fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    if !url.scheme().eq_ignore_ascii_case(SCHEME) {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_lowercase();
+
+    let mut params: Vec<(String, String)> = Vec::new();
+    for (key, value) in url.query_pairs() {
+        if params.iter().any(|(k, _)| *k == key) {
+            return Err(format!("Parameter \"{}\" is given more than once", key));
+        }
+        params.push((key.to_string(), value.to_string()));
+    }
+    let allowed: &[&str] = match action.as_str() {
+        "open" => &["path", "line", "heading"],
+        "new" => &["template"],
+        "search" => &["q", "path"],
+        _ => return Err(format!("Unknown action: {}", action)),
+    };
+    if let Some((key, _)) = params.iter().find(|(k, _)| !allowed.contains(&k.as_str())) {
+        return Err(format!("Unknown parameter \"{}\" for {}", key, action));
+    }
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    match action.as_str() {
+        "open" => {
+            let path = param("path").ok_or("Missing parameter \"path\"")?;
+            let line = param("line")
+                .map(|line| match line.parse::<u32>() {
+                    Ok(line) if line > 0 => Ok(line),
+                    _ => Err(format!("Invalid line number: {}", line)),
+                })
+                .transpose()?;
+            Ok(DeepLinkAction::Open {
+                path: absolute_path(&path)?,
+                line,
+                heading: param("heading"),
+            })
+        }
+        "new" => {
+            let template = param("template");
+            if let Some(name) = &template {
+                let valid = name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                if !valid {
+                    return Err(format!("Invalid template name: {}", name));
+                }
+            }
+            Ok(DeepLinkAction::New { template })
+        }
+        _ => Ok(DeepLinkAction::Search {
+            query: param("q").ok_or("Missing parameter \"q\"")?,
+            path: param("path").map(|path| absolute_path(&path)).transpose()?,
+        }),
+    }
+}
+
+/// Converts a path parameter, which may also be a `file://` URL, to an absolute path.
+///
+/// Relative paths are rejected because a link has no working directory.
+fn absolute_path(value: &str) -> Result<PathBuf, String> {
+    let path = crate::extract_file_path(value);
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Err(format!("Path must be absolute: {}", value))
+    }
+}
+
+/// Reads a template from the templates directory in the app config dir.
+fn read_template(app: &AppHandle, name: &str) -> Result<String, String> {
+    let file = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join(TEMPLATES_DIR)
+        .join(format!("{}.md", name));
+    std::fs::read_to_string(&file).map_err(|_| format!("Template not found: {}", name))
+}
+
+/// Handles deep links received at launch or while running.
+///
+/// Invalid links are reported in a dialog.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        eprintln!("[deep-link] Received: {}", url);
+        if let Err(e) = parse(&url).and_then(|action| dispatch(app, action)) {
+            app.dialog()
+                .message(format!(
+                    "The link \"{}\" could not be opened.\n\n{}",
+                    url, e
+                ))
+                .title("Invalid Link")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+        }
+    }
+}
+
+/// Carries out a deep link action in the focused window and brings it to the front.
+///
+/// Files go through the same path as command-line arguments.
+fn dispatch(app: &AppHandle, action: DeepLinkAction) -> Result<(), String> {
+    let window = crate::windows::focused_window(app).ok_or("No window to open the link in")?;
+    let label = window.label().to_string();
+
+    match action {
+        DeepLinkAction::Open {
+            path,
+            line,
+            heading,
+        } => {
+            if !path.is_file() {
+                return Err(format!("File not found: {}", path.display()));
+            }
+            let path = path.to_string_lossy().to_string();
+            crate::open_paths(app, &label, vec![path.clone()]);
+            if line.is_some() || heading.is_some() {
+                crate::queue_navigation(
+                    app,
+                    &label,
+                    crate::NavigateRequest {
+                        path,
+                        line,
+                        column: None,
+                        heading,
+                        readonly: false,
+                    },
+                );
+            }
+        }
+        DeepLinkAction::New { template } => {
+            let content = match template {
+                Some(name) => read_template(app, &name)?,
+                None => String::new(),
+            };
+            crate::queue_untitled(
+                app,
+                &label,
+                crate::UntitledDocument {
+                    content,
+                    title: None,
+                },
+            );
+        }
+        DeepLinkAction::Search { query, path } => {
+            let _ = app.emit_to(
+                &label,
+                "search-requested",
+                SearchRequest {
+                    query,
+                    path: path.map(|path| path.to_string_lossy().to_string()),
+                },
+            );
+        }
+    }
+
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(url: &str) -> Result<DeepLinkAction, String> {
+        parse(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_is_deep_link() {
+        assert!(is_deep_link("marklens://open?path=/a.md"));
+        assert!(is_deep_link("MarkLens://new"));
+        assert!(!is_deep_link("/home/me/notes.md"));
+        assert!(!is_deep_link("file:///home/me/notes.md"));
+    }
+
+    #[test]
+    fn test_parse_open() {
+        let path = if cfg!(windows) {
+            "C:\\notes\\My Notes.md"
+        } else {
+            "/notes/My Notes.md"
+        };
+        let url = Url::parse_with_params(
+            "marklens://open",
+            &[("path", path), ("line", "42"), ("heading", "Install")],
+        )
+        .unwrap();
+        assert_eq!(
+            parse(&url).unwrap(),
+            DeepLinkAction::Open {
+                path: PathBuf::from(path),
+                line: Some(42),
+                heading: Some("Install".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_open_validation() {
+        assert!(parse_str("marklens://open").is_err());
+        assert!(parse_str("marklens://open?path=notes.md").is_err());
+        assert!(parse_str("marklens://open?path=/a.md&line=0").is_err());
+        assert!(parse_str("marklens://open?path=/a.md&line=abc").is_err());
+        assert!(parse_str("marklens://open?path=/a.md&path=/b.md").is_err());
+        assert!(parse_str("marklens://open?path=/a.md&cmd=rm").is_err());
+    }
+
+    #[test]
+    fn test_parse_new() {
+        assert_eq!(
+            parse_str("marklens://new").unwrap(),
+            DeepLinkAction::New { template: None }
+        );
+        assert_eq!(
+            parse_str("marklens:new?template=meeting-notes").unwrap(),
+            DeepLinkAction::New {
+                template: Some("meeting-notes".to_string())
+            }
+        );
+        assert!(parse_str("marklens://new?template=../secrets").is_err());
+    }
+
+    #[test]
+    fn test_parse_search() {
+        assert_eq!(
+            parse_str("marklens://search?q=todo%20list").unwrap(),
+            DeepLinkAction::Search {
+                query: "todo list".to_string(),
+                path: None,
+            }
+        );
+        assert!(parse_str("marklens://search").is_err());
+        assert!(parse_str("marklens://search?q=x&path=relative").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_action_and_scheme() {
+        assert!(parse_str("marklens://delete?path=/a.md").is_err());
+        assert!(parse_str("https://open?path=/a.md").is_err());
+    }
+}
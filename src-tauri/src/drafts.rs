@@ -0,0 +1,455 @@
+use crate::fs::content_hash;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Directory inside the app data dir that holds the draft journal
+const DRAFTS_DIR: &str = "drafts";
+
+/// Identifies the running process so its own drafts are not offered for recovery
+pub struct DraftSession {
+    pub id: String,
+    /// Lock held while this process runs, so other instances leave its
+    /// drafts alone
+    lock: Mutex<Option<File>>,
+}
+
+impl DraftSession {
+    /// Takes the session lock in the journal, if not already held.
+    fn hold_lock(&self, dir: &Path) -> Result<(), String> {
+        let mut lock = self.lock.lock().unwrap();
+        if lock.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(session_lock_file(dir, &self.id)?)
+                .map_err(|e| e.to_string())?;
+            file.lock().map_err(|e| e.to_string())?;
+            *lock = Some(file);
+        }
+        Ok(())
+    }
+
+    /// Releases the session lock and removes its file, if held.
+    fn release_lock(&self, dir: &Path) -> Result<(), String> {
+        if self.lock.lock().unwrap().take().is_some() {
+            fs::remove_file(session_lock_file(dir, &self.id)?).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for DraftSession {
+    fn default() -> Self {
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        Self {
+            id: format!("{}-{}", started, std::process::id()),
+            lock: Mutex::new(None),
+        }
+    }
+}
+
+/// Snapshot of an unsaved buffer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    /// Document id assigned by the frontend
+    pub id: String,
+    /// Original file path, `None` for untitled documents
+    pub path: Option<String>,
+    pub name: String,
+    pub content: String,
+    /// Fingerprint of the on-disk content the buffer was based on
+    pub base_hash: Option<String>,
+    pub session: String,
+    pub saved_at: u64,
+}
+
+/// State of a draft's original file compared to when the draft was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskStatus {
+    /// File on disk still matches the draft's base
+    Unchanged,
+    /// File on disk was changed after the draft was taken
+    Modified,
+    /// File no longer exists
+    Missing,
+    /// Draft has no file on disk
+    Untitled,
+}
+
+/// Line of a diff between the file on disk and a draft
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// One of `equal`, `insert` or `delete`
+    pub kind: String,
+    pub text: String,
+}
+
+/// Draft left behind by a previous session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverableDraft {
+    pub draft: Draft,
+    pub disk_status: DiskStatus,
+    /// Changes needed to turn the file on disk into the draft
+    pub diff: Vec<DiffLine>,
+}
+
+/// Get the draft journal directory, creating it if needed
+fn drafts_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(DRAFTS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Get the journal file for a draft id
+///
+/// Ids are used as file names, so only ASCII alphanumerics, `-` and `_` are accepted.
+fn draft_file(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid draft id: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// Get the lock file of a session
+fn session_lock_file(dir: &Path, session: &str) -> Result<PathBuf, String> {
+    Ok(draft_file(dir, session)?.with_extension("lock"))
+}
+
+/// Checks whether another running instance still holds a session's lock.
+///
+/// Sessions that never took a lock, e.g. from older versions, count as ended.
+fn is_session_live(dir: &Path, session: &str) -> bool {
+    let Ok(file) = session_lock_file(dir, session)
+        .and_then(|path| File::open(path).map_err(|e| e.to_string()))
+    else {
+        return false;
+    };
+    matches!(file.try_lock(), Err(TryLockError::WouldBlock))
+}
+
+/// Removes the lock files of sessions that ended without cleaning up,
+/// e.g. after a crash. Locks still held by running instances are kept.
+fn remove_stale_locks(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_none_or(|ext| ext != "lock") {
+            continue;
+        }
+        let stale = File::open(&path).is_ok_and(|file| file.try_lock().is_ok());
+        if stale {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Write a draft to the journal, replacing any previous snapshot with the same id
+fn write_draft(dir: &Path, draft: &Draft) -> Result<(), String> {
+    let file = draft_file(dir, &draft.id)?;
+    let json = serde_json::to_string(draft).map_err(|e| e.to_string())?;
+
+    // Write to a temporary file first so a crash mid-write keeps the previous snapshot
+    let tmp = file.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &file).map_err(|e| e.to_string())
+}
+
+/// Read a single draft from the journal
+fn read_draft(dir: &Path, id: &str) -> Result<Draft, String> {
+    let content = fs::read_to_string(draft_file(dir, id)?).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Read all drafts in the journal, skipping unreadable entries
+fn read_drafts(dir: &Path) -> Vec<Draft> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut drafts: Vec<Draft> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    drafts.sort_by_key(|draft| std::cmp::Reverse(draft.saved_at));
+    drafts
+}
+
+/// Remove a draft from the journal; missing drafts are not an error
+fn remove_draft(dir: &Path, id: &str) -> Result<(), String> {
+    match fs::remove_file(draft_file(dir, id)?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Compare a draft against its original file on disk
+fn inspect_draft(draft: Draft) -> RecoverableDraft {
+    let disk_content = draft
+        .path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok());
+
+    let disk_status = match (&draft.path, &disk_content) {
+        (None, _) => DiskStatus::Untitled,
+        (Some(_), None) => DiskStatus::Missing,
+        (Some(_), Some(content)) => match &draft.base_hash {
+            Some(base) if *base == content_hash(content) => DiskStatus::Unchanged,
+            _ => DiskStatus::Modified,
+        },
+    };
+
+    let diff = diff_lines(disk_content.as_deref().unwrap_or(""), &draft.content);
+
+    RecoverableDraft {
+        draft,
+        disk_status,
+        diff,
+    }
+}
+
+/// Line-based diff from `old` to `new`
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            kind: match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Insert => "insert",
+                ChangeTag::Delete => "delete",
+            }
+            .to_string(),
+            text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+        })
+        .collect()
+}
+
+/// Save a snapshot of an unsaved buffer to the draft journal
+#[tauri::command]
+pub fn save_draft(
+    app: AppHandle,
+    id: String,
+    path: Option<String>,
+    name: String,
+    content: String,
+    base_hash: Option<String>,
+) -> Result<(), String> {
+    let session = app.state::<DraftSession>();
+    let dir = drafts_dir(&app)?;
+    session.hold_lock(&dir)?;
+    let draft = Draft {
+        id,
+        path,
+        name,
+        content,
+        base_hash,
+        session: session.id.clone(),
+        saved_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    write_draft(&dir, &draft)
+}
+
+/// Remove a draft, e.g. after its buffer was saved or closed without saving
+#[tauri::command]
+pub fn discard_draft(app: AppHandle, id: String) -> Result<(), String> {
+    remove_draft(&drafts_dir(&app)?, &id)
+}
+
+/// List drafts left behind by previous sessions, with their state on disk
+///
+/// Drafts of other instances that are still running are not included.
+#[tauri::command]
+pub fn get_recoverable_drafts(app: AppHandle) -> Result<Vec<RecoverableDraft>, String> {
+    let session = app.state::<DraftSession>();
+    let dir = drafts_dir(&app)?;
+    remove_stale_locks(&dir);
+    Ok(read_drafts(&dir)
+        .into_iter()
+        .filter(|draft| draft.session != session.id && !is_session_live(&dir, &draft.session))
+        .map(inspect_draft)
+        .collect())
+}
+
+/// Restore a draft from a previous session
+///
+/// The draft is adopted by the current session so it stays journaled
+/// until the restored buffer is saved or discarded.
+#[tauri::command]
+pub fn restore_draft(app: AppHandle, id: String) -> Result<Draft, String> {
+    let dir = drafts_dir(&app)?;
+    let mut draft = read_draft(&dir, &id)?;
+    if is_session_live(&dir, &draft.session) {
+        return Err("Draft is still being edited in another window".to_string());
+    }
+    let session = app.state::<DraftSession>();
+    session.hold_lock(&dir)?;
+    draft.session = session.id.clone();
+    write_draft(&dir, &draft)?;
+    Ok(draft)
+}
+
+/// Ends the draft session on a clean shutdown by removing its lock.
+///
+/// Drafts still in the journal stay recoverable by the next session.
+pub fn end_session(app: &AppHandle) {
+    let session = app.state::<DraftSession>();
+    let result = drafts_dir(app).and_then(|dir| session.release_lock(&dir));
+    if let Err(e) = result {
+        eprintln!("[drafts] Failed to remove session lock: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn draft(id: &str, path: Option<String>, content: &str, base_hash: Option<String>) -> Draft {
+        Draft {
+            id: id.to_string(),
+            path,
+            name: "test.md".to_string(),
+            content: content.to_string(),
+            base_hash,
+            session: "old".to_string(),
+            saved_at: 1234567890,
+        }
+    }
+
+    #[test]
+    fn test_draft_file_rejects_invalid_ids() {
+        let dir = Path::new("/drafts");
+        assert!(draft_file(dir, "1700000000000-abc123def").is_ok());
+        assert!(draft_file(dir, "").is_err());
+        assert!(draft_file(dir, "../escape").is_err());
+        assert!(draft_file(dir, "a/b").is_err());
+    }
+
+    #[test]
+    fn test_write_read_and_remove_draft() {
+        let dir = tempdir().unwrap();
+        let saved = draft("doc-1", None, "# Draft", None);
+
+        write_draft(dir.path(), &saved).unwrap();
+        let drafts = read_drafts(dir.path());
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "# Draft");
+        assert_eq!(read_draft(dir.path(), "doc-1").unwrap().id, "doc-1");
+
+        remove_draft(dir.path(), "doc-1").unwrap();
+        assert!(read_drafts(dir.path()).is_empty());
+        assert!(remove_draft(dir.path(), "doc-1").is_ok());
+    }
+
+    #[test]
+    fn test_write_draft_replaces_previous_snapshot() {
+        let dir = tempdir().unwrap();
+        write_draft(dir.path(), &draft("doc-1", None, "first", None)).unwrap();
+        write_draft(dir.path(), &draft("doc-1", None, "second", None)).unwrap();
+
+        let drafts = read_drafts(dir.path());
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "second");
+    }
+
+    #[test]
+    fn test_inspect_draft_disk_status() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.md");
+        fs::write(&file_path, "line 1\nline 2\n").unwrap();
+        let path = Some(file_path.to_string_lossy().to_string());
+
+        let base = Some(content_hash("line 1\nline 2\n"));
+        let unchanged = inspect_draft(draft("a", path.clone(), "line 1\nedited\n", base));
+        assert_eq!(unchanged.disk_status, DiskStatus::Unchanged);
+
+        let stale = Some(content_hash("older content"));
+        let modified = inspect_draft(draft("b", path, "line 1\n", stale));
+        assert_eq!(modified.disk_status, DiskStatus::Modified);
+
+        let missing_path = Some(dir.path().join("gone.md").to_string_lossy().to_string());
+        let missing = inspect_draft(draft("c", missing_path, "text", None));
+        assert_eq!(missing.disk_status, DiskStatus::Missing);
+
+        let untitled = inspect_draft(draft("d", None, "text", None));
+        assert_eq!(untitled.disk_status, DiskStatus::Untitled);
+    }
+
+    #[test]
+    fn test_session_lock_marks_live_sessions() {
+        let dir = tempdir().unwrap();
+        let session = DraftSession::default();
+        assert!(!is_session_live(dir.path(), &session.id));
+
+        session.hold_lock(dir.path()).unwrap();
+        assert!(is_session_live(dir.path(), &session.id));
+        assert!(!is_session_live(dir.path(), "1-1"));
+        assert!(!is_session_live(dir.path(), "../escape"));
+
+        // Lock files are not mistaken for drafts
+        assert!(read_drafts(dir.path()).is_empty());
+
+        drop(session);
+        let ended = DraftSession {
+            id: "2-2".to_string(),
+            lock: Mutex::new(None),
+        };
+        ended.hold_lock(dir.path()).unwrap();
+        drop(ended);
+        assert!(!is_session_live(dir.path(), "2-2"));
+    }
+
+    #[test]
+    fn test_lock_files_are_removed_once_sessions_end() {
+        let dir = tempdir().unwrap();
+        let running = DraftSession::default();
+        running.hold_lock(dir.path()).unwrap();
+        let lock_file = session_lock_file(dir.path(), &running.id).unwrap();
+
+        // A crashed session leaves an unlocked lock file behind
+        let stale = session_lock_file(dir.path(), "1-1").unwrap();
+        File::create(&stale).unwrap();
+
+        remove_stale_locks(dir.path());
+        assert!(!stale.exists());
+        assert!(lock_file.exists());
+
+        running.release_lock(dir.path()).unwrap();
+        assert!(!lock_file.exists());
+        assert!(!is_session_live(dir.path(), &running.id));
+        assert!(running.release_lock(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc\n", "a\nB\nc\n");
+        let kinds: Vec<&str> = diff.iter().map(|l| l.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["equal", "delete", "insert", "equal"]);
+        assert_eq!(diff[1].text, "b");
+        assert_eq!(diff[2].text, "B");
+    }
+}
@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Syntax of a front matter block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrontMatterFormat {
+    /// Between `---` lines, as used by Jekyll
+    #[default]
+    Yaml,
+    /// Between `+++` lines, as used by Hugo
+    Toml,
+    /// A JSON object, optionally between `;;;` lines
+    Json,
+}
+
+/// Metadata block at the start of a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrontMatter {
+    pub format: FrontMatterFormat,
+    /// Fields in their original order, `null` if the block is invalid
+    pub data: Value,
+    /// Why the block couldn't be parsed
+    pub error: Option<String>,
+    /// Number of lines taken by the block, including its delimiters
+    pub lines: usize,
+}
+
+/// Document split into front matter and Markdown body
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParsedDocument {
+    pub front_matter: Option<FrontMatter>,
+    pub body: String,
+}
+
+/// Location of a front matter block
+struct Block {
+    format: FrontMatterFormat,
+    /// Text between the delimiters
+    data: Range<usize>,
+    /// Start of the body, after the closing delimiter's line ending
+    end: usize,
+    /// Opening and closing delimiter lines, if any
+    delimiters: Option<(&'static str, String)>,
+}
+
+/// Splits `content` into lines with their byte ranges, without line endings.
+fn lines(content: &str) -> impl Iterator<Item = (&str, Range<usize>)> + '_ {
+    content.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((line.trim_end_matches(['\n', '\r']), start..*offset))
+    })
+}
+
+/// Finds the front matter block at the start of `content`.
+fn find_block(content: &str) -> Option<Block> {
+    let mut lines = lines(content);
+    let (first, first_range) = lines.next()?;
+    let (format, closing): (FrontMatterFormat, &[&str]) = match first.trim_end() {
+        "---" => (FrontMatterFormat::Yaml, &["---", "..."]),
+        "+++" => (FrontMatterFormat::Toml, &["+++"]),
+        ";;;" => (FrontMatterFormat::Json, &[";;;"]),
+        _ if first.starts_with('{') => return find_json_object(content),
+        _ => return None,
+    };
+    let (close, close_range) = lines.find(|(line, _)| closing.contains(&line.trim_end()))?;
+    let block = Block {
+        format,
+        data: first_range.end..close_range.start,
+        end: close_range.end,
+        delimiters: Some((closing[0], close.trim_end().to_string())),
+    };
+
+    // A document that starts with a thematic break isn't front matter, but
+    // an invalid block should still be reported as one
+    if format == FrontMatterFormat::Yaml {
+        if let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content[block.data.clone()]) {
+            if !value.is_mapping() && !value.is_null() {
+                return None;
+            }
+        }
+    }
+    Some(block)
+}
+
+/// Finds a JSON object that starts the document, ending on its own line.
+fn find_json_object(content: &str) -> Option<Block> {
+    let mut stream = serde_json::Deserializer::from_str(content).into_iter::<Map<String, Value>>();
+    stream.next()?.ok()?;
+    let object_end = stream.byte_offset();
+    let rest = &content[object_end..];
+    let line_end = rest
+        .find('\n')
+        .map_or(content.len(), |i| object_end + i + 1);
+    if !content[object_end..line_end].trim().is_empty() {
+        return None;
+    }
+    Some(Block {
+        format: FrontMatterFormat::Json,
+        data: 0..object_end,
+        end: line_end,
+        delimiters: None,
+    })
+}
+
+/// Parses the text of a block into a JSON object.
+fn parse_data(format: FrontMatterFormat, text: &str) -> Result<Map<String, Value>, String> {
+    let value = match format {
+        FrontMatterFormat::Yaml => {
+            let value: serde_yaml::Value = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+            if value.is_null() {
+                return Ok(Map::new());
+            }
+            serde_json::to_value(value).map_err(|e| e.to_string())?
+        }
+        FrontMatterFormat::Toml => {
+            let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+            toml_to_json(toml::Value::Table(table))
+        }
+        FrontMatterFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string())?,
+    };
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err("Front matter must be a set of fields".to_string()),
+    }
+}
+
+/// Converts TOML to JSON, with dates as strings.
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Splits a document into its front matter and Markdown body.
+pub fn parse(content: &str) -> ParsedDocument {
+    let Some(block) = find_block(content) else {
+        return ParsedDocument {
+            front_matter: None,
+            body: content.to_string(),
+        };
+    };
+    let (data, error) = match parse_data(block.format, &content[block.data.clone()]) {
+        Ok(data) => (Value::Object(data), None),
+        Err(e) => (Value::Null, Some(e)),
+    };
+    ParsedDocument {
+        front_matter: Some(FrontMatter {
+            format: block.format,
+            data,
+            error,
+            lines: content[..block.end].lines().count(),
+        }),
+        body: content[block.end..].to_string(),
+    }
+}
+
+/// Byte offset where the Markdown body starts, `0` without front matter.
+pub fn body_offset(content: &str) -> usize {
+    find_block(content).map_or(0, |block| block.end)
+}
+
+/// Blanks out the front matter, so it isn't parsed as Markdown while line
+/// numbers stay the same.
+pub fn mask(content: &str) -> Cow<'_, str> {
+    match find_block(content) {
+        Some(block) => {
+            let lines = content[..block.end].lines().count();
+            Cow::Owned("\n".repeat(lines) + &content[block.end..])
+        }
+        None => Cow::Borrowed(content),
+    }
+}
+
+/// Updates front matter fields, keeping the format and the order of the
+/// existing fields.
+///
+/// New fields are added at the end and fields set to `null` are removed.
+/// Without front matter a block in `format` is added. Comments and layout
+/// are kept for TOML, while YAML and JSON are written out again.
+pub fn set_fields(
+    content: &str,
+    fields: Map<String, Value>,
+    format: FrontMatterFormat,
+) -> Result<String, String> {
+    let block = find_block(content);
+    let (format, text) = match &block {
+        Some(block) => (block.format, &content[block.data.clone()]),
+        None => (format, ""),
+    };
+
+    let data = match format {
+        FrontMatterFormat::Yaml => set_yaml_fields(text, fields)?,
+        FrontMatterFormat::Toml => set_toml_fields(text, fields)?,
+        FrontMatterFormat::Json => set_json_fields(text, fields)?,
+    };
+
+    let (open, close) = match &block {
+        Some(block) => block.delimiters.clone(),
+        None if format == FrontMatterFormat::Toml => Some(("+++", "+++".to_string())),
+        None if format == FrontMatterFormat::Yaml => Some(("---", "---".to_string())),
+        None => None,
+    }
+    .map_or((String::new(), String::new()), |(open, close)| {
+        (format!("{}\n", open), format!("{}\n", close))
+    });
+    let body = &content[block.as_ref().map_or(0, |block| block.end)..];
+    Ok(format!("{}{}{}{}", open, data, close, body))
+}
+
+fn set_yaml_fields(text: &str, fields: Map<String, Value>) -> Result<String, String> {
+    let mut mapping = match serde_yaml::from_str(text).map_err(|e| e.to_string())? {
+        serde_yaml::Value::Mapping(mapping) => mapping,
+        serde_yaml::Value::Null => serde_yaml::Mapping::new(),
+        _ => return Err("Front matter must be a set of fields".to_string()),
+    };
+    for (key, value) in fields {
+        if value.is_null() {
+            mapping.shift_remove(key.as_str());
+        } else {
+            let value = serde_yaml::to_value(value).map_err(|e| e.to_string())?;
+            mapping.insert(serde_yaml::Value::String(key), value);
+        }
+    }
+    if mapping.is_empty() {
+        return Ok(String::new());
+    }
+    serde_yaml::to_string(&mapping).map_err(|e| e.to_string())
+}
+
+fn set_toml_fields(text: &str, fields: Map<String, Value>) -> Result<String, String> {
+    let mut document: toml_edit::DocumentMut = text
+        .parse()
+        .map_err(|e: toml_edit::TomlError| e.to_string())?;
+    for (key, value) in fields {
+        if value.is_null() {
+            document.remove(&key);
+            continue;
+        }
+        let new = json_to_toml(&value)?;
+        match document.get_mut(&key) {
+            // Keep comments around an existing value
+            Some(toml_edit::Item::Value(existing)) => {
+                let decor = existing.decor().clone();
+                *existing = new;
+                *existing.decor_mut() = decor;
+            }
+            // Keep `[section]` style for existing tables
+            Some(item @ toml_edit::Item::Table(_)) => match new {
+                toml_edit::Value::InlineTable(table) => {
+                    *item = toml_edit::Item::Table(table.into_table())
+                }
+                new => *item = toml_edit::Item::Value(new),
+            },
+            _ => {
+                document.insert(&key, toml_edit::Item::Value(new));
+            }
+        }
+    }
+    let mut text = document.to_string();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Converts a JSON value to TOML, which has no `null`.
+fn json_to_toml(value: &Value) -> Result<toml_edit::Value, String> {
+    Ok(match value {
+        Value::Null => return Err("TOML front matter can't hold null values".to_string()),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => {
+            let mut array = toml_edit::Array::new();
+            for item in items {
+                array.push(json_to_toml(item)?);
+            }
+            array.into()
+        }
+        Value::Object(map) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, value) in map {
+                table.insert(key, json_to_toml(value)?);
+            }
+            table.into()
+        }
+    })
+}
+
+fn set_json_fields(text: &str, fields: Map<String, Value>) -> Result<String, String> {
+    let mut map = if text.trim().is_empty() {
+        Map::new()
+    } else {
+        parse_data(FrontMatterFormat::Json, text)?
+    };
+    for (key, value) in fields {
+        if value.is_null() {
+            map.shift_remove(&key);
+        } else {
+            map.insert(key, value);
+        }
+    }
+    serde_json::to_string_pretty(&map)
+        .map(|json| json + "\n")
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Splits a document into its front matter fields and Markdown body.
+#[tauri::command]
+pub fn parse_front_matter(content: String) -> ParsedDocument {
+    parse(&content)
+}
+
+/// Updates front matter fields and returns the new document.
+///
+/// `format` is used only when the document has no front matter yet.
+#[tauri::command]
+pub fn set_front_matter(
+    content: String,
+    fields: Map<String, Value>,
+    format: Option<FrontMatterFormat>,
+) -> Result<String, String> {
+    set_fields(&content, fields, format.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let yaml = parse("---\ntitle: Hello\ntags: [a, b]\ndraft: false\n---\n# Body\n");
+        let front_matter = yaml.front_matter.unwrap();
+        assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+        assert_eq!(
+            front_matter.data,
+            json!({ "title": "Hello", "tags": ["a", "b"], "draft": false })
+        );
+        assert_eq!(front_matter.lines, 5);
+        assert_eq!(yaml.body, "# Body\n");
+
+        let toml =
+            parse("+++\ntitle = \"Hello\"\ndate = 2024-05-01\n[params]\nweight = 3\n+++\nBody\n");
+        let front_matter = toml.front_matter.unwrap();
+        assert_eq!(front_matter.format, FrontMatterFormat::Toml);
+        assert_eq!(
+            front_matter.data,
+            json!({ "title": "Hello", "date": "2024-05-01", "params": { "weight": 3 } })
+        );
+
+        let json = parse(";;;\n{ \"title\": \"Hello\" }\n;;;\nBody\n");
+        assert_eq!(json.front_matter.unwrap().data, json!({ "title": "Hello" }));
+        let bare = parse("{\n  \"title\": \"Hello\"\n}\nBody\n");
+        assert_eq!(bare.front_matter.unwrap().format, FrontMatterFormat::Json);
+        assert_eq!(bare.body, "Body\n");
+    }
+
+    #[test]
+    fn test_parse_without_front_matter() {
+        for content in [
+            "# Title\n",
+            "---\n\nSome text\n\n---\n",
+            "{braces} in text\n",
+            "---\ntitle: never closed\n",
+        ] {
+            let parsed = parse(content);
+            assert!(parsed.front_matter.is_none(), "{:?}", content);
+            assert_eq!(parsed.body, content);
+        }
+
+        let invalid = parse("---\ntitle: [unclosed\n---\nBody\n");
+        let front_matter = invalid.front_matter.unwrap();
+        assert!(front_matter.error.is_some());
+        assert_eq!(front_matter.data, Value::Null);
+        assert_eq!(invalid.body, "Body\n");
+    }
+
+    #[test]
+    fn test_mask_keeps_line_numbers() {
+        let content = "---\ntitle: Hello\n---\n# Body\n";
+        assert_eq!(mask(content), "\n\n\n# Body\n");
+        assert_eq!(crate::markdown::headings(content)[0].line, 4);
+        assert_eq!(body_offset(content), 21);
+    }
+
+    #[test]
+    fn test_set_yaml_fields_keeps_order() {
+        let content = "---\ntitle: Old\ndate: 2024-01-01\ntags:\n- a\n---\nBody\n";
+        let updated = set_fields(
+            content,
+            fields(json!({ "title": "New", "draft": true, "tags": null })),
+            FrontMatterFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "---\ntitle: New\ndate: 2024-01-01\ndraft: true\n---\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_set_toml_fields_keeps_comments() {
+        let content = "+++\n# Page title\ntitle = \"Old\" # shown in lists\nweight = 1\n\n[params]\nauthor = \"A\"\n+++\nBody\n";
+        let updated = set_fields(
+            content,
+            fields(json!({ "title": "New", "weight": null, "params": { "author": "B" } })),
+            FrontMatterFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "+++\n# Page title\ntitle = \"New\" # shown in lists\n\n[params]\nauthor = \"B\"\n+++\nBody\n"
+        );
+        assert!(set_fields(
+            content,
+            fields(json!({ "x": [null] })),
+            FrontMatterFormat::Yaml
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_set_json_fields() {
+        let content = ";;;\n{ \"b\": 1, \"a\": 2 }\n;;;\nBody\n";
+        let updated = set_fields(
+            content,
+            fields(json!({ "b": 3, "c": "x" })),
+            FrontMatterFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            ";;;\n{\n  \"b\": 3,\n  \"a\": 2,\n  \"c\": \"x\"\n}\n;;;\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_set_fields_adds_front_matter() {
+        let added = set_fields(
+            "# Title\n",
+            fields(json!({ "title": "Title" })),
+            FrontMatterFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(added, "+++\ntitle = \"Title\"\n+++\n# Title\n");
+        assert!(set_fields(
+            "---\ntitle: [unclosed\n---\n",
+            fields(json!({ "title": "x" })),
+            FrontMatterFormat::Yaml
+        )
+        .is_err());
+    }
+}
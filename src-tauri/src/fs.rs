@@ -1,6 +1,9 @@
+use crate::outline;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
+use tauri::AppHandle;
 
 /// Result of reading a file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +11,8 @@ pub struct ReadFileResult {
     pub path: String,
     pub name: String,
     pub content: String,
+    /// Fingerprint of `content`, see [`content_hash`]
+    pub hash: Option<String>,
     pub success: bool,
     pub error: Option<String>,
 }
@@ -16,6 +21,11 @@ pub struct ReadFileResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WriteFileResult {
     pub path: String,
+    /// Fingerprint of the written content, see [`content_hash`]
+    pub hash: Option<String>,
+    /// Content that was written, if it differs from the content sent,
+    /// e.g. after the table of contents was regenerated
+    pub content: Option<String>,
     pub success: bool,
     pub error: Option<String>,
 }
@@ -56,6 +66,13 @@ fn get_file_name(path: &str) -> String {
         .to_string()
 }
 
+/// Fingerprint of text content (hex-encoded SHA-256)
+///
+/// Used to detect whether a file changed on disk since it was loaded.
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
 /// Read text file content
 #[tauri::command]
 pub fn read_file(path: String) -> Result<ReadFileResult, String> {
@@ -65,6 +82,7 @@ pub fn read_file(path: String) -> Result<ReadFileResult, String> {
         Ok(content) => Ok(ReadFileResult {
             path,
             name,
+            hash: Some(content_hash(&content)),
             content,
             success: true,
             error: None,
@@ -73,6 +91,7 @@ pub fn read_file(path: String) -> Result<ReadFileResult, String> {
             path,
             name,
             content: String::new(),
+            hash: None,
             success: false,
             error: Some(e.to_string()),
         }),
@@ -80,19 +99,47 @@ pub fn read_file(path: String) -> Result<ReadFileResult, String> {
 }
 
 /// Write text content to file
+///
+/// Markdown files get their table of contents regenerated first if that is
+/// enabled in the settings.
 #[tauri::command]
-pub fn write_file(path: String, content: String) -> Result<WriteFileResult, String> {
-    match fs::write(&path, &content) {
-        Ok(()) => Ok(WriteFileResult {
+pub fn write_file(
+    app: AppHandle,
+    path: String,
+    content: String,
+) -> Result<WriteFileResult, String> {
+    let settings = crate::settings::current(&app);
+    let updated = if settings.update_toc_on_save
+        && crate::is_markdown_file(&path, &settings.markdown_extensions)
+    {
+        outline::refresh_toc(&content, &settings.toc).filter(|updated| *updated != content)
+    } else {
+        None
+    };
+    let mut result = write_text(path, updated.as_deref().unwrap_or(&content));
+    if result.success {
+        result.content = updated;
+    }
+    Ok(result)
+}
+
+/// Writes text content to a file, reporting failure in the result.
+fn write_text(path: String, content: &str) -> WriteFileResult {
+    match fs::write(&path, content) {
+        Ok(()) => WriteFileResult {
             path,
+            hash: Some(content_hash(content)),
+            content: None,
             success: true,
             error: None,
-        }),
-        Err(e) => Ok(WriteFileResult {
+        },
+        Err(e) => WriteFileResult {
             path,
+            hash: None,
+            content: None,
             success: false,
             error: Some(e.to_string()),
-        }),
+        },
     }
 }
 
@@ -285,6 +332,13 @@ mod tests {
         assert_eq!(get_file_name(""), "");
     }
 
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash("# Test"), content_hash("# Test"));
+        assert_ne!(content_hash("# Test"), content_hash("# Test\n"));
+        assert_eq!(content_hash("").len(), 64);
+    }
+
     #[test]
     fn test_read_file_success() {
         let dir = tempdir().unwrap();
@@ -299,6 +353,7 @@ mod tests {
         assert!(result.success);
         assert_eq!(result.content, "# Hello World");
         assert_eq!(result.name, "test.md");
+        assert_eq!(result.hash, Some(content_hash("# Hello World")));
         assert!(result.error.is_none());
     }
 
@@ -318,13 +373,8 @@ mod tests {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("test.md");
 
-        let result = write_file(
-            file_path.to_string_lossy().to_string(),
-            "# Test".to_string(),
-        );
+        let result = write_text(file_path.to_string_lossy().to_string(), "# Test");
 
-        assert!(result.is_ok());
-        let result = result.unwrap();
         assert!(result.success);
         assert!(result.error.is_none());
 
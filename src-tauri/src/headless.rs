@@ -0,0 +1,482 @@
+use crate::outline::{self, TocOptions};
+use crate::sanitize::TrustLevel;
+use crate::settings::Settings;
+use crate::{front_matter, lint, markdown};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Exit status when a command ran and found nothing to report
+const EXIT_OK: i32 = 0;
+
+/// Exit status when `lint` found problems
+const EXIT_ISSUES: i32 = 1;
+
+/// Exit status when a command failed
+const EXIT_ERROR: i32 = 2;
+
+/// Browsers that can print HTML to PDF in headless mode, by executable name
+const BROWSERS: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "microsoft-edge",
+    "msedge",
+    "chrome",
+];
+
+/// Commands that run without a window
+#[derive(Debug, Parser)]
+#[command(name = "mark-lens", version)]
+struct HeadlessCli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Render Markdown to an HTML fragment
+    Render {
+        /// Markdown file, or `-` for stdin
+        input: PathBuf,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Keep inline styles, classes and media in raw HTML
+        #[arg(long)]
+        trusted: bool,
+    },
+    /// Export a standalone HTML document, or a PDF with --pdf
+    Export {
+        /// Markdown file, or `-` for stdin
+        input: PathBuf,
+        /// Write to a file instead of stdout; defaults to the input with a
+        /// `.pdf` extension for --pdf
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Print to PDF with a headless Chromium-based browser
+        #[arg(long)]
+        pdf: bool,
+        /// Keep inline styles, classes and media in raw HTML
+        #[arg(long)]
+        trusted: bool,
+    },
+    /// Count words, headings, links and other elements
+    Stats {
+        /// Markdown file, or `-` for stdin
+        input: PathBuf,
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check files for common problems; exits with 1 when any are found
+    Lint {
+        /// Markdown files, or `-` for stdin
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a table of contents as a nested Markdown list
+    Toc {
+        /// Markdown file, or `-` for stdin
+        input: PathBuf,
+        /// Highest heading level to include [default: from the settings]
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6))]
+        min_level: Option<u8>,
+        /// Lowest heading level to include [default: from the settings]
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6))]
+        max_level: Option<u8>,
+        /// Print a numbered instead of a bulleted list
+        #[arg(long)]
+        ordered: bool,
+    },
+}
+
+/// Runs a headless subcommand if the first argument names one.
+///
+/// Returns the exit status, or `None` to start the application as usual.
+pub fn run_command() -> Option<i32> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let first = args.get(1)?.to_str()?.to_string();
+    let is_command = HeadlessCli::command()
+        .get_subcommands()
+        .any(|command| command.get_name() == first.as_str());
+    if !is_command {
+        return None;
+    }
+
+    #[cfg(windows)]
+    attach_console();
+
+    let cli = match HeadlessCli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return Some(e.exit_code());
+        }
+    };
+    let mut stdout = std::io::stdout().lock();
+    Some(match execute(cli.command, &mut stdout) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("mark-lens {}: {}", first, e);
+            EXIT_ERROR
+        }
+    })
+}
+
+/// Shows output in the terminal that started us, since release builds on
+/// Windows have no console of their own.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Runs a command, writing results to `out` unless it names an output file.
+fn execute(command: Command, out: &mut dyn Write) -> Result<i32, String> {
+    match command {
+        Command::Render {
+            input,
+            output,
+            trusted,
+        } => {
+            let html = markdown::render_html(&read_input(&input)?, trust_level(trusted));
+            write_output(output.as_deref(), &html, out)?;
+        }
+        Command::Export {
+            input,
+            output,
+            pdf,
+            trusted,
+        } => {
+            let content = read_input(&input)?;
+            let html = markdown::standalone_html(
+                &document_title(&content, &input),
+                &markdown::render_html(&content, trust_level(trusted)),
+            );
+            if pdf {
+                let output = match output {
+                    Some(output) => output,
+                    None if !is_stdin(&input) => input.with_extension("pdf"),
+                    None => return Err("--output is required for PDF from stdin".to_string()),
+                };
+                export_pdf(&html, &input, &output)?;
+            } else {
+                write_output(output.as_deref(), &html, out)?;
+            }
+        }
+        Command::Stats { input, json } => {
+            let stats = markdown::stats(&read_input(&input)?);
+            let text = if json {
+                serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())? + "\n"
+            } else {
+                format!(
+                    "Words:        {}\nCharacters:   {}\nLines:        {}\nHeadings:     {}\nLinks:        {}\nImages:       {}\nCode blocks:  {}\nReading time: {} min\n",
+                    stats.words,
+                    stats.characters,
+                    stats.lines,
+                    stats.headings,
+                    stats.links,
+                    stats.images,
+                    stats.code_blocks,
+                    stats.reading_minutes
+                )
+            };
+            write_output(None, &text, out)?;
+        }
+        Command::Lint { inputs, json } => {
+            let mut found = false;
+            let mut report = Vec::new();
+            for input in &inputs {
+                let issues = lint::lint(&read_input(input)?);
+                found |= !issues.is_empty();
+                report.push((input.to_string_lossy().to_string(), issues));
+            }
+
+            let text = if json {
+                let report: serde_json::Map<String, serde_json::Value> = report
+                    .into_iter()
+                    .map(|(file, issues)| (file, serde_json::json!(issues)))
+                    .collect();
+                serde_json::to_string_pretty(&report).map_err(|e| e.to_string())? + "\n"
+            } else {
+                report
+                    .iter()
+                    .flat_map(|(file, issues)| {
+                        issues.iter().map(move |issue| {
+                            format!(
+                                "{}:{}: {} {}\n",
+                                file, issue.line, issue.rule, issue.message
+                            )
+                        })
+                    })
+                    .collect()
+            };
+            write_output(None, &text, out)?;
+            return Ok(if found { EXIT_ISSUES } else { EXIT_OK });
+        }
+        Command::Toc {
+            input,
+            min_level,
+            max_level,
+            ordered,
+        } => {
+            // Same defaults as the app, which reads them from `settings.json`
+            let defaults = Settings::default().toc;
+            let options = TocOptions {
+                min_level: min_level.unwrap_or(defaults.min_level),
+                max_level: max_level.unwrap_or(defaults.max_level),
+                ordered: ordered || defaults.ordered,
+            };
+            options.validate()?;
+            let toc = outline::toc_list(&read_input(&input)?, &options);
+            write_output(None, &toc, out)?;
+        }
+    }
+    Ok(EXIT_OK)
+}
+
+/// Sanitization policy for the `--trusted` flag.
+fn trust_level(trusted: bool) -> TrustLevel {
+    if trusted {
+        TrustLevel::Trusted
+    } else {
+        TrustLevel::Untrusted
+    }
+}
+
+/// Checks whether an input argument means stdin.
+fn is_stdin(input: &Path) -> bool {
+    input == Path::new("-")
+}
+
+/// Reads a file, or stdin for `-`.
+fn read_input(input: &Path) -> Result<String, String> {
+    if is_stdin(input) {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| e.to_string())?;
+        Ok(content)
+    } else {
+        std::fs::read_to_string(input).map_err(|e| format!("{}: {}", input.display(), e))
+    }
+}
+
+/// Writes to a file if one is given, otherwise to `out`.
+fn write_output(output: Option<&Path>, text: &str, out: &mut dyn Write) -> Result<(), String> {
+    match output {
+        Some(path) => std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e)),
+        None => out.write_all(text.as_bytes()).map_err(|e| e.to_string()),
+    }
+}
+
+/// Title for an exported document: the front matter title, its first
+/// top-level heading, or the file name.
+fn document_title(content: &str, input: &Path) -> String {
+    front_matter::parse(content)
+        .front_matter
+        .and_then(|front_matter| front_matter.data.get("title")?.as_str().map(str::to_string))
+        .or_else(|| {
+            markdown::headings(content)
+                .into_iter()
+                .find(|h| h.level == 1)
+                .map(|h| h.text)
+        })
+        .or_else(|| {
+            input
+                .file_stem()
+                .filter(|_| !is_stdin(input))
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "Untitled".to_string())
+}
+
+/// Prints an exported HTML document to PDF with a headless browser.
+///
+/// The HTML goes to a temporary file, so relative image paths are resolved
+/// through a `<base>` pointing at the input's folder.
+fn export_pdf(html: &str, input: &Path, output: &Path) -> Result<(), String> {
+    let browser = find_browser().ok_or(
+        "No Chromium-based browser found; install Chrome, Chromium or Edge, or set MARK_LENS_BROWSER",
+    )?;
+
+    let html = match input
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+    {
+        Some(dir) if !is_stdin(input) => match Url::from_directory_path(&dir) {
+            Ok(base) => html.replacen(
+                "<head>\n",
+                &format!(
+                    "<head>\n<base href=\"{}\">\n",
+                    markdown::escape_html(base.as_str())
+                ),
+                1,
+            ),
+            Err(_) => html.to_string(),
+        },
+        _ => html.to_string(),
+    };
+    let page =
+        std::env::temp_dir().join(format!("mark-lens-export-{}.html", crate::cli::unique_id()));
+    std::fs::write(&page, html).map_err(|e| e.to_string())?;
+    let page_url = Url::from_file_path(&page).map_err(|_| "Invalid temporary path".to_string())?;
+
+    let output = std::path::absolute(output).map_err(|e| e.to_string())?;
+    let status = std::process::Command::new(&browser)
+        .arg("--headless")
+        .arg("--disable-gpu")
+        .arg("--no-pdf-header-footer")
+        .arg(format!("--print-to-pdf={}", output.display()))
+        .arg(page_url.as_str())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    let _ = std::fs::remove_file(&page);
+
+    match status {
+        Ok(status) if status.success() && output.exists() => Ok(()),
+        Ok(status) => Err(format!("{} failed with {}", browser.display(), status)),
+        Err(e) => Err(format!("{}: {}", browser.display(), e)),
+    }
+}
+
+/// Finds a browser for PDF export: `MARK_LENS_BROWSER`, then `PATH`, then
+/// the usual install locations.
+fn find_browser() -> Option<PathBuf> {
+    if let Some(browser) = std::env::var_os("MARK_LENS_BROWSER") {
+        return Some(PathBuf::from(browser));
+    }
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    for dir in std::env::split_paths(&path) {
+        for name in BROWSERS {
+            let candidate = dir
+                .join(name)
+                .with_extension(std::env::consts::EXE_EXTENSION);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+
+    let installed: &[&str] = if cfg!(target_os = "macos") {
+        &[
+            "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+            "/Applications/Chromium.app/Contents/MacOS/Chromium",
+            "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        ]
+    } else if cfg!(windows) {
+        &[
+            r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+            r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+            r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+        ]
+    } else {
+        &[]
+    };
+    installed
+        .iter()
+        .map(PathBuf::from)
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn run(args: &[&str]) -> (i32, String) {
+        let cli =
+            HeadlessCli::try_parse_from(std::iter::once("mark-lens").chain(args.iter().copied()))
+                .unwrap();
+        let mut out = Vec::new();
+        let code = execute(cli.command, &mut out).unwrap();
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_render_to_file() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.md");
+        let output = dir.path().join("out.html");
+        std::fs::write(&input, "# Hello\n").unwrap();
+
+        let (code, out) = run(&[
+            "render",
+            input.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ]);
+        assert_eq!(code, EXIT_OK);
+        assert!(out.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "<h1>Hello</h1>\n"
+        );
+    }
+
+    #[test]
+    fn test_export_html_uses_heading_as_title() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("notes.md");
+        std::fs::write(&input, "# Release & Notes\n\nText\n").unwrap();
+
+        let (_, out) = run(&["export", input.to_str().unwrap()]);
+        assert!(out.starts_with("<!DOCTYPE html>"));
+        assert!(out.contains("<title>Release &amp; Notes</title>"));
+
+        std::fs::write(&input, "---\ntitle: From front matter\n---\n# Heading\n").unwrap();
+        let (_, out) = run(&["export", input.to_str().unwrap()]);
+        assert!(out.contains("<title>From front matter</title>"));
+        assert!(!out.contains("<hr"));
+    }
+
+    #[test]
+    fn test_toc_and_stats() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("doc.md");
+        std::fs::write(&input, "## Intro\n\n### Setup\n\n## Usage\n").unwrap();
+        let input = input.to_str().unwrap();
+
+        let (_, toc) = run(&["toc", input]);
+        assert_eq!(
+            toc,
+            "- [Intro](#intro)\n  - [Setup](#setup)\n- [Usage](#usage)\n"
+        );
+
+        let (_, toc) = run(&["toc", input, "--max-level", "2", "--ordered"]);
+        assert_eq!(toc, "1. [Intro](#intro)\n1. [Usage](#usage)\n");
+
+        let (_, stats) = run(&["stats", input, "--json"]);
+        let stats: serde_json::Value = serde_json::from_str(&stats).unwrap();
+        assert_eq!(stats["headings"], 3);
+    }
+
+    #[test]
+    fn test_lint_exit_code() {
+        let dir = tempdir().unwrap();
+        let clean = dir.path().join("clean.md");
+        let messy = dir.path().join("messy.md");
+        std::fs::write(&clean, "# Title\n").unwrap();
+        std::fs::write(&messy, "# Title\n\n### Deep\n").unwrap();
+
+        assert_eq!(
+            run(&["lint", clean.to_str().unwrap()]),
+            (EXIT_OK, String::new())
+        );
+        let (code, out) = run(&["lint", messy.to_str().unwrap()]);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(out.contains(":3: heading-increment"));
+    }
+}
@@ -0,0 +1,517 @@
+use crate::menu::MenuCommand;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub(crate) const KEYBINDINGS_FILE: &str = "keybindings.json";
+
+/// Commands handled by the backend that can also be bound to keys
+const APP_COMMANDS: &[&str] = &[
+    "new_window",
+    "open",
+    "close_window",
+    "quit",
+    "toggle_fullscreen",
+    "minimize",
+    "maximize",
+];
+
+/// Built-in key bindings, using Tauri accelerator syntax
+fn default_bindings() -> Vec<(&'static str, Option<&'static str>)> {
+    vec![
+        ("new_file", Some("CmdOrCtrl+N")),
+        ("new_window", Some("CmdOrCtrl+Shift+N")),
+        ("open", Some("CmdOrCtrl+O")),
+        ("save", Some("CmdOrCtrl+S")),
+        ("save_as", Some("CmdOrCtrl+Shift+S")),
+        ("export_html", None),
+        ("export_pdf", Some("CmdOrCtrl+P")),
+        ("close_window", Some("CmdOrCtrl+W")),
+        ("quit", Some("CmdOrCtrl+Q")),
+        ("find", Some("CmdOrCtrl+F")),
+        ("find_next", Some("CmdOrCtrl+G")),
+        ("find_previous", Some("CmdOrCtrl+Shift+G")),
+        ("replace", Some("CmdOrCtrl+Alt+F")),
+        ("view_edit", Some("CmdOrCtrl+1")),
+        ("view_preview", Some("CmdOrCtrl+2")),
+        ("view_split", Some("CmdOrCtrl+3")),
+        ("toggle_sidebar", Some("CmdOrCtrl+\\")),
+        ("zoom_in", Some("CmdOrCtrl+=")),
+        ("zoom_out", Some("CmdOrCtrl+-")),
+        ("zoom_reset", Some("CmdOrCtrl+0")),
+        ("reload", Some("CmdOrCtrl+R")),
+        #[cfg(target_os = "macos")]
+        ("toggle_fullscreen", Some("Ctrl+Cmd+F")),
+        #[cfg(not(target_os = "macos"))]
+        ("toggle_fullscreen", Some("F11")),
+        #[cfg(target_os = "macos")]
+        ("minimize", Some("Cmd+M")),
+        #[cfg(not(target_os = "macos"))]
+        ("minimize", None),
+        ("maximize", None),
+        ("keyboard_shortcuts", None),
+    ]
+}
+
+/// Checks whether a command id can be bound
+fn is_known_command(command: &str) -> bool {
+    APP_COMMANDS.contains(&command) || MenuCommand::from_id(command).is_some()
+}
+
+// ============================================================================
+// Accelerators
+// ============================================================================
+
+/// Named keys accepted in accelerators, besides letters, digits, punctuation and F1-F24
+const NAMED_KEYS: &[&str] = &[
+    "BACKSPACE",
+    "DELETE",
+    "END",
+    "ENTER",
+    "ESCAPE",
+    "HOME",
+    "INSERT",
+    "PAGEDOWN",
+    "PAGEUP",
+    "SPACE",
+    "TAB",
+    "UP",
+    "DOWN",
+    "LEFT",
+    "RIGHT",
+    "PLUS",
+];
+
+/// Normalizes an accelerator for the current platform.
+///
+/// Modifiers are resolved (`CmdOrCtrl` becomes `Cmd` on macOS and `Ctrl`
+/// elsewhere) and ordered, and the key is upper-cased, so that equal
+/// shortcuts compare equal. Returns an error describing invalid input.
+pub fn normalize_accelerator(accelerator: &str) -> Result<String, String> {
+    let accelerator = accelerator.trim();
+    if accelerator.is_empty() {
+        return Err("Accelerator is empty".to_string());
+    }
+
+    // "Ctrl++" binds the plus key
+    let (modifiers_str, key) = match accelerator.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => match accelerator.rsplit_once('+') {
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", accelerator),
+        },
+    };
+
+    let (mut ctrl, mut alt, mut shift, mut cmd) = (false, false, false, false);
+    if !modifiers_str.is_empty() {
+        for token in modifiers_str.split('+') {
+            match token.trim().to_uppercase().as_str() {
+                "CTRL" | "CONTROL" => ctrl = true,
+                "ALT" | "OPTION" => alt = true,
+                "SHIFT" => shift = true,
+                "CMD" | "COMMAND" | "SUPER" | "META" => cmd = true,
+                "CMDORCTRL" | "CMDORCONTROL" | "COMMANDORCTRL" | "COMMANDORCONTROL" => {
+                    if cfg!(target_os = "macos") {
+                        cmd = true;
+                    } else {
+                        ctrl = true;
+                    }
+                }
+                "" => return Err(format!("Empty modifier in \"{}\"", accelerator)),
+                other => return Err(format!("Unknown modifier \"{}\"", other)),
+            }
+        }
+    }
+
+    let key = normalize_key(key.trim())
+        .ok_or_else(|| format!("Unsupported key \"{}\" in \"{}\"", key.trim(), accelerator))?;
+
+    let mut parts = Vec::new();
+    if ctrl {
+        parts.push("Ctrl".to_string());
+    }
+    if alt {
+        parts.push("Alt".to_string());
+    }
+    if shift {
+        parts.push("Shift".to_string());
+    }
+    if cmd {
+        parts.push("Cmd".to_string());
+    }
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+/// Normalizes the key part of an accelerator, or `None` if it isn't supported
+fn normalize_key(key: &str) -> Option<String> {
+    let upper = key.to_uppercase();
+    let mut chars = upper.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let valid = c.is_ascii_alphanumeric() || "`\\[],=-.';/+".contains(c);
+        return valid.then(|| c.to_string());
+    }
+
+    let aliases = [
+        ("ESC", "ESCAPE"),
+        ("RETURN", "ENTER"),
+        ("ARROWUP", "UP"),
+        ("ARROWDOWN", "DOWN"),
+        ("ARROWLEFT", "LEFT"),
+        ("ARROWRIGHT", "RIGHT"),
+    ];
+    let upper = aliases
+        .iter()
+        .find(|(alias, _)| *alias == upper)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(upper);
+
+    if NAMED_KEYS.contains(&upper.as_str()) {
+        return Some(upper);
+    }
+
+    let is_function_key = upper
+        .strip_prefix('F')
+        .and_then(|n| n.parse::<u8>().ok())
+        .is_some_and(|n| (1..=24).contains(&n));
+    is_function_key.then_some(upper)
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Problem found while loading the user's key bindings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeybindingIssue {
+    pub command: String,
+    pub message: String,
+}
+
+/// Effective binding for a command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keybinding {
+    pub command: String,
+    /// Accelerator in Tauri syntax, `None` if the command is unbound
+    pub accelerator: Option<String>,
+    /// Whether the binding comes from the built-in defaults
+    pub is_default: bool,
+}
+
+/// Effective key bindings: defaults overridden by the user's `keybindings.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeybindingRegistry {
+    pub bindings: Vec<Keybinding>,
+    pub issues: Vec<KeybindingIssue>,
+}
+
+impl KeybindingRegistry {
+    /// Builds the registry from user overrides, validating them against
+    /// known commands, accelerator syntax and each other.
+    ///
+    /// A user binding that takes a default binding's shortcut unbinds the
+    /// default; two user bindings with the same shortcut keep the first
+    /// (by command id) and drop the other. Both cases are reported as issues.
+    pub fn from_overrides(overrides: &BTreeMap<String, Option<String>>) -> Self {
+        let mut issues = Vec::new();
+        let mut user: BTreeMap<String, Option<String>> = BTreeMap::new();
+
+        for (command, accelerator) in overrides {
+            if !is_known_command(command) {
+                issues.push(KeybindingIssue {
+                    command: command.clone(),
+                    message: "Unknown command".to_string(),
+                });
+                continue;
+            }
+            let accelerator = accelerator
+                .as_deref()
+                .map(str::trim)
+                .filter(|a| !a.is_empty());
+            match accelerator.map(normalize_accelerator) {
+                Some(Err(message)) => issues.push(KeybindingIssue {
+                    command: command.clone(),
+                    message,
+                }),
+                _ => {
+                    user.insert(command.clone(), accelerator.map(str::to_string));
+                }
+            }
+        }
+
+        // Resolve conflicts between user bindings
+        let mut taken: HashMap<String, String> = HashMap::new();
+        for (command, accelerator) in user.iter_mut() {
+            let Some(normalized) = accelerator
+                .as_deref()
+                .and_then(|a| normalize_accelerator(a).ok())
+            else {
+                continue;
+            };
+            if let Some(owner) = taken.get(&normalized) {
+                issues.push(KeybindingIssue {
+                    command: command.clone(),
+                    message: format!("Shortcut {} is already bound to {}", normalized, owner),
+                });
+                *accelerator = None;
+            } else {
+                taken.insert(normalized, command.clone());
+            }
+        }
+
+        let mut bindings = Vec::new();
+        for (command, default) in default_bindings() {
+            if let Some(accelerator) = user.get(command) {
+                bindings.push(Keybinding {
+                    command: command.to_string(),
+                    accelerator: accelerator.clone(),
+                    is_default: false,
+                });
+                continue;
+            }
+
+            // Defaults give way to user bindings on the same shortcut
+            let displaced_by = default
+                .and_then(|a| normalize_accelerator(a).ok())
+                .and_then(|normalized| taken.get(&normalized));
+            if let Some(owner) = displaced_by {
+                issues.push(KeybindingIssue {
+                    command: command.to_string(),
+                    message: format!("Default shortcut unbound, it is now used by {}", owner),
+                });
+            }
+            bindings.push(Keybinding {
+                command: command.to_string(),
+                accelerator: default
+                    .filter(|_| displaced_by.is_none())
+                    .map(str::to_string),
+                is_default: displaced_by.is_none(),
+            });
+        }
+
+        Self { bindings, issues }
+    }
+
+    /// Returns the accelerator bound to a command, if any
+    pub fn accelerator(&self, command: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.command == command)
+            .and_then(|binding| binding.accelerator.as_deref())
+    }
+}
+
+/// Path of the user's key bindings file
+fn keybindings_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(KEYBINDINGS_FILE))
+}
+
+/// Loads key bindings from a JSON file mapping command ids to accelerators.
+///
+/// A missing file yields the defaults; an unparsable file yields the
+/// defaults plus an issue describing the error.
+fn load_from_file(path: &Path) -> KeybindingRegistry {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return KeybindingRegistry::from_overrides(&BTreeMap::new()),
+    };
+
+    match serde_json::from_str::<BTreeMap<String, Option<String>>>(&content) {
+        Ok(overrides) => KeybindingRegistry::from_overrides(&overrides),
+        Err(e) => {
+            let mut registry = KeybindingRegistry::from_overrides(&BTreeMap::new());
+            registry.issues.push(KeybindingIssue {
+                command: String::new(),
+                message: format!("Invalid {}: {}", KEYBINDINGS_FILE, e),
+            });
+            registry
+        }
+    }
+}
+
+/// Loads the user's key bindings from the config directory.
+pub fn load_keybindings(app: &AppHandle) -> KeybindingRegistry {
+    let registry = match keybindings_path(app) {
+        Some(path) => load_from_file(&path),
+        None => KeybindingRegistry::from_overrides(&BTreeMap::new()),
+    };
+    for issue in &registry.issues {
+        eprintln!("[keybindings] {}: {}", issue.command, issue.message);
+    }
+    registry
+}
+
+/// Returns the accelerator bound to a command in the managed registry.
+pub fn accelerator(app: &AppHandle, command: &str) -> Option<String> {
+    app.state::<Mutex<KeybindingRegistry>>()
+        .lock()
+        .unwrap()
+        .accelerator(command)
+        .map(str::to_string)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the effective key bindings and any problems with the user's file.
+#[tauri::command]
+pub fn get_keybindings(app: AppHandle) -> KeybindingRegistry {
+    app.state::<Mutex<KeybindingRegistry>>()
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// Makes `registry` the effective bindings, rebuilds the menus and emits
+/// `keybindings-changed`.
+fn apply(app: &AppHandle, registry: &KeybindingRegistry) {
+    *app.state::<Mutex<KeybindingRegistry>>().lock().unwrap() = registry.clone();
+    crate::menu::refresh_recent_menus(app);
+    let _ = app.emit("keybindings-changed", registry);
+}
+
+/// Reloads `keybindings.json` after it was edited outside the app.
+///
+/// Nothing is rebuilt or emitted if the effective bindings are unchanged.
+pub fn reload_if_changed(app: &AppHandle) {
+    let registry = load_keybindings(app);
+    let changed = *app.state::<Mutex<KeybindingRegistry>>().lock().unwrap() != registry;
+    if changed {
+        apply(app, &registry);
+    }
+}
+
+/// Reloads `keybindings.json`, rebuilds the menus and emits `keybindings-changed`.
+#[tauri::command]
+pub fn reload_keybindings(app: AppHandle) -> KeybindingRegistry {
+    let registry = load_keybindings(&app);
+    apply(&app, &registry);
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn overrides(entries: &[(&str, Option<&str>)]) -> BTreeMap<String, Option<String>> {
+        entries
+            .iter()
+            .map(|(command, accelerator)| (command.to_string(), accelerator.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_accelerator() {
+        assert_eq!(
+            normalize_accelerator("shift+ctrl+s").unwrap(),
+            "Ctrl+Shift+S"
+        );
+        assert_eq!(normalize_accelerator("Alt+F4").unwrap(), "Alt+F4");
+        assert_eq!(normalize_accelerator("Ctrl++").unwrap(), "Ctrl++");
+        assert_eq!(normalize_accelerator("Ctrl+Esc").unwrap(), "Ctrl+ESCAPE");
+        assert_eq!(normalize_accelerator("F11").unwrap(), "F11");
+        #[cfg(target_os = "macos")]
+        assert_eq!(normalize_accelerator("CmdOrCtrl+O").unwrap(), "Cmd+O");
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(normalize_accelerator("CmdOrCtrl+O").unwrap(), "Ctrl+O");
+    }
+
+    #[test]
+    fn test_normalize_accelerator_invalid() {
+        assert!(normalize_accelerator("").is_err());
+        assert!(normalize_accelerator("Hyper+S").is_err());
+        assert!(normalize_accelerator("Ctrl+").is_err());
+        assert!(normalize_accelerator("Ctrl+F25").is_err());
+        assert!(normalize_accelerator("Ctrl+Banana").is_err());
+        assert!(normalize_accelerator("Ctrl++S").is_err());
+    }
+
+    #[test]
+    fn test_default_bindings_are_valid_and_unique() {
+        let registry = KeybindingRegistry::from_overrides(&BTreeMap::new());
+        assert!(registry.issues.is_empty());
+
+        let mut seen = std::collections::HashSet::new();
+        for binding in &registry.bindings {
+            assert!(is_known_command(&binding.command), "{}", binding.command);
+            if let Some(accelerator) = &binding.accelerator {
+                let normalized = normalize_accelerator(accelerator).unwrap();
+                assert!(seen.insert(normalized), "duplicate {}", accelerator);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_override_replaces_default() {
+        let registry =
+            KeybindingRegistry::from_overrides(&overrides(&[("find", Some("CmdOrCtrl+K"))]));
+        assert_eq!(registry.accelerator("find"), Some("CmdOrCtrl+K"));
+        assert!(registry.issues.is_empty());
+    }
+
+    #[test]
+    fn test_user_override_can_unbind() {
+        let registry = KeybindingRegistry::from_overrides(&overrides(&[("reload", None)]));
+        assert_eq!(registry.accelerator("reload"), None);
+    }
+
+    #[test]
+    fn test_user_override_displaces_conflicting_default() {
+        let registry =
+            KeybindingRegistry::from_overrides(&overrides(&[("find", Some("CmdOrCtrl+R"))]));
+        assert_eq!(registry.accelerator("find"), Some("CmdOrCtrl+R"));
+        assert_eq!(registry.accelerator("reload"), None);
+        assert_eq!(registry.issues.len(), 1);
+        assert_eq!(registry.issues[0].command, "reload");
+    }
+
+    #[test]
+    fn test_conflicting_user_overrides() {
+        let registry = KeybindingRegistry::from_overrides(&overrides(&[
+            ("find", Some("Ctrl+Shift+K")),
+            ("replace", Some("shift+ctrl+k")),
+        ]));
+        assert_eq!(registry.accelerator("find"), Some("Ctrl+Shift+K"));
+        assert_eq!(registry.accelerator("replace"), None);
+        assert_eq!(registry.issues.len(), 1);
+        assert_eq!(registry.issues[0].command, "replace");
+    }
+
+    #[test]
+    fn test_invalid_overrides_are_reported() {
+        let registry = KeybindingRegistry::from_overrides(&overrides(&[
+            ("no_such_command", Some("Ctrl+K")),
+            ("find", Some("Hyper+K")),
+        ]));
+        assert_eq!(registry.issues.len(), 2);
+        // Invalid override keeps the default
+        assert_eq!(registry.accelerator("find"), Some("CmdOrCtrl+F"));
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(KEYBINDINGS_FILE);
+
+        let registry = load_from_file(&path);
+        assert_eq!(registry.accelerator("save"), Some("CmdOrCtrl+S"));
+
+        std::fs::write(&path, r#"{ "save": "Ctrl+Alt+S" }"#).unwrap();
+        assert_eq!(
+            load_from_file(&path).accelerator("save"),
+            Some("Ctrl+Alt+S")
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        let registry = load_from_file(&path);
+        assert_eq!(registry.accelerator("save"), Some("CmdOrCtrl+S"));
+        assert_eq!(registry.issues.len(), 1);
+    }
+}
@@ -1,30 +1,108 @@
+mod cli;
+mod deep_link;
+mod drafts;
+mod front_matter;
 mod fs;
-
+mod headless;
+mod keybindings;
+mod lint;
+mod markdown;
+mod menu;
+mod outline;
+mod quick_open;
+mod replace;
+mod sanitize;
+mod search;
+mod search_index;
+mod settings;
+mod shutdown;
+mod wait;
+mod window_state;
+mod windows;
+mod workspace_watcher;
+
+use clap::Parser;
+use percent_encoding::percent_decode_str;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::{
-    menu::{Menu, MenuItem, Submenu},
-    AppHandle, Emitter, Manager, State, WebviewWindow, WindowEvent,
-};
-use tauri_plugin_dialog::DialogExt;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow, WindowEvent};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use url::Url;
+
+pub use headless::run_command;
+pub use menu::build_menu;
 
 const MAX_RECENT_FILES: usize = 10;
-const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd", "mkdn"];
+const MARKDOWN_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "mdown", "mkd", "mkdn", "mdx", "qmd", "rmd",
+];
+/// URL schemes whose URLs name local files
+const FILE_URL_SCHEMES: &[&str] = &["file", "markdown+file"];
+/// Number of bytes inspected when deciding whether a file is text
+const TEXT_SNIFF_LEN: usize = 8192;
 
 // ============================================================================
 // State
 // ============================================================================
 
-/// State for files opened at startup
+/// Position and mode for a file opened from the command line or a link
+#[derive(Debug, Clone, Serialize)]
+pub struct NavigateRequest {
+    pub path: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub heading: Option<String>,
+    pub readonly: bool,
+}
+
+/// Document that has no file yet, e.g. created from a template or read from stdin
+///
+/// Saving it goes through "Save As", with `title` as the suggested name.
+#[derive(Debug, Clone, Serialize)]
+pub struct UntitledDocument {
+    pub content: String,
+    pub title: Option<String>,
+}
+
+/// Launch requests for one window, kept until its frontend takes them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PendingLaunch {
+    pub paths: Vec<String>,
+    pub navigation: Vec<NavigateRequest>,
+    pub workspace: Option<String>,
+    pub untitled: Vec<UntitledDocument>,
+}
+
+/// State for files opened at startup or handed over later
+///
+/// Requests are queued per window label, since a window's frontend may not
+/// have loaded yet when they arrive.
+#[derive(Default)]
 pub struct StartupFiles {
-    pub paths: Mutex<Vec<String>>,
+    pending: Mutex<HashMap<String, PendingLaunch>>,
 }
 
-impl Default for StartupFiles {
-    fn default() -> Self {
-        Self {
-            paths: Mutex::new(Vec::new()),
-        }
+impl StartupFiles {
+    /// Changes the requests queued for a window.
+    fn update(&self, window: &str, f: impl FnOnce(&mut PendingLaunch)) {
+        f(self
+            .pending
+            .lock()
+            .unwrap()
+            .entry(window.to_string())
+            .or_default());
+    }
+
+    /// Removes and returns the requests queued for a window.
+    fn take(&self, window: &str) -> PendingLaunch {
+        self.pending
+            .lock()
+            .unwrap()
+            .remove(window)
+            .unwrap_or_default()
     }
 }
 
@@ -55,43 +133,30 @@ fn get_recent_files(app: AppHandle) -> Vec<RecentFile> {
 /// Adds a file to the recent files list and saves it to disk.
 ///
 /// If the file already exists in the list, it is moved to the top.
-/// The list is limited to [`MAX_RECENT_FILES`] entries.
+/// The list is limited to the `max_recent_files` setting.
 #[tauri::command]
 fn add_recent_file(app: AppHandle, path: String, name: String) {
-    let binding = app.state::<tokio::sync::Mutex<RecentFilesState>>();
-    let mut state = binding.blocking_lock();
-
-    // Remove if already exists
-    state.files.retain(|f| f.path != path);
-
-    // Add to front
-    state.files.insert(
-        0,
-        RecentFile {
-            path,
-            name,
-            last_opened: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        },
-    );
-
-    // Limit size
-    if state.files.len() > MAX_RECENT_FILES {
-        state.files.truncate(MAX_RECENT_FILES);
+    // Release the lock before rebuilding the menus, which read the list
+    {
+        let binding = app.state::<tokio::sync::Mutex<RecentFilesState>>();
+        let mut state = binding.blocking_lock();
+        let limit = settings::current(&app).max_recent_files;
+        push_recent_file(&mut state, path, name, limit);
+        save_recent_files(&app, &state);
     }
-
-    save_recent_files(&app, &state);
+    menu::refresh_recent_menus(&app);
 }
 
 /// Clears the recent files list and saves the empty state to disk.
 #[tauri::command]
 fn clear_recent_files(app: AppHandle) {
-    let binding = app.state::<tokio::sync::Mutex<RecentFilesState>>();
-    let mut state = binding.blocking_lock();
-    state.files.clear();
-    save_recent_files(&app, &state);
+    {
+        let binding = app.state::<tokio::sync::Mutex<RecentFilesState>>();
+        let mut state = binding.blocking_lock();
+        state.files.clear();
+        save_recent_files(&app, &state);
+    }
+    menu::refresh_recent_menus(&app);
 }
 
 /// Opens a file in the system's default editor for its file type.
@@ -100,42 +165,91 @@ fn open_in_default_editor(path: String) -> Result<(), String> {
     opener::open(&path).map_err(|e| e.to_string())
 }
 
-/// Returns the list of files passed at application startup.
+/// Returns and clears the files and other launch requests queued for the
+/// calling window.
 #[tauri::command]
-fn get_startup_files(state: State<StartupFiles>) -> Vec<String> {
-    state.paths.lock().unwrap().clone()
+fn take_startup_files(window: WebviewWindow, state: State<StartupFiles>) -> PendingLaunch {
+    state.take(window.label())
 }
 
-/// Clears the list of startup files.
+/// Sets the title of the calling window.
 #[tauri::command]
-fn clear_startup_files(state: State<StartupFiles>) {
-    let mut paths = state.paths.lock().unwrap();
-    paths.clear();
-}
-
-/// Sets the title of the main window.
-#[tauri::command]
-fn set_window_title(app: AppHandle, title: String) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.set_title(&title);
-    }
+fn set_window_title(window: WebviewWindow, title: String) {
+    let _ = window.set_title(&title);
 }
 
-/// Adds a file path to the startup files list if it's not already present.
+/// Queues a file for the focused window if it's not already queued.
+///
+/// The window is notified with an `add_to_startup_files` event.
 #[tauri::command]
 fn add_to_startup_files(app: AppHandle, path: String) {
-    let startup_files = app.state::<StartupFiles>();
-    let mut paths = startup_files.paths.lock().unwrap();
-    if !paths.contains(&path) {
-        paths.push(path.clone());
-    }
-    let _ = app.clone().emit("add_to_startup_files", &path);
+    let label = windows::focused_window(&app)
+        .map(|window| window.label().to_string())
+        .unwrap_or_else(|| windows::MAIN_WINDOW.to_string());
+    queue_file(&app, &label, path);
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// Queues a file for `window` and notifies its frontend.
+fn queue_file(app: &AppHandle, window: &str, path: String) {
+    app.state::<StartupFiles>().update(window, |pending| {
+        if !pending.paths.contains(&path) {
+            pending.paths.push(path.clone());
+        }
+    });
+    let _ = app.emit_to(window, "add_to_startup_files", &path);
+}
+
+/// Queues a navigation request and emits `navigate-requested` to `window`.
+fn queue_navigation(app: &AppHandle, window: &str, request: NavigateRequest) {
+    app.state::<StartupFiles>()
+        .update(window, |pending| pending.navigation.push(request.clone()));
+    let _ = app.emit_to(window, "navigate-requested", &request);
+}
+
+/// Queues a workspace folder and emits `workspace-open-requested` to `window`.
+fn queue_workspace(app: &AppHandle, window: &str, path: String) {
+    app.state::<StartupFiles>()
+        .update(window, |pending| pending.workspace = Some(path.clone()));
+    let _ = app.emit_to(window, "workspace-open-requested", &path);
+}
+
+/// Queues an untitled document and emits `new-document-requested` to `window`.
+fn queue_untitled(app: &AppHandle, window: &str, document: UntitledDocument) {
+    app.state::<StartupFiles>()
+        .update(window, |pending| pending.untitled.push(document.clone()));
+    let _ = app.emit_to(window, "new-document-requested", &document);
+}
+
+/// Moves a file to the top of the recent files list.
+///
+/// The list is limited to `limit` entries.
+fn push_recent_file(state: &mut RecentFilesState, path: String, name: String, limit: usize) {
+    // Remove if already exists
+    state.files.retain(|f| f.path != path);
+
+    // Add to front
+    state.files.insert(
+        0,
+        RecentFile {
+            path,
+            name,
+            last_opened: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        },
+    );
+
+    // Limit size
+    if state.files.len() > limit {
+        state.files.truncate(limit);
+    }
+}
+
 /// Saves the recent files state to disk as JSON.
 ///
 /// The file is stored in the application's data directory as `recent_files.json`.
@@ -164,110 +278,173 @@ fn load_recent_files(app: &AppHandle) -> RecentFilesState {
     RecentFilesState::default()
 }
 
-/// Checks if a file path has a Markdown extension.
-fn is_markdown_file(path: &str) -> bool {
+/// Checks if a file path has one of the given Markdown extensions.
+///
+/// Extensions are compared case-insensitively, so `README.MD` matches `md`.
+fn is_markdown_file(path: &str, extensions: &[String]) -> bool {
     std::path::Path::new(path)
         .extension()
         .and_then(|e| e.to_str())
-        .is_some_and(|ext| MARKDOWN_EXTENSIONS.contains(&ext))
+        .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
 }
 
-/// Extracts a file path from a file:// URL.
-///
-/// If the string is not a file:// URL, returns it unchanged.
-fn extract_file_path(url: &str) -> String {
-    url.strip_prefix("file://").unwrap_or(url).to_string()
-}
+/// Checks if a file looks like text: no NUL bytes and valid UTF-8 in its first bytes.
+fn is_text_file(path: &std::path::Path) -> bool {
+    use std::io::Read;
 
-// ============================================================================
-// Menu
-// ============================================================================
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    if !file.metadata().is_ok_and(|m| m.is_file()) {
+        return false;
+    }
 
-/// Builds the application menu bar.
-///
-/// On macOS, includes the app menu (About, Quit) plus File and View menus.
-/// On other platforms, includes only File and View menus.
-pub fn build_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
-    let about_mi = MenuItem::with_id(app, "about", "About Mark Lens", true, None::<&str>)?;
-    let quit_mi = MenuItem::with_id(app, "quit", "Quit", true, Some("CmdOrCtrl+Q"))?;
-    let open_mi = MenuItem::with_id(app, "open", "Open…", true, Some("CmdOrCtrl+O"))?;
-    let close_window_mi = MenuItem::with_id(
-        app,
-        "close_window",
-        "Close Window",
-        true,
-        Some("CmdOrCtrl+W"),
-    )?;
-    let reload_mi = MenuItem::with_id(app, "reload", "Reload", true, Some("CmdOrCtrl+R"))?;
-    let toggle_fullscreen_mi = MenuItem::with_id(
-        app,
-        "toggle_fullscreen",
-        "Toggle Full Screen",
-        true,
-        Some("Ctrl+Cmd+F"),
-    )?;
-
-    let file_menu = Submenu::with_items(app, "File", true, &[&open_mi, &close_window_mi])?;
-    let view_menu = Submenu::with_items(app, "View", true, &[&reload_mi, &toggle_fullscreen_mi])?;
-
-    #[cfg(target_os = "macos")]
+    let mut sample = Vec::with_capacity(TEXT_SNIFF_LEN);
+    if file
+        .take(TEXT_SNIFF_LEN as u64)
+        .read_to_end(&mut sample)
+        .is_err()
     {
-        let app_menu = Submenu::with_items(app, "Mark Lens", true, &[&about_mi, &quit_mi])?;
-        return Menu::with_items(app, &[&app_menu, &file_menu, &view_menu]);
+        return false;
     }
 
-    #[cfg(not(target_os = "macos"))]
+    // A multi-byte character cut off at the end of the sample is still text
+    !sample.contains(&0)
+        && match std::str::from_utf8(&sample) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        }
+}
+
+/// How a file passed to the app is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    /// Has a Markdown extension and opens directly
+    Markdown,
+    /// Other text file, opened after the user confirms
+    Text,
+    /// Binary, missing or unreadable file
+    Unsupported,
+}
+
+/// Classifies a file by its extension and, failing that, its contents.
+fn classify_file(path: &str, extensions: &[String]) -> FileKind {
+    if is_markdown_file(path, extensions) {
+        FileKind::Markdown
+    } else if is_text_file(std::path::Path::new(path)) {
+        FileKind::Text
+    } else {
+        FileKind::Unsupported
+    }
+}
+
+/// Returns the file name of a path for display.
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Extracts a file path from a `file://` URL or one of the other schemes in
+/// [`FILE_URL_SCHEMES`].
+///
+/// The path is percent-decoded, and a host other than `localhost` becomes a
+/// UNC path (`\\host\share` on Windows, `//host/share` elsewhere). If the
+/// string is not such a URL, it is returned unchanged as a path.
+fn extract_file_path(url: &str) -> PathBuf {
+    PathBuf::from(file_url_to_path(url, cfg!(windows)).unwrap_or_else(|| url.to_string()))
+}
+
+/// Converts a file URL to a path string, using Windows path rules if `windows` is set.
+///
+/// Returns `None` if `input` is not a URL with one of [`FILE_URL_SCHEMES`].
+fn file_url_to_path(input: &str, windows: bool) -> Option<String> {
+    let (scheme, _) = input.split_once(':')?;
+    if !FILE_URL_SCHEMES
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(scheme))
     {
-        Menu::with_items(app, &[&file_menu, &view_menu])
+        return None;
     }
+
+    // Parse as a plain file URL so the host and path follow file URL rules
+    let url = Url::parse(&format!("file{}", &input[scheme.len()..])).ok()?;
+    let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().to_string();
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty() && !host.eq_ignore_ascii_case("localhost"));
+    let path = decode(url.path());
+
+    let path = match host {
+        // "file://notes.md" names a relative file rather than a host
+        Some(host) if path.is_empty() || path == "/" => decode(host),
+        Some(host) => format!("//{}{}", host, path),
+        None => {
+            // "/C:/Users" is a drive path on Windows
+            let bytes = path.as_bytes();
+            let has_drive = bytes.len() >= 3
+                && bytes[0] == b'/'
+                && bytes[1].is_ascii_alphabetic()
+                && bytes[2] == b':';
+            if windows && has_drive {
+                path[1..].to_string()
+            } else {
+                path
+            }
+        }
+    };
+
+    Some(if windows {
+        path.replace('/', "\\")
+    } else {
+        path
+    })
 }
 
 // ============================================================================
 // Event Handlers
 // ============================================================================
 
-/// Sets up window event handlers for drag-and-drop support.
+/// Sets up window event handlers for drag-and-drop support, focus and
+/// geometry tracking, and close requests.
 ///
-/// When files are dropped onto the window, they are added to the startup files list.
-fn setup_window_event_handlers(window: &WebviewWindow, app_handle: AppHandle) {
+/// Files dropped onto the window are opened in it through [`open_paths`].
+/// Close requests are held back until unsaved changes are resolved.
+pub(crate) fn setup_window_event_handlers(window: &WebviewWindow, app_handle: AppHandle) {
     let app_handle = app_handle.clone();
-    window.on_window_event(move |event| {
-        if let WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
-            for path in paths {
-                println!("[App] Dragged file: {}", path.to_string_lossy());
-                add_to_startup_files(app_handle.clone(), path.to_string_lossy().to_string());
-            }
+    let target = window.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+            let paths = paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
+            open_paths(&app_handle, target.label(), paths);
         }
-    });
-}
-
-/// Sets up menu event handlers for all menu items.
-///
-/// Handles: About, Quit, Open, Clear Recent, Close Window, Reload, Toggle Full Screen.
-fn setup_menu_events(app: &AppHandle) {
-    app.on_menu_event(move |app, event| match event.id.as_ref() {
-        "about" => handle_about_event(app),
-        "quit" => std::process::exit(0),
-        "open" => handle_open_event(app),
-        "clear_recent" => {
-            clear_recent_files(app.clone());
-            let _ = app.emit("recent-files-changed", ());
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+            window_state::track(&app_handle, &target);
         }
-        "close_window" => {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.close();
-            }
+        WindowEvent::Focused(true) => {
+            app_handle
+                .state::<windows::WindowRegistry>()
+                .set_focused(target.label());
         }
-        "reload" => {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.eval("window.dispatchEvent(new CustomEvent('reload-file'))");
-            }
+        WindowEvent::CloseRequested { api, .. } => {
+            api.prevent_close();
+            window_state::save_window(&app_handle, &target);
+            shutdown::request_close(&app_handle, target.clone());
         }
-        "toggle_fullscreen" => {
-            if let Some(window) = app.get_webview_window("main") {
-                let is_fullscreen = window.is_fullscreen().unwrap_or(false);
-                let _ = window.set_fullscreen(!is_fullscreen);
-            }
+        WindowEvent::Destroyed => {
+            app_handle
+                .state::<windows::WindowRegistry>()
+                .unregister(target.label());
+            app_handle
+                .state::<shutdown::ShutdownState>()
+                .unregister(target.label());
+            wait::finish_window(&app_handle, target.label());
+            app_handle.state::<StartupFiles>().take(target.label());
         }
         _ => {}
     });
@@ -287,37 +464,213 @@ fn handle_about_event(app: &AppHandle) {
 
 /// Opens a file picker dialog to select a Markdown file.
 ///
-/// On file selection, emits a `file-open-requested` event and adds the file to recent files.
+/// The picker also offers "All Files"; other text files are opened after
+/// confirmation. On success, emits a `file-open-requested` event and adds
+/// the file to recent files.
 fn handle_open_event(app: &AppHandle) {
     let app_handle = app.clone();
+    let extensions = settings::current(app).markdown_extensions;
     tauri::async_runtime::spawn(async move {
+        // Dialog filters are case-sensitive on some platforms
+        let mut patterns: Vec<String> = Vec::new();
+        for ext in &extensions {
+            for pattern in [ext.to_lowercase(), ext.to_uppercase()] {
+                if !patterns.contains(&pattern) {
+                    patterns.push(pattern);
+                }
+            }
+        }
+        let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+
         app_handle
             .dialog()
             .file()
-            .add_filter("Markdown", MARKDOWN_EXTENSIONS)
+            .add_filter("Markdown", &patterns)
+            .add_filter("All Files", &["*"])
             .pick_file(move |file| {
-                if let Some(file_path) = file {
-                    let path = file_path.to_string();
-                    let name = std::path::Path::new(&path)
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-                    let _ = app_handle.emit("file-open-requested", &path);
-                    add_recent_file(app_handle.clone(), path, name);
+                let Some(file_path) = file else {
+                    return;
+                };
+                let path = file_path.to_string();
+                let open = |app: &AppHandle, path: String| {
+                    windows::emit_to_focused(app, "file-open-requested", &path);
+                    add_recent_file(app.clone(), path.clone(), file_name(&path));
+                };
+                match classify_file(&path, &extensions) {
+                    FileKind::Markdown => open(&app_handle, path),
+                    FileKind::Text => confirm_open_anyway(&app_handle, path, open),
+                    FileKind::Unsupported => {
+                        app_handle
+                            .dialog()
+                            .message(format!(
+                                "\"{}\" is not a text file and cannot be opened.",
+                                file_name(&path)
+                            ))
+                            .title("Open")
+                            .kind(MessageDialogKind::Error)
+                            .show(|_| {});
+                    }
                 }
             });
     });
 }
 
-/// Processes command-line arguments and adds Markdown files to the startup list.
-fn process_startup_files(app_handle: &AppHandle, args: &[String]) {
-    for path in args.iter().filter(|p| is_markdown_file(p)) {
-        eprintln!("[setup] Storing startup file: {}", path);
-        add_to_startup_files(app_handle.clone(), path.clone());
+/// Asks whether to open a text file without a Markdown extension.
+///
+/// Calls `open` with the path if the user confirms.
+fn confirm_open_anyway<F>(app: &AppHandle, path: String, open: F)
+where
+    F: FnOnce(&AppHandle, String) + Send + 'static,
+{
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "\"{}\" is not a Markdown file. Do you want to open it anyway?",
+            file_name(&path)
+        ))
+        .title("Open")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Open Anyway".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            if confirmed {
+                open(&app_handle, path);
+            }
+        });
+}
+
+/// Opens files passed on the command line or dropped onto a window.
+///
+/// Markdown files are queued for `window` directly, other text files after
+/// confirmation; binary and missing files are skipped.
+fn open_paths(app: &AppHandle, window: &str, paths: Vec<String>) {
+    for path in paths {
+        open_path(app, window, path, true);
+    }
+}
+
+/// Queues a file for `window`, asking first for text files that aren't
+/// Markdown when `confirm` is set.
+fn open_path(app: &AppHandle, window: &str, path: String, confirm: bool) {
+    let extensions = settings::current(app).markdown_extensions;
+    match classify_file(&path, &extensions) {
+        FileKind::Markdown => queue_file(app, window, path),
+        FileKind::Text if !confirm => queue_file(app, window, path),
+        FileKind::Text => {
+            let window = window.to_string();
+            confirm_open_anyway(app, path, move |app, path| queue_file(app, &window, path));
+        }
+        FileKind::Unsupported => eprintln!("[App] Skipping unsupported file: {}", path),
     }
 }
 
+/// Opens a file picked from the "Open Recent" submenu.
+///
+/// Emits a `file-open-requested` event and moves the file to the top of recent files.
+fn handle_open_recent_event(app: &AppHandle, path: &str) {
+    if !std::path::Path::new(path).exists() {
+        app.dialog()
+            .message(format!("The file \"{}\" could not be found.", path))
+            .title("Open Recent")
+            .show(|_| {});
+        return;
+    }
+
+    windows::emit_to_focused(app, "file-open-requested", path);
+    add_recent_file(app.clone(), path.to_string(), file_name(path));
+    let _ = app.emit("recent-files-changed", ());
+}
+
+/// Opens the files, folder and piped content named on a command line in `window`.
+///
+/// Files with a line or opened read-only also get a navigation request. A
+/// `--wait` request completes when its files or `window` are closed; its
+/// files are opened without asking, since tools like Git hand over files such as `COMMIT_EDITMSG`.
+fn handle_launch(app: &AppHandle, window: &str, request: cli::LaunchRequest) {
+    eprintln!("[launch] {:?}", request);
+    let confirm = request.wait_id.is_none();
+    let mut documents = Vec::new();
+    for target in request.targets {
+        match target {
+            cli::Target::File { path, line, column } => {
+                let path = path.to_string_lossy().to_string();
+                open_path(app, window, path.clone(), confirm);
+                documents.push(path.clone());
+                if line.is_some() || request.readonly {
+                    queue_navigation(
+                        app,
+                        window,
+                        NavigateRequest {
+                            path,
+                            line,
+                            column,
+                            heading: None,
+                            readonly: request.readonly,
+                        },
+                    );
+                }
+            }
+            cli::Target::Directory(path) => {
+                queue_workspace(app, window, path.to_string_lossy().to_string())
+            }
+            cli::Target::Piped(file) => match cli::take_piped(&file) {
+                Ok(content) => queue_untitled(
+                    app,
+                    window,
+                    UntitledDocument {
+                        content,
+                        title: request.title.clone(),
+                    },
+                ),
+                Err(e) => eprintln!("[launch] Failed to read piped content: {}", e),
+            },
+            // Read by the launching process and passed on as `Piped`
+            cli::Target::Stdin => {}
+        }
+    }
+
+    if let Some(id) = &request.wait_id {
+        wait::register(app, window, id, documents);
+    }
+}
+
+/// Handles a second launch of the application.
+///
+/// The second process forwards its arguments and exits; they are handled in
+/// the focused window, or a new one with `--new-window` or `--wait`, which is
+/// brought to the front.
+fn handle_second_instance(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    eprintln!(
+        "[single-instance] Forwarded args: {:?} (cwd: {})",
+        argv, cwd
+    );
+    let request = match cli::parse_request(&argv, std::path::Path::new(&cwd)) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("[single-instance] Invalid arguments: {}", e);
+            return;
+        }
+    };
+
+    let window = if request.new_window || request.wait_id.is_some() {
+        windows::create_window(app, None)
+            .inspect_err(|e| eprintln!("[single-instance] Failed to open window: {}", e))
+            .ok()
+    } else {
+        windows::focused_window(app)
+    };
+    let Some(window) = window else {
+        return;
+    };
+    handle_launch(app, window.label(), request);
+
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
 /// Handles file open events when the app is already running.
 ///
 /// This is triggered when a user opens a file via Finder (macOS) or File Explorer (Windows/Linux)
@@ -325,12 +678,19 @@ fn process_startup_files(app_handle: &AppHandle, args: &[String]) {
 fn handle_opened_urls(app_handle: &AppHandle, urls: Vec<tauri::Url>) {
     eprintln!("[RunEvent::Opened] Received {} file(s)", urls.len());
 
-    for url in urls {
-        let url_str = url.to_string();
-        eprintln!("[RunEvent::Opened] File URL: {}", url_str);
-        let path = extract_file_path(&url_str);
-        add_to_startup_files(app_handle.clone(), path);
-    }
+    let paths = urls
+        .iter()
+        .map(|url| {
+            eprintln!("[RunEvent::Opened] File URL: {}", url);
+            extract_file_path(url.as_str())
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    let window = windows::focused_window(app_handle)
+        .map(|window| window.label().to_string())
+        .unwrap_or_else(|| windows::MAIN_WINDOW.to_string());
+    open_paths(app_handle, &window, paths);
 }
 
 // ============================================================================
@@ -342,19 +702,46 @@ fn handle_opened_urls(app_handle: &AppHandle, urls: Vec<tauri::Url>) {
 /// Initializes plugins, registers commands, sets up state management,
 /// and configures event handlers for the application lifecycle.
 pub fn run() {
-    tauri::Builder::default()
+    // Exits with a message for --help, --version and invalid arguments
+    let cli = cli::Cli::parse();
+    if let Some(code) = cli::relaunch_if_needed(&cli) {
+        std::process::exit(code);
+    }
+    let new_instance = cli.new_instance;
+    let request = cli.into_request(&std::env::current_dir().unwrap_or_default());
+
+    let mut builder = tauri::Builder::default();
+    if !new_instance {
+        // Registered first so a second instance exits before doing any work
+        builder = builder.plugin(tauri_plugin_single_instance::init(handle_second_instance));
+    }
+
+    builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
         .manage(tokio::sync::Mutex::new(RecentFilesState::default()))
         .manage(StartupFiles::default())
+        .manage(drafts::DraftSession::default())
+        .manage(shutdown::ShutdownState::default())
+        .manage(settings::SettingsState::default())
+        .manage(windows::WindowRegistry::default())
+        .manage(window_state::WindowStateStore::default())
+        .manage(wait::WaitRegistry::default())
+        .manage(search::SearchState::default())
+        .manage(search_index::IndexState::default())
+        .manage(quick_open::QuickOpenState::default())
+        .manage(workspace_watcher::WorkspaceWatchers::default())
+        .manage(std::sync::Mutex::new(
+            keybindings::KeybindingRegistry::default(),
+        ))
         .invoke_handler(tauri::generate_handler![
             get_recent_files,
             add_recent_file,
             clear_recent_files,
             open_in_default_editor,
-            get_startup_files,
-            clear_startup_files,
+            take_startup_files,
             add_to_startup_files,
             set_window_title,
             fs::read_file,
@@ -367,16 +754,61 @@ pub fn run() {
             fs::delete_path,
             fs::rename_path,
             fs::copy_file,
+            drafts::save_draft,
+            drafts::discard_draft,
+            drafts::get_recoverable_drafts,
+            drafts::restore_draft,
+            shutdown::register_unsaved_changes_handler,
+            shutdown::report_dirty_documents,
+            shutdown::report_saved_documents,
+            keybindings::get_keybindings,
+            keybindings::reload_keybindings,
+            settings::get_settings,
+            settings::update_settings,
+            windows::new_window,
+            windows::get_window_document,
+            windows::set_window_document,
+            wait::document_closed,
+            windows::list_windows,
+            markdown::render_markdown,
+            markdown::get_document_stats,
+            lint::lint_markdown,
+            outline::get_outline,
+            outline::update_document_toc,
+            front_matter::parse_front_matter,
+            front_matter::set_front_matter,
+            search::search_workspace,
+            search::cancel_search,
+            search_index::open_index,
+            search_index::close_index,
+            search_index::query_index,
+            replace::replace_in_workspace,
+            replace::undo_replace,
+            quick_open::open_quick_open,
+            quick_open::close_quick_open,
+            quick_open::quick_open,
         ])
-        .setup(|app| {
+        .setup(move |app| {
             eprintln!("[setup] Application starting up...");
 
-            let window = app.get_webview_window("main").unwrap();
+            let window = app.get_webview_window(windows::MAIN_WINDOW).unwrap();
             let app_handle = app.handle().clone();
+            app.state::<windows::WindowRegistry>()
+                .register(windows::MAIN_WINDOW, None);
+
+            // The main window starts hidden so it appears at its saved position
+            window_state::load(&app_handle);
+            window_state::restore(&app_handle, &window);
+            window.show()?;
 
             // Setup event handlers
             setup_window_event_handlers(&window, app_handle.clone());
-            setup_menu_events(&app_handle);
+            menu::setup_menu_events(&app_handle);
+
+            // Load settings before anything that depends on them
+            if let Err(e) = settings::init(&app_handle) {
+                eprintln!("[setup] Settings unavailable: {}", e);
+            }
 
             // Load recent files from disk
             let recent_files = load_recent_files(&app_handle);
@@ -384,26 +816,50 @@ pub fn run() {
                 .blocking_lock() = recent_files.clone();
             eprintln!("[setup] Loaded {} recent files", recent_files.files.len());
 
+            // Load key bindings before building menus that use them
+            *app.state::<std::sync::Mutex<keybindings::KeybindingRegistry>>()
+                .lock()
+                .unwrap() = keybindings::load_keybindings(&app_handle);
+
             // Set up menu
             let menu = build_menu(&app_handle)?;
             app.set_menu(menu)?;
+            if let Err(e) = menu::setup_tray(&app_handle) {
+                eprintln!("[setup] Tray icon unavailable: {}", e);
+            }
 
             // Handle files opened via command line arguments (first launch)
-            let args: Vec<String> = std::env::args().skip(1).collect();
-            eprintln!("[setup] Command line args: {:?}", args);
-            process_startup_files(&app_handle, &args);
+            handle_launch(&app_handle, windows::MAIN_WINDOW, request);
+
+            // Handle marklens:// links, at launch and while running
+            #[cfg(any(target_os = "linux", windows))]
+            if let Err(e) = app_handle.deep_link().register_all() {
+                eprintln!("[setup] Failed to register URL scheme: {}", e);
+            }
+            let deep_link_handle = app_handle.clone();
+            app_handle.deep_link().on_open_url(move |event| {
+                deep_link::handle_urls(&deep_link_handle, event.urls());
+            });
+            if let Ok(Some(urls)) = app_handle.deep_link().get_current() {
+                deep_link::handle_urls(&app_handle, urls);
+            }
 
             Ok(())
         })
         .build(tauri::generate_context!())
         .expect("error while running tauri application")
-        .run(|app_handle, event| {
+        .run(|app_handle, event| match event {
             // Handle file open events (when app is already running)
             // RunEvent::Opened is only available on macOS
             #[cfg(target_os = "macos")]
-            if let tauri::RunEvent::Opened { urls } = event {
-                handle_opened_urls(&app_handle, urls);
+            tauri::RunEvent::Opened { urls } => {
+                handle_opened_urls(app_handle, urls);
             }
+            tauri::RunEvent::ExitRequested { api, .. } if shutdown::intercept_exit(app_handle) => {
+                api.prevent_exit();
+            }
+            tauri::RunEvent::Exit => drafts::end_session(app_handle),
+            _ => {}
         });
 }
 
@@ -414,6 +870,11 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
+
+    fn extensions() -> Vec<String> {
+        settings::Settings::default().markdown_extensions
+    }
 
     // ========================================================================
     // is_markdown_file tests
@@ -421,56 +882,101 @@ mod tests {
 
     #[test]
     fn test_is_markdown_file_with_md_extension() {
-        assert!(is_markdown_file("README.md"));
-        assert!(is_markdown_file("notes.md"));
-        assert!(is_markdown_file("/path/to/file.md"));
+        assert!(is_markdown_file("README.md", &extensions()));
+        assert!(is_markdown_file("notes.md", &extensions()));
+        assert!(is_markdown_file("/path/to/file.md", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_with_markdown_extension() {
-        assert!(is_markdown_file("file.markdown"));
-        assert!(is_markdown_file("/path/to/file.markdown"));
+        assert!(is_markdown_file("file.markdown", &extensions()));
+        assert!(is_markdown_file("/path/to/file.markdown", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_with_mdown_extension() {
-        assert!(is_markdown_file("file.mdown"));
+        assert!(is_markdown_file("file.mdown", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_with_mkd_extension() {
-        assert!(is_markdown_file("file.mkd"));
+        assert!(is_markdown_file("file.mkd", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_with_mkdn_extension() {
-        assert!(is_markdown_file("file.mkdn"));
+        assert!(is_markdown_file("file.mkdn", &extensions()));
+    }
+
+    #[test]
+    fn test_is_markdown_file_case_insensitive() {
+        assert!(is_markdown_file("README.MD", &extensions()));
+        assert!(is_markdown_file("file.Md", &extensions()));
+        assert!(is_markdown_file("notes.QMD", &extensions()));
     }
 
     #[test]
-    fn test_is_markdown_file_case_sensitive() {
-        // Extensions are case-sensitive on Unix-like systems
-        assert!(!is_markdown_file("file.MD"));
-        assert!(!is_markdown_file("file.Md"));
+    fn test_is_markdown_file_with_custom_extensions() {
+        let custom = vec!["txt".to_string()];
+        assert!(is_markdown_file("notes.TXT", &custom));
+        assert!(!is_markdown_file("notes.md", &custom));
     }
 
     #[test]
     fn test_is_markdown_file_non_markdown() {
-        assert!(!is_markdown_file("file.txt"));
-        assert!(!is_markdown_file("file.rs"));
-        assert!(!is_markdown_file("file.json"));
-        assert!(!is_markdown_file("file.md.bak"));
+        assert!(!is_markdown_file("file.txt", &extensions()));
+        assert!(!is_markdown_file("file.rs", &extensions()));
+        assert!(!is_markdown_file("file.json", &extensions()));
+        assert!(!is_markdown_file("file.md.bak", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_no_extension() {
-        assert!(!is_markdown_file("README"));
-        assert!(!is_markdown_file("file"));
+        assert!(!is_markdown_file("README", &extensions()));
+        assert!(!is_markdown_file("file", &extensions()));
     }
 
     #[test]
     fn test_is_markdown_file_empty_string() {
-        assert!(!is_markdown_file(""));
+        assert!(!is_markdown_file("", &extensions()));
+    }
+
+    #[test]
+    fn test_classify_file() {
+        let dir = tempdir().unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "plain notes ✓").unwrap();
+        let binary = dir.path().join("image.png");
+        std::fs::write(&binary, [0x89, b'P', b'N', b'G', 0, 0, 0]).unwrap();
+
+        let path = |p: &std::path::Path| p.to_string_lossy().to_string();
+        assert_eq!(
+            classify_file("missing.md", &extensions()),
+            FileKind::Markdown
+        );
+        assert_eq!(classify_file(&path(&text), &extensions()), FileKind::Text);
+        assert_eq!(
+            classify_file(&path(&binary), &extensions()),
+            FileKind::Unsupported
+        );
+        assert_eq!(
+            classify_file(&path(dir.path()), &extensions()),
+            FileKind::Unsupported
+        );
+        assert_eq!(
+            classify_file(&path(&dir.path().join("missing.txt")), &extensions()),
+            FileKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_is_text_file_with_truncated_utf8() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        let mut content = "a".repeat(TEXT_SNIFF_LEN - 1).into_bytes();
+        content.extend("é".as_bytes());
+        std::fs::write(&file, content).unwrap();
+        assert!(is_text_file(&file));
     }
 
     // ========================================================================
@@ -480,30 +986,101 @@ mod tests {
     #[test]
     fn test_extract_file_path_with_file_protocol() {
         assert_eq!(
-            extract_file_path("file:///path/to/file.md"),
+            file_url_to_path("file:///path/to/file.md", false).unwrap(),
             "/path/to/file.md"
         );
         assert_eq!(
-            extract_file_path("file:///Users/test/document.md"),
+            file_url_to_path("file:///Users/test/document.md", false).unwrap(),
             "/Users/test/document.md"
         );
     }
 
     #[test]
     fn test_extract_file_path_without_file_protocol() {
-        assert_eq!(extract_file_path("/path/to/file.md"), "/path/to/file.md");
-        assert_eq!(extract_file_path("relative/path.md"), "relative/path.md");
+        assert_eq!(
+            extract_file_path("/path/to/file.md"),
+            PathBuf::from("/path/to/file.md")
+        );
+        assert_eq!(
+            extract_file_path("relative/path.md"),
+            PathBuf::from("relative/path.md")
+        );
+        assert_eq!(file_url_to_path("https://example.com/a.md", false), None);
+        assert_eq!(file_url_to_path("C:\\notes\\a.md", true), None);
     }
 
     #[test]
     fn test_extract_file_path_partial_protocol() {
         // Edge case: string starts with "file://" but has more content
-        assert_eq!(extract_file_path("file://test.md"), "test.md");
+        assert_eq!(
+            file_url_to_path("file://test.md", false).unwrap(),
+            "test.md"
+        );
     }
 
     #[test]
     fn test_extract_file_path_empty_string() {
-        assert_eq!(extract_file_path(""), "");
+        assert_eq!(extract_file_path(""), PathBuf::from(""));
+    }
+
+    #[test]
+    fn test_extract_file_path_percent_decoding() {
+        assert_eq!(
+            file_url_to_path("file:///home/me/My%20Notes.md", false).unwrap(),
+            "/home/me/My Notes.md"
+        );
+        assert_eq!(
+            file_url_to_path("file:///home/me/%E2%9C%93%20done.md", false).unwrap(),
+            "/home/me/✓ done.md"
+        );
+        assert_eq!(
+            extract_file_path("file:///home/me/My%20Notes.md"),
+            PathBuf::from(if cfg!(windows) {
+                "\\home\\me\\My Notes.md"
+            } else {
+                "/home/me/My Notes.md"
+            })
+        );
+    }
+
+    #[test]
+    fn test_extract_file_path_with_host() {
+        assert_eq!(
+            file_url_to_path("file://localhost/etc/notes.md", false).unwrap(),
+            "/etc/notes.md"
+        );
+        assert_eq!(
+            file_url_to_path("file://host/share/x.md", false).unwrap(),
+            "//host/share/x.md"
+        );
+        assert_eq!(
+            file_url_to_path("file://host/share/x.md", true).unwrap(),
+            "\\\\host\\share\\x.md"
+        );
+    }
+
+    #[test]
+    fn test_extract_file_path_windows_drive() {
+        assert_eq!(
+            file_url_to_path("file:///C:/Users/me/My%20Notes.md", true).unwrap(),
+            "C:\\Users\\me\\My Notes.md"
+        );
+        assert_eq!(
+            file_url_to_path("file:///c:/notes.md", false).unwrap(),
+            "/c:/notes.md"
+        );
+    }
+
+    #[test]
+    fn test_extract_file_path_other_schemes() {
+        assert_eq!(
+            file_url_to_path("markdown+file:///home/me/a.md", false).unwrap(),
+            "/home/me/a.md"
+        );
+        assert_eq!(
+            file_url_to_path("FILE:///home/me/a.md", false).unwrap(),
+            "/home/me/a.md"
+        );
     }
 
     // ========================================================================
@@ -561,6 +1138,52 @@ mod tests {
         assert_eq!(state.files[1].name, deserialized.files[1].name);
     }
 
+    #[test]
+    fn test_push_recent_file_moves_existing_to_top() {
+        let mut state = RecentFilesState::default();
+        push_recent_file(
+            &mut state,
+            "/a.md".to_string(),
+            "a.md".to_string(),
+            MAX_RECENT_FILES,
+        );
+        push_recent_file(
+            &mut state,
+            "/b.md".to_string(),
+            "b.md".to_string(),
+            MAX_RECENT_FILES,
+        );
+        push_recent_file(
+            &mut state,
+            "/a.md".to_string(),
+            "a.md".to_string(),
+            MAX_RECENT_FILES,
+        );
+
+        assert_eq!(state.files.len(), 2);
+        assert_eq!(state.files[0].path, "/a.md");
+        assert_eq!(state.files[1].path, "/b.md");
+    }
+
+    #[test]
+    fn test_push_recent_file_limits_size() {
+        let mut state = RecentFilesState::default();
+        for i in 0..MAX_RECENT_FILES + 5 {
+            push_recent_file(
+                &mut state,
+                format!("/file{}.md", i),
+                format!("file{}.md", i),
+                MAX_RECENT_FILES,
+            );
+        }
+
+        assert_eq!(state.files.len(), MAX_RECENT_FILES);
+        assert_eq!(
+            state.files[0].path,
+            format!("/file{}.md", MAX_RECENT_FILES + 4)
+        );
+    }
+
     // ========================================================================
     // StartupFiles tests
     // ========================================================================
@@ -568,22 +1191,42 @@ mod tests {
     #[test]
     fn test_startup_files_default() {
         let startup_files = StartupFiles::default();
-        let paths = startup_files.paths.lock().unwrap();
-        assert!(paths.is_empty());
+        assert!(startup_files.take(windows::MAIN_WINDOW).paths.is_empty());
     }
 
     #[test]
     fn test_startup_files_add_and_retrieve() {
         let startup_files = StartupFiles::default();
-        {
-            let mut paths = startup_files.paths.lock().unwrap();
-            paths.push("/path/to/file1.md".to_string());
-            paths.push("/path/to/file2.md".to_string());
-        }
+        startup_files.update(windows::MAIN_WINDOW, |pending| {
+            pending.paths.push("/path/to/file1.md".to_string());
+            pending.paths.push("/path/to/file2.md".to_string());
+        });
 
-        let paths = startup_files.paths.lock().unwrap();
+        let paths = startup_files.take(windows::MAIN_WINDOW).paths;
         assert_eq!(paths.len(), 2);
         assert_eq!(paths[0], "/path/to/file1.md");
         assert_eq!(paths[1], "/path/to/file2.md");
+
+        // Taking the requests clears them
+        assert!(startup_files.take(windows::MAIN_WINDOW).paths.is_empty());
+    }
+
+    #[test]
+    fn test_startup_files_are_kept_per_window() {
+        let startup_files = StartupFiles::default();
+        startup_files.update(windows::MAIN_WINDOW, |pending| {
+            pending.paths.push("/a.md".to_string())
+        });
+        startup_files.update("window-1", |pending| {
+            pending.workspace = Some("/notes".to_string())
+        });
+
+        let other = startup_files.take("window-1");
+        assert!(other.paths.is_empty());
+        assert_eq!(other.workspace, Some("/notes".to_string()));
+        assert_eq!(
+            startup_files.take(windows::MAIN_WINDOW).paths,
+            vec!["/a.md"]
+        );
     }
 }
@@ -0,0 +1,164 @@
+use crate::markdown::{self, gfm_options};
+use comrak::nodes::NodeValue;
+use comrak::Arena;
+use serde::Serialize;
+
+/// Problem found in a document, with rule names following markdownlint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    /// 1-based line of the problem
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Checks a document for common Markdown problems.
+///
+/// Issues are sorted by line.
+pub fn lint(content: &str) -> Vec<LintIssue> {
+    let arena = Arena::new();
+    let root = markdown::parse(&arena, content, &gfm_options());
+
+    let mut issues = Vec::new();
+    let mut code_lines = Vec::new();
+    let mut previous_level = 0;
+    let mut top_level_seen = false;
+
+    for node in root.descendants() {
+        let ast = node.data.borrow();
+        let line = ast.sourcepos.start.line;
+        match &ast.value {
+            NodeValue::Heading(heading) => {
+                if previous_level > 0 && heading.level > previous_level + 1 {
+                    issues.push(LintIssue {
+                        line,
+                        rule: "heading-increment",
+                        message: format!(
+                            "Heading level jumps from {} to {}",
+                            previous_level, heading.level
+                        ),
+                    });
+                }
+                if heading.level == 1 {
+                    if top_level_seen {
+                        issues.push(LintIssue {
+                            line,
+                            rule: "single-h1",
+                            message: "Multiple top-level headings".to_string(),
+                        });
+                    }
+                    top_level_seen = true;
+                }
+                previous_level = heading.level;
+            }
+            NodeValue::Link(link) if link.url.trim().is_empty() => issues.push(LintIssue {
+                line,
+                rule: "no-empty-links",
+                message: "Link has no destination".to_string(),
+            }),
+            NodeValue::CodeBlock(_) => {
+                code_lines.push(ast.sourcepos.start.line..=ast.sourcepos.end.line)
+            }
+            _ => {}
+        }
+    }
+
+    let mut blank_run = 0;
+    for (index, text) in content.lines().enumerate() {
+        let line = index + 1;
+        if code_lines.iter().any(|lines| lines.contains(&line)) {
+            blank_run = 0;
+            continue;
+        }
+
+        // Two trailing spaces are a hard line break
+        let trailing = text.len() - text.trim_end().len();
+        if trailing > 0 && !(trailing == 2 && text.ends_with("  ") && !text.trim().is_empty()) {
+            issues.push(LintIssue {
+                line,
+                rule: "no-trailing-spaces",
+                message: "Trailing whitespace".to_string(),
+            });
+        }
+        if text.contains('\t') {
+            issues.push(LintIssue {
+                line,
+                rule: "no-hard-tabs",
+                message: "Hard tab".to_string(),
+            });
+        }
+
+        if text.trim().is_empty() {
+            blank_run += 1;
+            if blank_run == 2 {
+                issues.push(LintIssue {
+                    line,
+                    rule: "no-multiple-blanks",
+                    message: "Multiple consecutive blank lines".to_string(),
+                });
+            }
+        } else {
+            blank_run = 0;
+        }
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        issues.push(LintIssue {
+            line: content.lines().count(),
+            rule: "single-trailing-newline",
+            message: "File should end with a newline".to_string(),
+        });
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Checks a document for common Markdown problems.
+#[tauri::command]
+pub fn lint_markdown(content: String) -> Vec<LintIssue> {
+    lint(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str) -> Vec<(usize, &'static str)> {
+        lint(content)
+            .into_iter()
+            .map(|issue| (issue.line, issue.rule))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_document() {
+        assert!(lint("# Title\n\nText with a  \nbreak.\n\n## Section\n").is_empty());
+    }
+
+    #[test]
+    fn test_heading_rules() {
+        assert_eq!(
+            rules("# One\n\n### Three\n\n# Again\n"),
+            vec![(3, "heading-increment"), (5, "single-h1")]
+        );
+    }
+
+    #[test]
+    fn test_line_rules_skip_code_blocks() {
+        let content = "# Title\n\ntext \n\n\n```\n\tcode \n```\n[empty]()";
+        assert_eq!(
+            rules(content),
+            vec![
+                (3, "no-trailing-spaces"),
+                (5, "no-multiple-blanks"),
+                (9, "no-empty-links"),
+                (9, "single-trailing-newline"),
+            ]
+        );
+    }
+}
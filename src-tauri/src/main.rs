@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Subcommands such as `render` and `lint` run without a window
+    if let Some(code) = mark_lens_lib::run_command() {
+        std::process::exit(code);
+    }
     mark_lens_lib::run()
 }
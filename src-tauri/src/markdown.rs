@@ -0,0 +1,444 @@
+use crate::front_matter;
+use crate::sanitize::{self, TrustLevel};
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Anchorizer, Arena, Options};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Average reading speed used for the reading time estimate
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Markdown extensions and output settings for rendering
+///
+/// All GitHub Flavored Markdown extensions are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub tables: bool,
+    pub task_lists: bool,
+    pub strikethrough: bool,
+    /// Link bare URLs and `www.` addresses
+    pub autolinks: bool,
+    pub footnotes: bool,
+    /// Add `data-sourcepos` attributes and return a source map
+    pub source_positions: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            autolinks: true,
+            footnotes: true,
+            source_positions: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Converts to comrak options.
+    fn to_comrak(self) -> Options<'static> {
+        let mut options = Options::default();
+        options.extension.table = self.tables;
+        options.extension.tasklist = self.task_lists;
+        options.extension.strikethrough = self.strikethrough;
+        options.extension.autolink = self.autolinks;
+        options.extension.footnotes = self.footnotes;
+        options.render.sourcepos = self.source_positions;
+        // Raw HTML and all URLs are passed through and then sanitized
+        options.render.unsafe_ = true;
+        options
+    }
+}
+
+/// Comrak options for GitHub Flavored Markdown, used for parsing
+pub fn gfm_options() -> Options<'static> {
+    RenderOptions::default().to_comrak()
+}
+
+/// Parses a document, leaving out its front matter.
+///
+/// Source positions still refer to the lines of the whole document.
+pub fn parse<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    content: &str,
+    options: &Options,
+) -> &'a AstNode<'a> {
+    comrak::parse_document(arena, &front_matter::mask(content), options)
+}
+
+/// Range of source lines and columns a block was rendered from, all 1-based
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceSpan {
+    /// Block type, e.g. `heading`, `paragraph` or `code_block`
+    pub kind: &'static str,
+    /// Nesting depth, `0` for top-level blocks
+    pub depth: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// Result of [`render`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    /// Blocks in document order, empty unless source positions were requested.
+    /// The element for each block has a matching `data-sourcepos` attribute
+    /// of the form `start_line:start_column-end_line:end_column`.
+    pub source_map: Vec<SourceSpan>,
+}
+
+/// Renders Markdown to sanitized HTML.
+///
+/// This is the one renderer behind the preview, export and the command line.
+/// Raw HTML in the document is kept as far as `trust` allows.
+pub fn render(content: &str, options: &RenderOptions, trust: TrustLevel) -> RenderedMarkdown {
+    let arena = Arena::new();
+    let comrak_options = options.to_comrak();
+    let root = parse(&arena, content, &comrak_options);
+
+    let mut html = Vec::new();
+    // Writing to a Vec can't fail
+    let _ = comrak::format_html(root, &comrak_options, &mut html);
+
+    let source_map = if options.source_positions {
+        source_map(root)
+    } else {
+        Vec::new()
+    };
+    RenderedMarkdown {
+        html: sanitize::sanitize(&String::from_utf8_lossy(&html), trust),
+        source_map,
+    }
+}
+
+/// Lists the blocks that are rendered as an element, in document order.
+fn source_map<'a>(root: &'a AstNode<'a>) -> Vec<SourceSpan> {
+    root.descendants()
+        .filter_map(|node| {
+            let ast = node.data.borrow();
+            let kind = block_kind(&ast.value)?;
+            // Paragraphs in tight lists are rendered without a `<p>`
+            if kind == "paragraph" && in_tight_list(node) {
+                return None;
+            }
+            let pos = ast.sourcepos;
+            Some(SourceSpan {
+                kind,
+                depth: node.ancestors().count() - 2,
+                start_line: pos.start.line,
+                start_column: pos.start.column,
+                end_line: pos.end.line,
+                end_column: pos.end.column,
+            })
+        })
+        .collect()
+}
+
+/// Checks whether a node is directly inside an item of a tight list.
+fn in_tight_list<'a>(node: &'a AstNode<'a>) -> bool {
+    node.parent().and_then(|item| item.parent()).is_some_and(
+        |list| matches!(&list.data.borrow().value, NodeValue::List(list) if list.tight),
+    )
+}
+
+/// Name of a block that comrak renders with a `data-sourcepos` attribute.
+fn block_kind(value: &NodeValue) -> Option<&'static str> {
+    Some(match value {
+        NodeValue::BlockQuote => "block_quote",
+        NodeValue::List(_) => "list",
+        NodeValue::Item(_) | NodeValue::TaskItem(_) => "item",
+        NodeValue::CodeBlock(_) => "code_block",
+        NodeValue::HtmlBlock(_) => "html_block",
+        NodeValue::Paragraph => "paragraph",
+        NodeValue::Heading(_) => "heading",
+        NodeValue::ThematicBreak => "thematic_break",
+        NodeValue::FootnoteDefinition(_) => "footnote_definition",
+        NodeValue::Table(_) => "table",
+        NodeValue::TableRow(_) => "table_row",
+        NodeValue::TableCell => "table_cell",
+        _ => return None,
+    })
+}
+
+/// Renders Markdown to an HTML fragment with the default options.
+pub fn render_html(content: &str, trust: TrustLevel) -> String {
+    render(content, &RenderOptions::default(), trust).html
+}
+
+/// Wraps an HTML fragment in a standalone document for export.
+pub fn standalone_html(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<article>\n{}</article>\n</body>\n</html>\n",
+        escape_html(title),
+        EXPORT_STYLE,
+        body
+    )
+}
+
+/// Styles for exported documents, kept close to the preview
+const EXPORT_STYLE: &str = "body{max-width:48rem;margin:2rem auto;padding:0 1rem;font-family:system-ui,sans-serif;line-height:1.6;color:#1f2328}pre,code{font-family:ui-monospace,monospace;background:#f6f8fa}pre{padding:1rem;overflow:auto}table{border-collapse:collapse}th,td{border:1px solid #d0d7de;padding:.25rem .75rem}blockquote{margin:0;padding-left:1rem;border-left:.25rem solid #d0d7de;color:#59636e}img{max-width:100%}";
+
+/// Escapes text for use in HTML content and attributes.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Concatenates the text inside a node, without markup.
+pub fn node_text<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    for child in node.descendants() {
+        match &child.data.borrow().value {
+            NodeValue::Text(t) => text.push_str(t),
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Heading of a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    /// GitHub-compatible anchor, unique within the document
+    pub slug: String,
+    /// 1-based line of the heading
+    pub line: usize,
+}
+
+/// Lists the headings of a document in order.
+pub fn headings(content: &str) -> Vec<Heading> {
+    let arena = Arena::new();
+    let root = parse(&arena, content, &gfm_options());
+    let mut anchorizer = Anchorizer::new();
+
+    root.descendants()
+        .filter_map(|node| {
+            let ast = node.data.borrow();
+            let NodeValue::Heading(heading) = &ast.value else {
+                return None;
+            };
+            let text = node_text(node);
+            Some(Heading {
+                level: heading.level,
+                slug: anchorizer.anchorize(text.clone()),
+                text,
+                line: ast.sourcepos.start.line,
+            })
+        })
+        .collect()
+}
+
+/// Word counts and structure of a document
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DocumentStats {
+    /// Words of prose, not counting code blocks
+    pub words: usize,
+    pub characters: usize,
+    pub lines: usize,
+    pub headings: usize,
+    pub links: usize,
+    pub images: usize,
+    pub code_blocks: usize,
+    /// Estimated reading time, rounded up
+    pub reading_minutes: usize,
+}
+
+/// Counts words, characters and elements of a document.
+pub fn stats(content: &str) -> DocumentStats {
+    let arena = Arena::new();
+    let root = parse(&arena, content, &gfm_options());
+
+    let mut stats = DocumentStats {
+        characters: content.chars().count(),
+        lines: content.lines().count(),
+        ..DocumentStats::default()
+    };
+    for node in root.descendants() {
+        match &node.data.borrow().value {
+            NodeValue::Text(text) => stats.words += text.split_whitespace().count(),
+            NodeValue::Code(code) => stats.words += code.literal.split_whitespace().count(),
+            NodeValue::Heading(_) => stats.headings += 1,
+            NodeValue::Link(_) => stats.links += 1,
+            NodeValue::Image(_) => stats.images += 1,
+            NodeValue::CodeBlock(_) => stats.code_blocks += 1,
+            _ => {}
+        }
+    }
+    stats.reading_minutes = stats.words.div_ceil(WORDS_PER_MINUTE);
+    stats
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Renders Markdown to HTML, with GFM extensions unless `options` turn them off.
+///
+/// Raw HTML is sanitized with the trusted policy only if `path` is inside
+/// one of the trusted folders from the settings.
+#[tauri::command]
+pub fn render_markdown(
+    app: AppHandle,
+    content: String,
+    options: Option<RenderOptions>,
+    path: Option<String>,
+) -> RenderedMarkdown {
+    let trusted_folders = crate::settings::current(&app).trusted_folders;
+    let trust = sanitize::trust_for(path.as_deref().map(Path::new), &trusted_folders);
+    render(&content, &options.unwrap_or_default(), trust)
+}
+
+/// Returns word counts and structure of a document.
+#[tauri::command]
+pub fn get_document_stats(content: String) -> DocumentStats {
+    stats(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_gfm() {
+        let html = render_html(
+            "~~old~~ https://example.com\n\n- [x] done\n",
+            TrustLevel::Untrusted,
+        );
+        assert!(html.contains("<del>old</del>"));
+        assert!(html.contains("<a href=\"https://example.com\""));
+        assert!(html.contains("checked"));
+    }
+
+    #[test]
+    fn test_render_options_disable_extensions() {
+        let options = RenderOptions {
+            strikethrough: false,
+            autolinks: false,
+            tables: false,
+            ..RenderOptions::default()
+        };
+        let html = render(
+            "~~old~~ https://example.com\n\n| a |\n|---|\n",
+            &options,
+            TrustLevel::Untrusted,
+        )
+        .html;
+        assert!(!html.contains("<del>"));
+        assert!(!html.contains("<a "));
+        assert!(!html.contains("<table>"));
+    }
+
+    #[test]
+    fn test_render_source_map() {
+        let options = RenderOptions {
+            source_positions: true,
+            ..RenderOptions::default()
+        };
+        let rendered = render(
+            "# Title\n\n- one\n- two\n\n> quote\n",
+            &options,
+            TrustLevel::Untrusted,
+        );
+        for span in &rendered.source_map {
+            let attribute = format!(
+                "data-sourcepos=\"{}:{}-{}:{}\"",
+                span.start_line, span.start_column, span.end_line, span.end_column
+            );
+            assert!(rendered.html.contains(&attribute), "{}", attribute);
+        }
+        let kinds: Vec<(&str, usize, usize)> = rendered
+            .source_map
+            .iter()
+            .map(|span| (span.kind, span.depth, span.start_line))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("heading", 0, 1),
+                ("list", 0, 3),
+                ("item", 1, 3),
+                ("item", 1, 4),
+                ("block_quote", 0, 6),
+                ("paragraph", 1, 6),
+            ]
+        );
+        assert!(render(
+            "# Title\n",
+            &RenderOptions::default(),
+            TrustLevel::Untrusted
+        )
+        .source_map
+        .is_empty());
+    }
+
+    #[test]
+    fn test_headings() {
+        let content =
+            "# Intro\n\nText\n\nSetup `npm`\n-----\n\n```\n# not a heading\n```\n\n## Intro\n";
+        let headings = headings(content);
+        assert_eq!(
+            headings,
+            vec![
+                Heading {
+                    level: 1,
+                    text: "Intro".to_string(),
+                    slug: "intro".to_string(),
+                    line: 1,
+                },
+                Heading {
+                    level: 2,
+                    text: "Setup npm".to_string(),
+                    slug: "setup-npm".to_string(),
+                    line: 5,
+                },
+                Heading {
+                    level: 2,
+                    text: "Intro".to_string(),
+                    slug: "intro-1".to_string(),
+                    line: 12,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let content = "# Title\n\nSome words [here](a.md) ![img](b.png)\n\n```\nlet x = 1;\n```\n";
+        let stats = stats(content);
+        assert_eq!(stats.words, 5);
+        assert_eq!(stats.headings, 1);
+        assert_eq!(stats.links, 1);
+        assert_eq!(stats.images, 1);
+        assert_eq!(stats.code_blocks, 1);
+        assert_eq!(stats.lines, 7);
+        assert_eq!(stats.reading_minutes, 1);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use crate::{keybindings, windows, RecentFilesState};
+use serde::{Deserialize, Serialize};
+use tauri::{
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::TrayIconBuilder,
+    AppHandle, Emitter, Manager,
+};
+
+pub(crate) const RECENT_FILE_ID_PREFIX: &str = "open_recent:";
+const TRAY_ID: &str = "main";
+const ISSUES_URL: &str = "https://github.com/Enot-Racoon/mark-lens/issues";
+
+// ============================================================================
+// Menu Commands
+// ============================================================================
+
+/// Menu command handled by the frontend.
+///
+/// Delivered to the window through the `menu-command` event as `{ "id": "<command>" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuCommand {
+    NewFile,
+    Save,
+    SaveAs,
+    ExportHtml,
+    ExportPdf,
+    Find,
+    FindNext,
+    FindPrevious,
+    Replace,
+    ViewEdit,
+    ViewPreview,
+    ViewSplit,
+    ToggleSidebar,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Reload,
+    KeyboardShortcuts,
+}
+
+impl MenuCommand {
+    pub const ALL: &'static [MenuCommand] = &[
+        MenuCommand::NewFile,
+        MenuCommand::Save,
+        MenuCommand::SaveAs,
+        MenuCommand::ExportHtml,
+        MenuCommand::ExportPdf,
+        MenuCommand::Find,
+        MenuCommand::FindNext,
+        MenuCommand::FindPrevious,
+        MenuCommand::Replace,
+        MenuCommand::ViewEdit,
+        MenuCommand::ViewPreview,
+        MenuCommand::ViewSplit,
+        MenuCommand::ToggleSidebar,
+        MenuCommand::ZoomIn,
+        MenuCommand::ZoomOut,
+        MenuCommand::ZoomReset,
+        MenuCommand::Reload,
+        MenuCommand::KeyboardShortcuts,
+    ];
+
+    /// Menu item id, matching the serialized name
+    pub fn id(self) -> &'static str {
+        match self {
+            MenuCommand::NewFile => "new_file",
+            MenuCommand::Save => "save",
+            MenuCommand::SaveAs => "save_as",
+            MenuCommand::ExportHtml => "export_html",
+            MenuCommand::ExportPdf => "export_pdf",
+            MenuCommand::Find => "find",
+            MenuCommand::FindNext => "find_next",
+            MenuCommand::FindPrevious => "find_previous",
+            MenuCommand::Replace => "replace",
+            MenuCommand::ViewEdit => "view_edit",
+            MenuCommand::ViewPreview => "view_preview",
+            MenuCommand::ViewSplit => "view_split",
+            MenuCommand::ToggleSidebar => "toggle_sidebar",
+            MenuCommand::ZoomIn => "zoom_in",
+            MenuCommand::ZoomOut => "zoom_out",
+            MenuCommand::ZoomReset => "zoom_reset",
+            MenuCommand::Reload => "reload",
+            MenuCommand::KeyboardShortcuts => "keyboard_shortcuts",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|command| command.id() == id)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuCommand::NewFile => "New",
+            MenuCommand::Save => "Save",
+            MenuCommand::SaveAs => "Save As…",
+            MenuCommand::ExportHtml => "HTML…",
+            MenuCommand::ExportPdf => "PDF…",
+            MenuCommand::Find => "Find…",
+            MenuCommand::FindNext => "Find Next",
+            MenuCommand::FindPrevious => "Find Previous",
+            MenuCommand::Replace => "Replace…",
+            MenuCommand::ViewEdit => "Editor",
+            MenuCommand::ViewPreview => "Preview",
+            MenuCommand::ViewSplit => "Split View",
+            MenuCommand::ToggleSidebar => "Toggle Sidebar",
+            MenuCommand::ZoomIn => "Zoom In",
+            MenuCommand::ZoomOut => "Zoom Out",
+            MenuCommand::ZoomReset => "Actual Size",
+            MenuCommand::Reload => "Reload",
+            MenuCommand::KeyboardShortcuts => "Keyboard Shortcuts",
+        }
+    }
+
+    /// Builds the menu item for this command.
+    fn menu_item(self, app: &AppHandle) -> Result<MenuItem<tauri::Wry>, tauri::Error> {
+        MenuItem::with_id(
+            app,
+            self.id(),
+            self.label(),
+            true,
+            keybindings::accelerator(app, self.id()),
+        )
+    }
+}
+
+/// Payload of the `menu-command` event
+#[derive(Debug, Clone, Serialize)]
+struct MenuCommandEvent {
+    id: MenuCommand,
+}
+
+/// Sends a menu command to the focused window's frontend.
+fn dispatch_command(app: &AppHandle, command: MenuCommand) {
+    windows::emit_to_focused(app, "menu-command", MenuCommandEvent { id: command });
+}
+
+// ============================================================================
+// Menu
+// ============================================================================
+
+/// Builds the application menu bar.
+///
+/// On macOS, includes the app menu (About, Services, Hide, Quit).
+/// On other platforms, About moves to the Help menu and Quit to the File menu.
+/// The File menu contains an "Open Recent" submenu built from the recent files list.
+pub fn build_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let about_mi = MenuItem::with_id(app, "about", "About Mark Lens", true, None::<&str>)?;
+    let quit_mi = MenuItem::with_id(
+        app,
+        "quit",
+        "Quit",
+        true,
+        keybindings::accelerator(app, "quit"),
+    )?;
+    let separator = || PredefinedMenuItem::separator(app);
+
+    // File
+    let new_window_mi = MenuItem::with_id(
+        app,
+        "new_window",
+        "New Window",
+        true,
+        keybindings::accelerator(app, "new_window"),
+    )?;
+    let open_mi = MenuItem::with_id(
+        app,
+        "open",
+        "Open…",
+        true,
+        keybindings::accelerator(app, "open"),
+    )?;
+    let close_window_mi = MenuItem::with_id(
+        app,
+        "close_window",
+        "Close Window",
+        true,
+        keybindings::accelerator(app, "close_window"),
+    )?;
+    let export_menu = Submenu::with_items(
+        app,
+        "Export",
+        true,
+        &[
+            &MenuCommand::ExportHtml.menu_item(app)?,
+            &MenuCommand::ExportPdf.menu_item(app)?,
+        ],
+    )?;
+    let file_menu = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[
+            &MenuCommand::NewFile.menu_item(app)?,
+            &new_window_mi,
+            &open_mi,
+            &build_recent_submenu(app)?,
+            &separator()?,
+            &MenuCommand::Save.menu_item(app)?,
+            &MenuCommand::SaveAs.menu_item(app)?,
+            &export_menu,
+            &separator()?,
+            &close_window_mi,
+        ],
+    )?;
+    #[cfg(not(target_os = "macos"))]
+    file_menu.append_items(&[&separator()?, &quit_mi])?;
+
+    // Edit
+    let edit_menu = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+            &separator()?,
+            &MenuCommand::Find.menu_item(app)?,
+            &MenuCommand::FindNext.menu_item(app)?,
+            &MenuCommand::FindPrevious.menu_item(app)?,
+            &MenuCommand::Replace.menu_item(app)?,
+        ],
+    )?;
+
+    // View
+    let toggle_fullscreen_mi = MenuItem::with_id(
+        app,
+        "toggle_fullscreen",
+        "Toggle Full Screen",
+        true,
+        keybindings::accelerator(app, "toggle_fullscreen"),
+    )?;
+    let view_menu = Submenu::with_items(
+        app,
+        "View",
+        true,
+        &[
+            &MenuCommand::ViewEdit.menu_item(app)?,
+            &MenuCommand::ViewPreview.menu_item(app)?,
+            &MenuCommand::ViewSplit.menu_item(app)?,
+            &separator()?,
+            &MenuCommand::ToggleSidebar.menu_item(app)?,
+            &separator()?,
+            &MenuCommand::ZoomIn.menu_item(app)?,
+            &MenuCommand::ZoomOut.menu_item(app)?,
+            &MenuCommand::ZoomReset.menu_item(app)?,
+            &separator()?,
+            &MenuCommand::Reload.menu_item(app)?,
+            &toggle_fullscreen_mi,
+        ],
+    )?;
+
+    // Window
+    let window_menu = Submenu::with_items(
+        app,
+        "Window",
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "minimize",
+                "Minimize",
+                true,
+                keybindings::accelerator(app, "minimize"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "maximize",
+                "Zoom",
+                true,
+                keybindings::accelerator(app, "maximize"),
+            )?,
+        ],
+    )?;
+
+    // Help
+    let report_issue_mi =
+        MenuItem::with_id(app, "report_issue", "Report an Issue…", true, None::<&str>)?;
+    let help_menu = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[
+            &MenuCommand::KeyboardShortcuts.menu_item(app)?,
+            &report_issue_mi,
+        ],
+    )?;
+    #[cfg(not(target_os = "macos"))]
+    help_menu.append_items(&[&separator()?, &about_mi])?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let app_menu = Submenu::with_items(
+            app,
+            "Mark Lens",
+            true,
+            &[
+                &about_mi,
+                &separator()?,
+                &PredefinedMenuItem::services(app, None)?,
+                &separator()?,
+                &PredefinedMenuItem::hide(app, None)?,
+                &PredefinedMenuItem::hide_others(app, None)?,
+                &PredefinedMenuItem::show_all(app, None)?,
+                &separator()?,
+                &quit_mi,
+            ],
+        )?;
+        return Menu::with_items(
+            app,
+            &[
+                &app_menu,
+                &file_menu,
+                &edit_menu,
+                &view_menu,
+                &window_menu,
+                &help_menu,
+            ],
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Menu::with_items(
+            app,
+            &[&file_menu, &edit_menu, &view_menu, &window_menu, &help_menu],
+        )
+    }
+}
+
+/// Builds the "Open Recent" submenu.
+///
+/// Each item's id is the file path prefixed with [`RECENT_FILE_ID_PREFIX`].
+/// The submenu ends with a "Clear Recent" item, disabled when the list is empty.
+fn build_recent_submenu(app: &AppHandle) -> Result<Submenu<tauri::Wry>, tauri::Error> {
+    let files = app
+        .state::<tokio::sync::Mutex<RecentFilesState>>()
+        .blocking_lock()
+        .files
+        .clone();
+
+    let submenu = Submenu::with_id(app, "open_recent", "Open Recent", true)?;
+    if files.is_empty() {
+        let empty_mi = MenuItem::with_id(app, "no_recent", "No Recent Files", false, None::<&str>)?;
+        submenu.append(&empty_mi)?;
+    }
+    for file in &files {
+        let id = format!("{}{}", RECENT_FILE_ID_PREFIX, file.path);
+        submenu.append(&MenuItem::with_id(app, id, &file.name, true, None::<&str>)?)?;
+    }
+    submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    submenu.append(&MenuItem::with_id(
+        app,
+        "clear_recent",
+        "Clear Recent",
+        !files.is_empty(),
+        None::<&str>,
+    )?)?;
+
+    Ok(submenu)
+}
+
+/// Builds the tray icon menu: Open, Open Recent and Quit.
+fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    let open_mi = MenuItem::with_id(app, "open", "Open…", true, None::<&str>)?;
+    let open_recent_menu = build_recent_submenu(app)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit_mi = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(app, &[&open_mi, &open_recent_menu, &separator, &quit_mi])
+}
+
+/// Creates the tray icon, which gives access to recent files from the
+/// system tray (Windows, Linux) or menu bar (macOS).
+pub(crate) fn setup_tray(app: &AppHandle) -> Result<(), tauri::Error> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Mark Lens")
+        .menu(&build_tray_menu(app)?)
+        .show_menu_on_left_click(true);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Rebuilds the menu bar and tray menu after the recent files list or key bindings changed.
+pub(crate) fn refresh_recent_menus(app: &AppHandle) {
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = app.set_menu(menu);
+        }
+        Err(e) => eprintln!("[menu] Failed to rebuild menu: {}", e),
+    }
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        match build_tray_menu(app) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!("[menu] Failed to rebuild tray menu: {}", e),
+        }
+    }
+}
+
+// ============================================================================
+// Event Handlers
+// ============================================================================
+
+/// Sets up menu event handlers for all menu items.
+///
+/// Application-level items (About, Quit, New Window, Open, Open Recent,
+/// Clear Recent, Close Window, Toggle Full Screen, Minimize, Zoom, Report an Issue) are
+/// handled here; every [`MenuCommand`] is forwarded to the focused window.
+pub(crate) fn setup_menu_events(app: &AppHandle) {
+    app.on_menu_event(move |app, event| match event.id.as_ref() {
+        "about" => crate::handle_about_event(app),
+        "quit" => crate::shutdown::request_quit(app),
+        "new_window" => {
+            if let Err(e) = windows::create_window(app, None) {
+                eprintln!("[menu] Failed to open window: {}", e);
+            }
+        }
+        "open" => crate::handle_open_event(app),
+        "clear_recent" => {
+            crate::clear_recent_files(app.clone());
+            let _ = app.emit("recent-files-changed", ());
+        }
+        "close_window" => {
+            if let Some(window) = windows::focused_window(app) {
+                crate::shutdown::request_close(app, window);
+            }
+        }
+        "toggle_fullscreen" => {
+            if let Some(window) = windows::focused_window(app) {
+                let is_fullscreen = window.is_fullscreen().unwrap_or(false);
+                let _ = window.set_fullscreen(!is_fullscreen);
+            }
+        }
+        "minimize" => {
+            if let Some(window) = windows::focused_window(app) {
+                let _ = window.minimize();
+            }
+        }
+        "maximize" => {
+            if let Some(window) = windows::focused_window(app) {
+                if window.is_maximized().unwrap_or(false) {
+                    let _ = window.unmaximize();
+                } else {
+                    let _ = window.maximize();
+                }
+            }
+        }
+        "report_issue" => {
+            let _ = opener::open(ISSUES_URL);
+        }
+        id => {
+            if let Some(path) = id.strip_prefix(RECENT_FILE_ID_PREFIX) {
+                crate::handle_open_recent_event(app, path);
+            } else if let Some(command) = MenuCommand::from_id(id) {
+                dispatch_command(app, command);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_command_id_round_trip() {
+        for command in MenuCommand::ALL {
+            assert_eq!(MenuCommand::from_id(command.id()), Some(*command));
+        }
+        assert_eq!(MenuCommand::from_id("unknown"), None);
+    }
+
+    #[test]
+    fn test_menu_command_id_matches_serialized_name() {
+        for command in MenuCommand::ALL {
+            let json = serde_json::to_string(command).unwrap();
+            assert_eq!(json, format!("\"{}\"", command.id()));
+        }
+    }
+
+    #[test]
+    fn test_menu_command_event_serialization() {
+        let event = MenuCommandEvent {
+            id: MenuCommand::SaveAs,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"id":"save_as"}"#
+        );
+    }
+
+    #[test]
+    fn test_menu_command_ids_are_unique() {
+        let mut ids: Vec<&str> = MenuCommand::ALL.iter().map(|c| c.id()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), MenuCommand::ALL.len());
+    }
+}
@@ -0,0 +1,404 @@
+use crate::front_matter;
+use crate::markdown::{self, gfm_options};
+use comrak::nodes::NodeValue;
+use comrak::Arena;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use tauri::AppHandle;
+
+/// Line that starts a generated table of contents
+pub const TOC_START: &str = "<!-- toc -->";
+
+/// Line that ends a generated table of contents
+pub const TOC_END: &str = "<!-- tocstop -->";
+
+/// Heading in the outline of a document, with the headings of its section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineItem {
+    pub level: u8,
+    pub text: String,
+    /// GitHub-compatible anchor, unique within the document
+    pub slug: String,
+    /// 1-based line of the heading
+    pub start_line: usize,
+    /// Last line of the section, before the next heading of the same or a
+    /// higher level
+    pub end_line: usize,
+    pub children: Vec<OutlineItem>,
+}
+
+/// Builds the heading tree of a document.
+///
+/// Both ATX and setext headings are included; lines in code blocks are not
+/// headings. A heading that skips levels is nested under the closest
+/// heading above it with a lower level.
+pub fn outline(content: &str) -> Vec<OutlineItem> {
+    let mut roots = Vec::new();
+    let mut open: Vec<OutlineItem> = Vec::new();
+
+    for heading in markdown::headings(content) {
+        while open.last().is_some_and(|item| item.level >= heading.level) {
+            close(&mut open, &mut roots, heading.line - 1);
+        }
+        open.push(OutlineItem {
+            level: heading.level,
+            text: heading.text,
+            slug: heading.slug,
+            start_line: heading.line,
+            end_line: heading.line,
+            children: Vec::new(),
+        });
+    }
+
+    let last_line = content.lines().count();
+    while !open.is_empty() {
+        close(&mut open, &mut roots, last_line);
+    }
+    roots
+}
+
+/// Ends the innermost open section at `end_line` and adds it to its parent.
+fn close(open: &mut Vec<OutlineItem>, roots: &mut Vec<OutlineItem>, end_line: usize) {
+    let Some(mut item) = open.pop() else {
+        return;
+    };
+    item.end_line = end_line.max(item.start_line);
+    match open.last_mut() {
+        Some(parent) => parent.children.push(item),
+        None => roots.push(item),
+    }
+}
+
+// ============================================================================
+// Table of contents
+// ============================================================================
+
+/// Headings and list style of a generated table of contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TocOptions {
+    /// Highest heading level to include; `2` leaves out the document title
+    pub min_level: u8,
+    /// Lowest heading level to include
+    pub max_level: u8,
+    /// Numbered instead of bulleted list
+    pub ordered: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            min_level: 2,
+            max_level: 6,
+            ordered: false,
+        }
+    }
+}
+
+impl TocOptions {
+    /// Checks that the levels form a valid range.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=6).contains(&self.min_level) || !(1..=6).contains(&self.max_level) {
+            return Err(format!(
+                "Table of contents levels must be between 1 and 6, got {} to {}",
+                self.min_level, self.max_level
+            ));
+        }
+        if self.min_level > self.max_level {
+            return Err(format!(
+                "Table of contents minimum level {} is above the maximum level {}",
+                self.min_level, self.max_level
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Builds a nested Markdown list linking to the headings of a document.
+///
+/// Nesting follows the outline, so a heading that skips levels is indented
+/// only one step below its parent.
+pub fn toc_list(content: &str, options: &TocOptions) -> String {
+    let (marker, indent) = if options.ordered {
+        ("1.", "   ")
+    } else {
+        ("-", "  ")
+    };
+    let mut levels: Vec<u8> = Vec::new();
+    let mut list = String::new();
+    for heading in markdown::headings(content)
+        .into_iter()
+        .filter(|h| (options.min_level..=options.max_level).contains(&h.level))
+    {
+        while levels.last().is_some_and(|&level| level >= heading.level) {
+            levels.pop();
+        }
+        list.push_str(&format!(
+            "{}{} [{}](#{})\n",
+            indent.repeat(levels.len()),
+            marker,
+            escape_link_text(&heading.text),
+            heading.slug
+        ));
+        levels.push(heading.level);
+    }
+    list
+}
+
+/// Escapes characters that would end or break the text of a link.
+fn escape_link_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Generated table of contents, including its markers
+fn toc_block(content: &str, options: &TocOptions) -> String {
+    let list = toc_list(content, options);
+    if list.is_empty() {
+        format!("{}\n{}", TOC_START, TOC_END)
+    } else {
+        format!("{}\n\n{}\n{}", TOC_START, list, TOC_END)
+    }
+}
+
+/// Regenerates the table of contents between the markers.
+///
+/// Returns `None` if the document has no `<!-- toc -->` marker. A missing
+/// `<!-- tocstop -->` is added after the list. The result doesn't change
+/// when updated again.
+pub fn refresh_toc(content: &str, options: &TocOptions) -> Option<String> {
+    let range = toc_range(content)?;
+    let mut updated = String::with_capacity(content.len());
+    updated.push_str(&content[..range.start]);
+    updated.push_str(&toc_block(content, options));
+    updated.push_str(&content[range.end..]);
+    Some(updated)
+}
+
+/// Updates the table of contents, or inserts one if there are no markers.
+///
+/// A new table of contents goes below the document title, or at the top
+/// of the body when the document doesn't start with a top-level heading.
+pub fn update_toc(content: &str, options: &TocOptions) -> String {
+    if let Some(updated) = refresh_toc(content, options) {
+        return updated;
+    }
+
+    let arena = Arena::new();
+    let root = markdown::parse(&arena, content, &gfm_options());
+    let title_end = root.first_child().and_then(|node| {
+        let ast = node.data.borrow();
+        matches!(&ast.value, NodeValue::Heading(h) if h.level == 1)
+            .then_some(ast.sourcepos.end.line)
+    });
+
+    // Below the title, with a blank line, or else after the front matter
+    let (at, gap) = match title_end {
+        Some(line) => (
+            line_spans(content)
+                .nth(line - 1)
+                .map_or(content.len(), |span| span.end),
+            "\n",
+        ),
+        None => (front_matter::body_offset(content), ""),
+    };
+    let (before, after) = content.split_at(at);
+    let newline = if before.is_empty() || before.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    // Keep one blank line between the list and what follows
+    let separator = if after.is_empty() || after.trim_start_matches('\r').starts_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    format!(
+        "{}{}{}{}\n{}{}",
+        before,
+        newline,
+        gap,
+        toc_block(content, options),
+        separator,
+        after
+    )
+}
+
+/// Byte ranges of the lines of `content`, including their line endings.
+fn line_spans(content: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    content.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some(start..*offset)
+    })
+}
+
+/// Finds the text from the start marker to the end of the stop marker, or
+/// to the end of the start marker line when there is no stop marker.
+///
+/// Line endings after the markers are not included; markers in code blocks
+/// are ignored.
+fn toc_range(content: &str) -> Option<Range<usize>> {
+    let arena = Arena::new();
+    let root = markdown::parse(&arena, content, &gfm_options());
+    let code_lines: Vec<_> = root
+        .descendants()
+        .filter_map(|node| {
+            let ast = node.data.borrow();
+            matches!(ast.value, NodeValue::CodeBlock(_))
+                .then(|| ast.sourcepos.start.line..=ast.sourcepos.end.line)
+        })
+        .collect();
+
+    let mut markers = line_spans(content)
+        .enumerate()
+        .filter(|(index, _)| !code_lines.iter().any(|lines| lines.contains(&(index + 1))))
+        .map(|(_, span)| (content[span.clone()].trim(), span));
+    let (_, start) = markers.find(|(line, _)| line.eq_ignore_ascii_case(TOC_START))?;
+    let end = markers
+        .find(|(line, _)| line.eq_ignore_ascii_case(TOC_END))
+        .map_or(start.clone(), |(_, span)| span);
+    let end = content[..end.end].trim_end_matches(['\n', '\r']).len();
+    Some(start.start..end)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the heading tree of a document.
+#[tauri::command]
+pub fn get_outline(content: String) -> Vec<OutlineItem> {
+    outline(&content)
+}
+
+/// Updates or inserts the table of contents of a document, with the options
+/// from the settings unless others are given.
+#[tauri::command]
+pub fn update_document_toc(
+    app: AppHandle,
+    content: String,
+    options: Option<TocOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_else(|| crate::settings::current(&app).toc);
+    options.validate()?;
+    Ok(update_toc(&content, &options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flattens an outline to `(depth, slug, start_line, end_line)`.
+    fn flatten(items: &[OutlineItem], depth: usize, out: &mut Vec<(usize, String, usize, usize)>) {
+        for item in items {
+            out.push((depth, item.slug.clone(), item.start_line, item.end_line));
+            flatten(&item.children, depth + 1, out);
+        }
+    }
+
+    #[test]
+    fn test_outline_tree() {
+        let content = "# Guide\n\nIntro\n\n## Install\n\n```sh\n# not a heading\n```\n\nUsage\n-----\n\n### Flags\n\n# Appendix\n\n## Install\n";
+        let mut items = Vec::new();
+        flatten(&outline(content), 0, &mut items);
+        assert_eq!(
+            items,
+            vec![
+                (0, "guide".to_string(), 1, 15),
+                (1, "install".to_string(), 5, 10),
+                (1, "usage".to_string(), 11, 15),
+                (2, "flags".to_string(), 14, 15),
+                (0, "appendix".to_string(), 16, 18),
+                (1, "install-1".to_string(), 18, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outline_skipped_levels() {
+        let content = "### Deep\n\n# Top\n\n### Skipped\n## Second\n";
+        let outline = outline(content);
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].level, 3);
+        let children: Vec<(u8, &str)> = outline[1]
+            .children
+            .iter()
+            .map(|item| (item.level, item.text.as_str()))
+            .collect();
+        assert_eq!(children, vec![(3, "Skipped"), (2, "Second")]);
+        assert!(super::outline("No headings\n").is_empty());
+    }
+
+    #[test]
+    fn test_insert_toc_below_title() {
+        let content = "# Project\n\nIntro\n\n## Install\n\n### From source\n\n## Usage [beta]\n";
+        let updated = update_toc(content, &TocOptions::default());
+        assert_eq!(
+            updated,
+            "# Project\n\n<!-- toc -->\n\n- [Install](#install)\n  - [From source](#from-source)\n- [Usage \\[beta\\]](#usage-beta)\n\n<!-- tocstop -->\n\nIntro\n\n## Install\n\n### From source\n\n## Usage [beta]\n"
+        );
+        assert_eq!(update_toc(&updated, &TocOptions::default()), updated);
+        assert_eq!(
+            refresh_toc(&updated, &TocOptions::default()).as_ref(),
+            Some(&updated)
+        );
+    }
+
+    #[test]
+    fn test_update_toc_between_markers() {
+        let options = TocOptions {
+            min_level: 1,
+            max_level: 2,
+            ordered: true,
+        };
+        let content = "Intro\n\n```\n<!-- toc -->\n```\n\n<!-- TOC -->\n- [Old](#old)\n<!-- tocstop -->\n\n# A\n\n#### Deep\n\n## B\n";
+        let updated = update_toc(content, &options);
+        assert_eq!(
+            updated,
+            "Intro\n\n```\n<!-- toc -->\n```\n\n<!-- toc -->\n\n1. [A](#a)\n   1. [B](#b)\n\n<!-- tocstop -->\n\n# A\n\n#### Deep\n\n## B\n"
+        );
+        assert_eq!(update_toc(&updated, &options), updated);
+    }
+
+    #[test]
+    fn test_refresh_toc_adds_missing_stop_marker() {
+        let content = "<!-- toc -->\n\n## A\n";
+        assert_eq!(
+            refresh_toc(content, &TocOptions::default()).unwrap(),
+            "<!-- toc -->\n\n- [A](#a)\n\n<!-- tocstop -->\n\n## A\n"
+        );
+        assert_eq!(refresh_toc("## A\n", &TocOptions::default()), None);
+        assert_eq!(
+            update_toc("", &TocOptions::default()),
+            "<!-- toc -->\n<!-- tocstop -->\n"
+        );
+        assert_eq!(
+            update_toc("---\ntitle: A\n---\n## A\n", &TocOptions::default()),
+            "---\ntitle: A\n---\n<!-- toc -->\n\n- [A](#a)\n\n<!-- tocstop -->\n\n## A\n"
+        );
+    }
+
+    #[test]
+    fn test_toc_options_validate() {
+        assert!(TocOptions::default().validate().is_ok());
+        let invalid = TocOptions {
+            min_level: 3,
+            max_level: 2,
+            ordered: false,
+        };
+        assert!(invalid.validate().is_err());
+        let invalid = TocOptions {
+            min_level: 0,
+            ..TocOptions::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
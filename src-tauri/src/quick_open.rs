@@ -0,0 +1,496 @@
+use crate::search_index::{is_visible, markdown_files};
+use crate::workspace_watcher;
+use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher, Utf32Str, Utf32String};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Quiet time after a file change before the list is updated
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Files matched per thread; smaller lists are matched on the calling thread
+const CHUNK_SIZE: usize = 8192;
+
+/// Best files of the quick first pass that are scored exactly
+const RESCORED: usize = 512;
+
+/// Score added to the most recently opened file, decreasing for older ones.
+/// About as much as four more matched characters.
+const RECENT_BOOST: u32 = 64;
+
+/// File offered by quick open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuickOpenHit {
+    pub path: String,
+    /// Path relative to the workspace root, as matched
+    pub relative: String,
+    pub score: u32,
+    /// Matched characters of `relative` as 0-based `[start, end)` character
+    /// offsets
+    pub highlights: Vec<(usize, usize)>,
+    pub recent: bool,
+}
+
+/// Markdown file in the workspace
+struct Entry {
+    path: PathBuf,
+    /// Relative path, decoded once so queries don't have to
+    relative: Utf32String,
+}
+
+/// In-memory list of the Markdown files in a workspace
+pub struct FileList {
+    root: PathBuf,
+    extensions: Vec<String>,
+    entries: Vec<Entry>,
+    /// Index of each file in `entries`
+    positions: HashMap<PathBuf, usize>,
+}
+
+impl FileList {
+    /// Lists the Markdown files under `root` that aren't ignored.
+    pub fn load(root: &Path, extensions: Vec<String>) -> Self {
+        let mut list = Self {
+            root: root.to_path_buf(),
+            entries: Vec::new(),
+            positions: HashMap::new(),
+            extensions,
+        };
+        let mut paths: Vec<PathBuf> = markdown_files(root, &list.extensions).into_keys().collect();
+        paths.sort();
+        for path in paths {
+            list.insert(path);
+        }
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn insert(&mut self, path: PathBuf) {
+        if self.positions.contains_key(&path) {
+            return;
+        }
+        let relative = path
+            .strip_prefix(&self.root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        self.positions.insert(path.clone(), self.entries.len());
+        self.entries.push(Entry {
+            path,
+            relative: relative.into(),
+        });
+    }
+
+    /// Updates the list for paths reported by the file watcher.
+    ///
+    /// Paths may be files or folders that were created, moved or removed.
+    pub fn update_paths(&mut self, paths: &[PathBuf]) {
+        let before = self.entries.len();
+        self.entries.retain(|entry| {
+            !paths.iter().any(|path| entry.path.starts_with(path)) || entry.path.exists()
+        });
+        if self.entries.len() != before {
+            self.positions = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| (entry.path.clone(), i))
+                .collect();
+        }
+
+        for path in paths {
+            if !is_visible(&self.root, path) {
+                continue;
+            }
+            if path.is_dir() {
+                let mut found: Vec<PathBuf> =
+                    markdown_files(path, &self.extensions).into_keys().collect();
+                found.sort();
+                for path in found {
+                    self.insert(path);
+                }
+            } else if crate::is_markdown_file(&path.to_string_lossy(), &self.extensions) {
+                self.insert(path.clone());
+            }
+        }
+    }
+
+    /// Finds the files matching `query`, best first.
+    ///
+    /// Matching is fuzzy over the relative path, with bonuses for matches at
+    /// the start of path segments and words. Recently opened files, most
+    /// recent first in `recent`, rank higher. An empty query lists the
+    /// recent files followed by the others.
+    ///
+    /// Large lists are first scored with a quick greedy match, and only the
+    /// best of those are scored exactly, as fzf does.
+    pub fn query(&self, query: &str, recent: &[String], limit: usize) -> Vec<QuickOpenHit> {
+        let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+        let config = Config::DEFAULT.match_paths();
+        let boosts: HashMap<usize, u32> = recent
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, path)| {
+                let boost = RECENT_BOOST * (recent.len() - rank) as u32 / recent.len() as u32;
+                Some((*self.positions.get(Path::new(path))?, boost))
+            })
+            .collect();
+        let boost = |i: usize| boosts.get(&i).copied().unwrap_or(0);
+
+        let candidates: Vec<usize> = if pattern.atoms.is_empty() {
+            let mut recent: Vec<usize> = boosts.keys().copied().collect();
+            recent.sort_unstable_by_key(|&i| Reverse(boost(i)));
+            let others = (0..self.entries.len()).filter(|i| !boosts.contains_key(i));
+            recent.into_iter().chain(others).take(limit).collect()
+        } else {
+            let score_chunk = |chunk: &[Entry], offset: usize| {
+                let mut matcher = Matcher::new(config.clone());
+                chunk
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, entry)| {
+                        let score =
+                            greedy_score(&pattern.atoms, entry.relative.slice(..), &mut matcher)?;
+                        Some((score + boost(offset + i), offset + i))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let mut scored: Vec<(u32, usize)> = if self.entries.len() <= CHUNK_SIZE {
+                score_chunk(&self.entries, 0)
+            } else {
+                std::thread::scope(|scope| {
+                    let score_chunk = &score_chunk;
+                    self.entries
+                        .chunks(CHUNK_SIZE)
+                        .enumerate()
+                        .map(|(n, chunk)| scope.spawn(move || score_chunk(chunk, n * CHUNK_SIZE)))
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .flat_map(|handle| handle.join().unwrap())
+                        .collect()
+                })
+            };
+            let keep = RESCORED.max(limit);
+            if scored.len() > keep {
+                scored.select_nth_unstable_by_key(keep - 1, |&(score, _)| Reverse(score));
+                scored.truncate(keep);
+            }
+            scored.into_iter().map(|(_, i)| i).collect()
+        };
+
+        let mut matcher = Matcher::new(config);
+        let mut indices = Vec::new();
+        let mut hits: Vec<QuickOpenHit> = candidates
+            .into_iter()
+            .filter_map(|i| {
+                let entry = &self.entries[i];
+                indices.clear();
+                let score =
+                    pattern.indices(entry.relative.slice(..), &mut matcher, &mut indices)?;
+                indices.sort_unstable();
+                indices.dedup();
+                Some(QuickOpenHit {
+                    path: entry.path.to_string_lossy().to_string(),
+                    relative: entry.relative.to_string(),
+                    score: score + boost(i),
+                    highlights: ranges(&indices),
+                    recent: boosts.contains_key(&i),
+                })
+            })
+            .collect();
+        // Best score first, then shorter and alphabetically earlier paths
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(a.relative.len().cmp(&b.relative.len()))
+                .then_with(|| a.relative.cmp(&b.relative))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Scores a path like [`Pattern::score`], but with greedy instead of optimal
+/// fuzzy matching.
+///
+/// The score approximates the exact one, and a path matches here exactly
+/// when it matches there.
+fn greedy_score(atoms: &[Atom], haystack: Utf32Str<'_>, matcher: &mut Matcher) -> Option<u32> {
+    let mut score = 0;
+    for atom in atoms {
+        score += if atom.kind == AtomKind::Fuzzy && !atom.negative {
+            let needle = atom.needle_text();
+            // As chosen by `CaseMatching::Smart` and `Normalization::Smart`
+            matcher.config.ignore_case = !needle.chars().any(char::is_uppercase);
+            matcher.config.normalize = needle.chars().all(|c| c.is_ascii());
+            matcher.fuzzy_match_greedy(haystack, needle)?
+        } else {
+            atom.score(haystack, matcher)?
+        } as u32;
+    }
+    Some(score)
+}
+
+/// Joins sorted character indices into `[start, end)` ranges.
+fn ranges(indices: &[u32]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &i in indices {
+        let i = i as usize;
+        match ranges.last_mut() {
+            Some(last) if last.1 == i => last.1 = i + 1,
+            _ => ranges.push((i, i + 1)),
+        }
+    }
+    ranges
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Name of quick open among the subscribers of the workspace watcher
+const WATCH_SUBSCRIBER: &str = "quick_open";
+
+/// File list that is kept up to date while its workspace is open
+struct OpenFileList {
+    root: PathBuf,
+    files: Arc<Mutex<FileList>>,
+}
+
+/// File list of the open workspace
+#[derive(Default)]
+pub struct QuickOpenState {
+    current: Mutex<Option<OpenFileList>>,
+}
+
+/// Applies changes reported by the watcher until quick open is closed.
+fn run_updates(files: Arc<Mutex<FileList>>, changes: mpsc::Receiver<PathBuf>) {
+    while let Some(paths) = workspace_watcher::next_batch(&changes, DEBOUNCE) {
+        files.lock().unwrap().update_paths(&paths);
+    }
+}
+
+/// Lists the Markdown files of a workspace for quick open and keeps the
+/// list up to date. Returns the number of files.
+#[tauri::command(async)]
+pub fn open_quick_open(app: AppHandle, root: String) -> Result<usize, String> {
+    let root = PathBuf::from(root)
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    let state = app.state::<QuickOpenState>();
+    let mut current = state.current.lock().unwrap();
+    if let Some(open) = current.as_ref() {
+        if open.root == root {
+            return Ok(open.files.lock().unwrap().len());
+        }
+    }
+    if let Some(previous) = current.take() {
+        workspace_watcher::unsubscribe(&app, &previous.root, WATCH_SUBSCRIBER);
+    }
+
+    let extensions = crate::settings::current(&app).markdown_extensions;
+    // Subscribed first, so that changes made while listing aren't missed
+    let changes = workspace_watcher::subscribe(&app, &root, WATCH_SUBSCRIBER)?;
+    let files = Arc::new(Mutex::new(FileList::load(&root, extensions)));
+    let count = files.lock().unwrap().len();
+    *current = Some(OpenFileList {
+        root,
+        files: files.clone(),
+    });
+    std::thread::spawn(move || run_updates(files, changes));
+    Ok(count)
+}
+
+/// Forgets the workspace file list.
+#[tauri::command]
+pub fn close_quick_open(app: AppHandle) {
+    let open = app.state::<QuickOpenState>().current.lock().unwrap().take();
+    if let Some(open) = open {
+        workspace_watcher::unsubscribe(&app, &open.root, WATCH_SUBSCRIBER);
+    }
+}
+
+/// Resolves a recent file's path like the workspace root, so that it
+/// matches the listed files. Paths that no longer exist are kept as they are.
+fn canonical(path: String) -> String {
+    Path::new(&path)
+        .canonicalize()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(path)
+}
+
+/// Finds workspace files matching a fuzzy query, best first.
+#[tauri::command]
+pub async fn quick_open(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenHit>, String> {
+    let files = app
+        .state::<QuickOpenState>()
+        .current
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|open| open.files.clone())
+        .ok_or("No workspace is open")?;
+    let recent: Vec<String> = app
+        .state::<tokio::sync::Mutex<crate::RecentFilesState>>()
+        .lock()
+        .await
+        .files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect();
+    let recent: Vec<String> = recent.into_iter().map(canonical).collect();
+    let files = files.lock().unwrap();
+    Ok(files.query(&query, &recent, limit.unwrap_or(50)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn extensions() -> Vec<String> {
+        crate::settings::Settings::default().markdown_extensions
+    }
+
+    fn relative(hits: &[QuickOpenHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.relative.as_str()).collect()
+    }
+
+    #[test]
+    fn test_query_ranks_and_highlights() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs/guides")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        for file in [
+            "README.md",
+            "docs/guides/getting-started.md",
+            "docs/glossary.md",
+            "docs/changelog.md",
+            "node_modules/readme.md",
+            "notes.txt",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let list = FileList::load(root, extensions());
+        assert_eq!(list.len(), 4);
+
+        let hits = list.query("gs", &[], 10);
+        assert_eq!(relative(&hits)[0], "docs/guides/getting-started.md");
+        assert_eq!(list.query("readme", &[], 10)[0].highlights, vec![(0, 6)]);
+        assert!(list.query("zzz", &[], 10).is_empty());
+        assert_eq!(list.query("md", &[], 2).len(), 2);
+
+        // Recently opened files rank higher, and come first with no query
+        let recent = vec![root.join("docs/changelog.md").to_string_lossy().to_string()];
+        let hits = list.query("d", &recent, 10);
+        assert_eq!(relative(&hits)[0], "docs/changelog.md");
+        assert!(hits[0].recent);
+        let hits = list.query("", &recent, 10);
+        assert_eq!(hits.len(), 4);
+        assert_eq!(relative(&hits)[0], "docs/changelog.md");
+    }
+
+    #[test]
+    fn test_update_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::write(root.join("old/a.md"), "").unwrap();
+        fs::write(root.join("b.md"), "").unwrap();
+        let mut list = FileList::load(root, extensions());
+        assert_eq!(list.len(), 2);
+
+        fs::rename(root.join("old"), root.join("new")).unwrap();
+        fs::write(root.join("c.md"), "").unwrap();
+        fs::remove_file(root.join("b.md")).unwrap();
+        list.update_paths(&[
+            root.join("old"),
+            root.join("new"),
+            root.join("c.md"),
+            root.join("b.md"),
+        ]);
+        let mut files: Vec<String> = list
+            .query("", &[], 10)
+            .into_iter()
+            .map(|hit| hit.relative)
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["c.md", "new/a.md"]);
+    }
+
+    #[test]
+    fn test_canonical_recent_paths_match() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/a.md"), "").unwrap();
+        fs::write(root.join("b.md"), "").unwrap();
+        let list = FileList::load(&root, extensions());
+
+        let recent = root.join("docs/../docs/a.md").to_string_lossy().to_string();
+        let hits = list.query("", &[canonical(recent)], 10);
+        assert_eq!(relative(&hits)[0], "docs/a.md");
+        assert!(hits[0].recent);
+
+        let gone = root.join("gone.md").to_string_lossy().to_string();
+        assert_eq!(canonical(gone.clone()), gone);
+    }
+
+    #[test]
+    fn test_query_is_fast_on_large_lists() {
+        let mut list = FileList {
+            root: PathBuf::from("/ws"),
+            extensions: extensions(),
+            entries: Vec::new(),
+            positions: HashMap::new(),
+        };
+        for i in 0..50_000 {
+            list.insert(PathBuf::from(format!(
+                "/ws/area-{}/project-{}/notes/meeting-{i}.md",
+                i % 37,
+                i % 101
+            )));
+        }
+
+        // Unoptimized test builds are much slower than release builds, and
+        // the chunks of the list are only matched in parallel on several cores
+        let budget = if cfg!(debug_assertions) {
+            Duration::from_millis(200)
+        } else {
+            Duration::from_millis(10)
+        };
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let budget = budget * 4 / cores.clamp(1, 4) as u32;
+        let fastest = (0..5)
+            .map(|_| {
+                let started = std::time::Instant::now();
+                assert!(!list.query("pro12 meet", &[], 50).is_empty());
+                started.elapsed()
+            })
+            .min()
+            .unwrap();
+        assert!(fastest < budget, "query took {fastest:?}");
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(ranges(&[0, 1, 2, 5, 7, 8]), vec![(0, 3), (5, 6), (7, 9)]);
+        assert!(ranges(&[]).is_empty());
+    }
+}
@@ -0,0 +1,729 @@
+use crate::fs::content_hash;
+use crate::search::{self, SearchOptions};
+use grep_matcher::{Captures, Matcher};
+use grep_regex::RegexMatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Directory inside the app data dir that holds undo manifests
+const UNDO_DIR: &str = "replace-undo";
+
+/// Undo manifests kept; older ones are removed when a new one is written
+const MAX_UNDO_MANIFESTS: usize = 20;
+
+/// Region of a line, or of consecutive lines, changed by a replacement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineChange {
+    /// 1-based line number of the first line
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Replacements in one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileReplacement {
+    pub path: String,
+    /// Fingerprint of the file before replacing
+    pub hash: String,
+    /// Fingerprint after replacing, `None` in a dry run
+    pub new_hash: Option<String>,
+    /// Number of matches replaced
+    pub replacements: usize,
+    pub changes: Vec<LineChange>,
+}
+
+/// File that was left untouched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Result of [`replace_in_workspace`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReplaceOutcome {
+    pub files: Vec<FileReplacement>,
+    pub replacements: usize,
+    pub skipped: Vec<SkippedFile>,
+    /// Id to pass to [`undo_replace`], `None` in a dry run or when nothing
+    /// was changed
+    pub undo_id: Option<String>,
+}
+
+/// Result of [`undo_replace`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UndoOutcome {
+    pub restored: Vec<String>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Original content of a file changed by a replace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UndoEntry {
+    path: String,
+    original: String,
+    /// Fingerprint right after replacing, so later edits aren't overwritten
+    replaced_hash: String,
+}
+
+/// Everything needed to revert one replace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UndoManifest {
+    id: String,
+    query: String,
+    replacement: String,
+    created_at: u64,
+    files: Vec<UndoEntry>,
+}
+
+/// Replacement of the bytes `start..end`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// File with its content before and after replacing
+struct PlannedFile {
+    original: String,
+    replaced: String,
+    preview: FileReplacement,
+}
+
+/// Finds the matches in `content` and what to replace them with.
+///
+/// In regex mode the replacement may refer to capture groups as `$1` or
+/// `$name`; otherwise it is used literally.
+fn find_edits(
+    matcher: &RegexMatcher,
+    content: &str,
+    replacement: &str,
+    regex: bool,
+) -> Result<Vec<Edit>, String> {
+    let haystack = content.as_bytes();
+    let mut caps = matcher.new_captures().map_err(|e| e.to_string())?;
+    let mut edits = Vec::new();
+    let mut invalid = false;
+    matcher
+        .captures_iter(haystack, &mut caps, |caps| {
+            let Some(m) = caps.get(0) else {
+                return true;
+            };
+            let text = if regex {
+                let mut dst = Vec::new();
+                caps.interpolate(
+                    |name| matcher.capture_index(name),
+                    haystack,
+                    replacement.as_bytes(),
+                    &mut dst,
+                );
+                match String::from_utf8(dst) {
+                    Ok(text) => text,
+                    Err(_) => {
+                        invalid = true;
+                        return false;
+                    }
+                }
+            } else {
+                replacement.to_string()
+            };
+            edits.push(Edit {
+                start: m.start(),
+                end: m.end(),
+                text,
+            });
+            true
+        })
+        .map_err(|e| e.to_string())?;
+    if invalid
+        || edits
+            .iter()
+            .any(|e| !content.is_char_boundary(e.start) || !content.is_char_boundary(e.end))
+    {
+        return Err("Replacement would produce invalid UTF-8".to_string());
+    }
+    Ok(edits)
+}
+
+/// Applies edits to the part of the content starting at byte `offset`.
+fn apply_edits(text: &str, offset: usize, edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for edit in edits {
+        result.push_str(&text[pos..edit.start - offset]);
+        result.push_str(&edit.text);
+        pos = edit.end - offset;
+    }
+    result.push_str(&text[pos..]);
+    result
+}
+
+/// Lines touched by the edits, before and after replacing.
+///
+/// Edits on the same line, or in a match spanning several lines, are
+/// reported as one change.
+fn line_changes(content: &str, edits: &[Edit]) -> Vec<LineChange> {
+    let line_start = |pos: usize| content[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = |pos: usize| content[pos..].find('\n').map_or(content.len(), |i| pos + i);
+
+    // Line-aligned byte regions with the indices of their edits
+    let mut regions: Vec<(usize, usize, usize, usize)> = Vec::new();
+    for (i, edit) in edits.iter().enumerate() {
+        let start = line_start(edit.start);
+        let end = line_end(if edit.end > edit.start {
+            edit.end - 1
+        } else {
+            edit.start
+        });
+        match regions.last_mut() {
+            Some(last) if start <= last.1 => {
+                last.1 = last.1.max(end);
+                last.3 = i + 1;
+            }
+            _ => regions.push((start, end, i, i + 1)),
+        }
+    }
+
+    let mut line = 1;
+    let mut counted = 0;
+    regions
+        .into_iter()
+        .map(|(start, end, first, last)| {
+            line += content[counted..start].matches('\n').count();
+            counted = start;
+            let before = &content[start..end];
+            LineChange {
+                line,
+                before: before.to_string(),
+                after: apply_edits(before, start, &edits[first..last]),
+            }
+        })
+        .collect()
+}
+
+/// Works out the replacements in every file under `root` that matches.
+fn plan(
+    root: &Path,
+    query: &str,
+    replacement: &str,
+    options: &SearchOptions,
+    extensions: &[String],
+) -> Result<(Vec<PlannedFile>, Vec<SkippedFile>), String> {
+    let matcher = search::build_matcher(query, options)?;
+    let options = SearchOptions {
+        context_lines: 0,
+        max_results: usize::MAX,
+        ..options.clone()
+    };
+    let paths = Mutex::new(Vec::new());
+    search::search(
+        root,
+        query,
+        &options,
+        extensions,
+        &AtomicBool::new(false),
+        |path, _| paths.lock().unwrap().push(path),
+    )?;
+    let mut paths = paths.into_inner().unwrap();
+    paths.sort();
+
+    let mut planned = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let edits = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                let edits = find_edits(&matcher, &content, replacement, options.regex)?;
+                Ok((content, edits))
+            });
+        let (original, edits) = match edits {
+            Ok(result) => result,
+            Err(reason) => {
+                skipped.push(SkippedFile { path, reason });
+                continue;
+            }
+        };
+        if edits.is_empty() {
+            continue;
+        }
+        let replaced = apply_edits(&original, 0, &edits);
+        planned.push(PlannedFile {
+            preview: FileReplacement {
+                path,
+                hash: content_hash(&original),
+                new_hash: None,
+                replacements: edits.len(),
+                changes: line_changes(&original, &edits),
+            },
+            original,
+            replaced,
+        });
+    }
+    Ok((planned, skipped))
+}
+
+/// Writes a file through a temporary file, so it is never left half written.
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Not a file: {}", path.display()))?;
+    let tmp = path.with_file_name(format!(".{}.replace.tmp", name.to_string_lossy()));
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = fs::set_permissions(&tmp, metadata.permissions());
+    }
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+/// Writes the planned files whose fingerprint is still the expected one.
+///
+/// The undo manifest is written to `undo_dir` before any file is touched, so
+/// that whatever gets replaced can be undone; files that fail to write are
+/// dropped from it afterwards. Returns the written files and the undo id,
+/// if any file was written.
+fn apply(
+    undo_dir: &Path,
+    query: &str,
+    replacement: &str,
+    planned: Vec<PlannedFile>,
+    fingerprints: &HashMap<String, String>,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<(Vec<FileReplacement>, Option<String>), String> {
+    let mut unchanged = Vec::new();
+    for file in planned {
+        let path = &file.preview.path;
+        if fingerprints
+            .get(path)
+            .is_some_and(|expected| *expected != file.preview.hash)
+        {
+            skipped.push(SkippedFile {
+                path: path.clone(),
+                reason: "File changed since it was loaded".to_string(),
+            });
+        } else {
+            unchanged.push(file);
+        }
+    }
+    if unchanged.is_empty() {
+        return Ok((Vec::new(), None));
+    }
+
+    let created_at = now_millis();
+    let mut manifest = UndoManifest {
+        id: format!("{}-{}", created_at, std::process::id()),
+        query: query.to_string(),
+        replacement: replacement.to_string(),
+        created_at,
+        files: unchanged
+            .iter()
+            .map(|file| UndoEntry {
+                path: file.preview.path.clone(),
+                original: file.original.clone(),
+                replaced_hash: content_hash(&file.replaced),
+            })
+            .collect(),
+    };
+    write_manifest(undo_dir, &manifest)?;
+
+    let mut files = Vec::new();
+    for mut file in unchanged {
+        let path = file.preview.path.clone();
+        if let Err(reason) = write_atomic(Path::new(&path), &file.replaced) {
+            skipped.push(SkippedFile { path, reason });
+            continue;
+        }
+        file.preview.new_hash = Some(content_hash(&file.replaced));
+        files.push(file.preview);
+    }
+
+    if files.len() < manifest.files.len() {
+        manifest
+            .files
+            .retain(|entry| files.iter().any(|file| file.path == entry.path));
+        if manifest.files.is_empty() {
+            let _ = fs::remove_file(manifest_file(undo_dir, &manifest.id)?);
+            return Ok((files, None));
+        }
+        // Left as is on failure: undo skips files that weren't replaced, since
+        // their content doesn't match
+        let _ = write_manifest(undo_dir, &manifest);
+    }
+    Ok((files, Some(manifest.id)))
+}
+
+/// Restores the files of a manifest that weren't edited since the replace.
+fn undo(manifest: &UndoManifest) -> UndoOutcome {
+    let mut outcome = UndoOutcome::default();
+    for entry in &manifest.files {
+        let current = match fs::read_to_string(&entry.path) {
+            Ok(content) => content,
+            Err(e) => {
+                outcome.skipped.push(SkippedFile {
+                    path: entry.path.clone(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        if content_hash(&current) != entry.replaced_hash {
+            outcome.skipped.push(SkippedFile {
+                path: entry.path.clone(),
+                reason: "File changed after the replace".to_string(),
+            });
+            continue;
+        }
+        match write_atomic(Path::new(&entry.path), &entry.original) {
+            Ok(()) => outcome.restored.push(entry.path.clone()),
+            Err(reason) => outcome.skipped.push(SkippedFile {
+                path: entry.path.clone(),
+                reason,
+            }),
+        }
+    }
+    outcome
+}
+
+/// Get the undo manifest directory, creating it if needed
+fn undo_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(UNDO_DIR);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Get the manifest file for an undo id
+///
+/// Ids are used as file names, so only ASCII alphanumerics, `-` and `_` are accepted.
+fn manifest_file(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid undo id: {}", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// Writes a manifest and removes the oldest ones beyond [`MAX_UNDO_MANIFESTS`].
+fn write_manifest(dir: &Path, manifest: &UndoManifest) -> Result<(), String> {
+    let json = serde_json::to_string(manifest).map_err(|e| e.to_string())?;
+    write_atomic(&manifest_file(dir, &manifest.id)?, &json)?;
+
+    let mut manifests: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            let manifest: UndoManifest = serde_json::from_str(&content).ok()?;
+            Some((manifest.created_at, path))
+        })
+        .collect();
+    manifests.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+    for (_, path) in manifests.into_iter().skip(MAX_UNDO_MANIFESTS) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Replaces `query` in every matching file under `root`.
+///
+/// With `dry_run` nothing is written and the result lists each change with
+/// the lines before and after. Otherwise each file is replaced atomically.
+/// Files listed in `fingerprints` are only changed if their content still
+/// has that fingerprint, so pass the hashes from the dry run and those of
+/// open documents; changed files are skipped. The returned `undo_id`
+/// reverts the whole replace with [`undo_replace`].
+#[tauri::command(async)]
+pub fn replace_in_workspace(
+    app: AppHandle,
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    dry_run: bool,
+    fingerprints: Option<HashMap<String, String>>,
+) -> Result<ReplaceOutcome, String> {
+    let options = options.unwrap_or_default();
+    let extensions = crate::settings::current(&app).markdown_extensions;
+    let (planned, mut skipped) = plan(
+        Path::new(&root),
+        &query,
+        &replacement,
+        &options,
+        &extensions,
+    )?;
+
+    let (files, undo_id) = if dry_run {
+        (planned.into_iter().map(|file| file.preview).collect(), None)
+    } else {
+        apply(
+            &undo_dir(&app)?,
+            &query,
+            &replacement,
+            planned,
+            &fingerprints.unwrap_or_default(),
+            &mut skipped,
+        )?
+    };
+
+    Ok(ReplaceOutcome {
+        replacements: files.iter().map(|file| file.replacements).sum(),
+        files,
+        skipped,
+        undo_id,
+    })
+}
+
+/// Reverts a replace made by [`replace_in_workspace`].
+///
+/// Files edited after the replace are left alone and reported as skipped.
+#[tauri::command]
+pub fn undo_replace(app: AppHandle, id: String) -> Result<UndoOutcome, String> {
+    let file = manifest_file(&undo_dir(&app)?, &id)?;
+    let content = fs::read_to_string(&file).map_err(|e| e.to_string())?;
+    let manifest: UndoManifest = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let outcome = undo(&manifest);
+    fs::remove_file(&file).map_err(|e| e.to_string())?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn extensions() -> Vec<String> {
+        crate::settings::Settings::default().markdown_extensions
+    }
+
+    fn edits(content: &str, query: &str, replacement: &str, regex: bool) -> Vec<Edit> {
+        let options = SearchOptions {
+            regex,
+            ..SearchOptions::default()
+        };
+        let matcher = search::build_matcher(query, &options).unwrap();
+        find_edits(&matcher, content, replacement, regex).unwrap()
+    }
+
+    #[test]
+    fn test_find_edits_and_line_changes() {
+        let content = "# Acme\n\nAcme makes acme tools.\nNothing here.\nLast acme\n";
+        let found = edits(content, "acme", "Globex", false);
+        assert_eq!(found.len(), 4);
+        assert_eq!(
+            apply_edits(content, 0, &found),
+            "# Globex\n\nGlobex makes Globex tools.\nNothing here.\nLast Globex\n"
+        );
+
+        let changes = line_changes(content, &found);
+        assert_eq!(
+            changes,
+            vec![
+                LineChange {
+                    line: 1,
+                    before: "# Acme".to_string(),
+                    after: "# Globex".to_string(),
+                },
+                LineChange {
+                    line: 3,
+                    before: "Acme makes acme tools.".to_string(),
+                    after: "Globex makes Globex tools.".to_string(),
+                },
+                LineChange {
+                    line: 5,
+                    before: "Last acme".to_string(),
+                    after: "Last Globex".to_string(),
+                },
+            ]
+        );
+
+        // Capture groups in regex mode, literal `$` otherwise
+        let found = edits("v1.2 and v3.4", r"v(\d)\.(\d)", "v$2.$1", true);
+        assert_eq!(apply_edits("v1.2 and v3.4", 0, &found), "v2.1 and v4.3");
+        let found = edits("cost: 5", "5", "$5", false);
+        assert_eq!(apply_edits("cost: 5", 0, &found), "cost: $5");
+
+        // A match spanning lines is one change
+        let content = "one\ntwo\nthree\n";
+        let found = edits(content, r"o\nt", "o t", true);
+        let changes = line_changes(content, &found);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].line, 2);
+        assert_eq!(changes[0].before, "two\nthree");
+        assert_eq!(changes[0].after, "two three");
+    }
+
+    #[test]
+    fn test_replace_apply_and_undo() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let a = root.join("a.md");
+        let b = root.join("b.md");
+        let c = root.join("c.md");
+        fs::write(&a, "Acme docs\n").unwrap();
+        fs::write(&b, "Use Acme.\n").unwrap();
+        fs::write(&c, "Nothing\n").unwrap();
+        fs::write(root.join("notes.txt"), "Acme\n").unwrap();
+
+        let (planned, skipped) = plan(
+            root,
+            "Acme",
+            "Globex",
+            &SearchOptions::default(),
+            &extensions(),
+        )
+        .unwrap();
+        assert!(skipped.is_empty());
+        let paths: Vec<&str> = planned.iter().map(|f| f.preview.path.as_str()).collect();
+        assert_eq!(paths, vec![a.to_str().unwrap(), b.to_str().unwrap()]);
+
+        // b.md is open with a buffer based on an older version
+        let fingerprints = HashMap::from([
+            (
+                a.to_string_lossy().to_string(),
+                planned[0].preview.hash.clone(),
+            ),
+            (b.to_string_lossy().to_string(), content_hash("Use Acme\n")),
+        ]);
+        let undo_dir = tempdir().unwrap();
+        let mut skipped = Vec::new();
+        let (files, undo_id) = apply(
+            undo_dir.path(),
+            "Acme",
+            "Globex",
+            planned,
+            &fingerprints,
+            &mut skipped,
+        )
+        .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_hash, Some(content_hash("Globex docs\n")));
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, b.to_string_lossy());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Globex docs\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "Use Acme.\n");
+        assert!(!root.join(".a.md.replace.tmp").exists());
+
+        let file = manifest_file(undo_dir.path(), &undo_id.unwrap()).unwrap();
+        let manifest: UndoManifest =
+            serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        let outcome = undo(&manifest);
+        assert_eq!(outcome.restored, vec![a.to_string_lossy().to_string()]);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Acme docs\n");
+
+        // Edited after the replace, so it is left alone
+        fs::write(&a, "Edited\n").unwrap();
+        let outcome = undo(&manifest);
+        assert!(outcome.restored.is_empty());
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Edited\n");
+    }
+
+    #[test]
+    fn test_replace_anchored_pattern() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        fs::write(&a, "# Title\n\nTODO: one\nsee TODO\nTODO: two\n").unwrap();
+
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let (planned, _) = plan(
+            dir.path(),
+            "^TODO: (.*)$",
+            "- [ ] $1",
+            &options,
+            &extensions(),
+        )
+        .unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(
+            planned[0].replaced,
+            "# Title\n\n- [ ] one\nsee TODO\n- [ ] two\n"
+        );
+
+        let (planned, _) = plan(dir.path(), "Title$", "Heading", &options, &extensions()).unwrap();
+        assert_eq!(
+            planned[0].replaced,
+            "# Heading\n\nTODO: one\nsee TODO\nTODO: two\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_leaves_files_alone_without_undo_manifest() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        fs::write(&a, "Acme\n").unwrap();
+        let (planned, _) = plan(
+            dir.path(),
+            "Acme",
+            "Globex",
+            &SearchOptions::default(),
+            &extensions(),
+        )
+        .unwrap();
+
+        // The undo directory can't be written to
+        let not_a_dir = dir.path().join("undo.txt");
+        fs::write(&not_a_dir, "").unwrap();
+        let mut skipped = Vec::new();
+        assert!(apply(
+            &not_a_dir,
+            "Acme",
+            "Globex",
+            planned,
+            &HashMap::new(),
+            &mut skipped
+        )
+        .is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "Acme\n");
+    }
+
+    #[test]
+    fn test_write_manifest_keeps_newest() {
+        let dir = tempdir().unwrap();
+        for created_at in 0..(MAX_UNDO_MANIFESTS as u64 + 2) {
+            let manifest = UndoManifest {
+                id: format!("{}-1", created_at),
+                query: String::new(),
+                replacement: String::new(),
+                created_at,
+                files: Vec::new(),
+            };
+            write_manifest(dir.path(), &manifest).unwrap();
+        }
+        assert_eq!(
+            fs::read_dir(dir.path()).unwrap().count(),
+            MAX_UNDO_MANIFESTS
+        );
+        assert!(!dir.path().join("0-1.json").exists());
+        assert!(!dir.path().join("1-1.json").exists());
+        assert!(dir.path().join("2-1.json").exists());
+        assert!(manifest_file(dir.path(), "../x").is_err());
+    }
+}
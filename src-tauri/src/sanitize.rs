@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// How much raw HTML a document may use.
+///
+/// Scripts, event handlers, frames, plugins and `javascript:` URLs are
+/// removed at every level; trust only unlocks presentational markup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    /// Formatting tags, `http(s)` and `mailto` links, and what the
+    /// Markdown renderer itself produces
+    #[default]
+    Untrusted,
+    /// Adds `class`, `id` and inline styles on any tag, media elements and
+    /// `file:` links
+    Trusted,
+}
+
+/// Tags allowed on top of ammonia's defaults, which the renderer produces
+const RENDERER_TAGS: &[&str] = &["input", "section"];
+
+/// Tags allowed only in trusted documents
+const TRUSTED_TAGS: &[&str] = &["audio", "video", "source", "picture"];
+
+/// URL schemes for links and images in untrusted documents
+const UNTRUSTED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Additional URL schemes for trusted documents
+const TRUSTED_SCHEMES: &[&str] = &["file"];
+
+/// Sanitizes rendered HTML according to `trust`.
+pub fn sanitize(html: &str, trust: TrustLevel) -> String {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(RENDERER_TAGS)
+        .url_schemes(UNTRUSTED_SCHEMES.iter().copied().collect())
+        .add_generic_attributes(&["data-sourcepos"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("section", &["class", "data-footnotes"])
+        .add_tag_attributes("sup", &["class"])
+        .add_tag_attributes(
+            "a",
+            &[
+                "id",
+                "class",
+                "aria-label",
+                "data-footnote-ref",
+                "data-footnote-backref",
+            ],
+        )
+        .add_tag_attributes("li", &["id"]);
+
+    match trust {
+        TrustLevel::Untrusted => {
+            builder.attribute_filter(|element, attribute, value| {
+                untrusted_attribute(element, attribute, value).then_some(Cow::Borrowed(value))
+            });
+        }
+        TrustLevel::Trusted => {
+            builder
+                .add_tags(TRUSTED_TAGS)
+                .add_url_schemes(TRUSTED_SCHEMES)
+                .add_generic_attributes(&["class", "id", "style", "align"])
+                .add_tag_attributes("audio", &["src", "controls", "loop", "muted"])
+                .add_tag_attributes("video", &["src", "controls", "loop", "muted", "poster"])
+                .add_tag_attributes("source", &["src", "type", "srcset", "media"])
+                .attribute_filter(|element, attribute, value| {
+                    trusted_attribute(element, attribute, value).then_some(Cow::Borrowed(value))
+                });
+        }
+    }
+
+    builder.clean(html).to_string()
+}
+
+/// Checks an attribute value the renderer produces, in untrusted documents.
+///
+/// Classes and ids are limited to the ones used for code languages and
+/// footnotes, so a document can't restyle or clobber the app.
+fn untrusted_attribute(element: &str, attribute: &str, value: &str) -> bool {
+    match (element, attribute) {
+        ("input", "type") => value == "checkbox",
+        ("code", "class") => value.starts_with("language-"),
+        ("section", "class") => value == "footnotes",
+        ("sup", "class") => value == "footnote-ref",
+        ("a", "class") => value == "footnote-backref",
+        ("a" | "li", "id") => value.starts_with("fn-") || value.starts_with("fnref-"),
+        _ => true,
+    }
+}
+
+/// Checks an attribute value in trusted documents.
+///
+/// Inline styles may not load anything, so they can't be used to track readers.
+fn trusted_attribute(element: &str, attribute: &str, value: &str) -> bool {
+    match (element, attribute) {
+        ("input", "type") => value == "checkbox",
+        (_, "style") => {
+            let value = value.to_ascii_lowercase();
+            !["url(", "expression(", "javascript:", "@import"]
+                .iter()
+                .any(|pattern| value.contains(pattern))
+        }
+        _ => true,
+    }
+}
+
+/// Decides how much to trust a document from its path.
+///
+/// Documents inside one of `trusted_folders` are trusted; untitled
+/// documents and everything else are not.
+pub fn trust_for(path: Option<&Path>, trusted_folders: &[String]) -> TrustLevel {
+    let Some(path) = path else {
+        return TrustLevel::Untrusted;
+    };
+    // Resolve `..` and symlinks so they can't lead out of a trusted folder
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let trusted = trusted_folders.iter().any(|folder| {
+        Path::new(folder)
+            .canonicalize()
+            .is_ok_and(|folder| path.starts_with(folder))
+    });
+    if trusted {
+        TrustLevel::Trusted
+    } else {
+        TrustLevel::Untrusted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::{render, RenderOptions};
+    use tempfile::tempdir;
+
+    /// Known XSS vectors, as raw HTML or Markdown
+    const XSS_CORPUS: &[&str] = &[
+        "<script>alert(1)</script>",
+        "<SCRIPT SRC=//evil.example/xss.js></SCRIPT>",
+        "<img src=x onerror=alert(1)>",
+        "<img src=x onerror=alert(1)//",
+        "<img src=\"x\" ONERROR='alert(1)'>",
+        "<a href=\"javascript:alert(1)\">x</a>",
+        "<a href=\"JaVaScRiPt:alert(1)\">x</a>",
+        "<a href=\" javascript:alert(1)\">x</a>",
+        "<a href=\"&#106;&#97;&#118;&#97;&#115;&#99;&#114;&#105;&#112;&#116;&#58;alert(1)\">x</a>",
+        "<a href=\"java\tscript:alert(1)\">x</a>",
+        "<a href=\"vbscript:msgbox(1)\">x</a>",
+        "<a href=\"data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==\">x</a>",
+        "[x](javascript:alert(1))",
+        "[x](JAVASCRIPT:alert(1) \"title\")",
+        "![x](javascript:alert(1))",
+        "<javascript:alert(1)>",
+        "<svg onload=alert(1)>",
+        "<svg><script>alert(1)</script></svg>",
+        "<svg><a xlink:href=\"javascript:alert(1)\"><text>x</text></a></svg>",
+        "<math><mtext><table><mglyph><style><img src=x onerror=alert(1)>",
+        "<object data=\"javascript:alert(1)\"></object>",
+        "<embed src=\"javascript:alert(1)\">",
+        "<iframe src=\"javascript:alert(1)\"></iframe>",
+        "<iframe srcdoc=\"<script>alert(1)</script>\"></iframe>",
+        "<form action=\"javascript:alert(1)\"><button>x</button></form>",
+        "<input onfocus=alert(1) autofocus>",
+        "<input type=\"image\" src=x onerror=alert(1)>",
+        "<details open ontoggle=alert(1)>",
+        "<body onload=alert(1)>",
+        "<meta http-equiv=\"refresh\" content=\"0;url=javascript:alert(1)\">",
+        "<base href=\"javascript:alert(1)//\">",
+        "<link rel=\"stylesheet\" href=\"javascript:alert(1)\">",
+        "<style>@import 'javascript:alert(1)';</style>",
+        "<div style=\"background:url(javascript:alert(1))\">x</div>",
+        "<div style=\"width: expression(alert(1))\">x</div>",
+        "<p onmouseover=alert(1)>x</p>",
+        "<a href=x onmouseover=alert(1)>x</a>",
+        "<table background=\"javascript:alert(1)\"><tr><td>x</td></tr></table>",
+        "<video><source onerror=alert(1)></video>",
+        "<audio src=x onerror=alert(1)>",
+        "<!--<img src=\"--><img src=x onerror=alert(1)//\">",
+        "<noscript><p title=\"</noscript><img src=x onerror=alert(1)>\">",
+        "<template><script>alert(1)</script></template>",
+    ];
+
+    /// Elements that must not survive sanitization
+    const FORBIDDEN_TAGS: &[&str] = &[
+        "script", "svg", "math", "object", "embed", "iframe", "form", "meta", "base", "link",
+        "style", "template",
+    ];
+
+    /// Attribute content that must not survive sanitization
+    const FORBIDDEN_ATTRIBUTES: &[&str] =
+        &["javascript:", "vbscript:", "data:text/html", "expression("];
+
+    /// Checks the tags in `output`; text is escaped, so it can't contain any.
+    fn assert_safe(input: &str, output: &str) {
+        for tag in output.split('<').skip(1) {
+            let tag = tag.split('>').next().unwrap().to_ascii_lowercase();
+            let name = tag
+                .trim_start_matches('/')
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap();
+            let has_handler = tag
+                .split_whitespace()
+                .skip(1)
+                .any(|attribute| attribute.starts_with("on"));
+            let unsafe_attribute = FORBIDDEN_ATTRIBUTES
+                .iter()
+                .any(|pattern| tag.contains(pattern));
+            assert!(
+                !FORBIDDEN_TAGS.contains(&name) && !has_handler && !unsafe_attribute,
+                "<{}> survived in {:?} from {:?}",
+                tag,
+                output,
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_xss_corpus() {
+        for trust in [TrustLevel::Untrusted, TrustLevel::Trusted] {
+            for vector in XSS_CORPUS {
+                assert_safe(vector, &sanitize(vector, trust));
+                let rendered = render(vector, &RenderOptions::default(), trust).html;
+                assert_safe(vector, &rendered);
+            }
+        }
+    }
+
+    #[test]
+    fn test_keeps_rendered_markdown() {
+        let content = "# Title\n\n- [x] done\n\n```rust\nfn main() {}\n```\n\nNote[^1]\n\n[^1]: Footnote\n\n<kbd>Ctrl</kbd> [link](https://example.com)\n";
+        let options = RenderOptions {
+            source_positions: true,
+            ..RenderOptions::default()
+        };
+        let html = render(content, &options, TrustLevel::Untrusted).html;
+        assert!(html.contains("<input type=\"checkbox\" checked=\"\" disabled=\"\""));
+        assert!(html.contains("class=\"language-rust\""));
+        assert!(html.contains("class=\"footnotes\""));
+        assert!(html.contains("id=\"fn-1\""));
+        assert!(html.contains("<kbd>Ctrl</kbd>"));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("data-sourcepos=\"1:1-1:7\""));
+    }
+
+    #[test]
+    fn test_trust_levels() {
+        let html = "<div class=\"warning\" id=\"top\" style=\"color: red\">x</div><video src=\"a.mp4\" controls></video><a href=\"file:///notes/a.md\">a</a>";
+
+        let untrusted = sanitize(html, TrustLevel::Untrusted);
+        assert_eq!(
+            untrusted,
+            "<div>x</div><a rel=\"noopener noreferrer\">a</a>"
+        );
+
+        let trusted = sanitize(html, TrustLevel::Trusted);
+        assert!(trusted.contains("class=\"warning\""));
+        assert!(trusted.contains("style=\"color: red\""));
+        assert!(trusted.contains("<video src=\"a.mp4\" controls=\"\">"));
+        assert!(trusted.contains("href=\"file:///notes/a.md\""));
+    }
+
+    #[test]
+    fn test_untrusted_rejects_checkbox_lookalikes() {
+        assert_eq!(
+            sanitize("<input type=\"password\">", TrustLevel::Untrusted),
+            "<input>"
+        );
+        assert_eq!(
+            sanitize("<code class=\"app-header\">x</code>", TrustLevel::Untrusted),
+            "<code>x</code>"
+        );
+    }
+
+    #[test]
+    fn test_trust_for() {
+        let dir = tempdir().unwrap();
+        let trusted = dir.path().join("trusted");
+        let other = dir.path().join("other");
+        std::fs::create_dir_all(&trusted).unwrap();
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(trusted.join("a.md"), "").unwrap();
+        std::fs::write(other.join("b.md"), "").unwrap();
+        let folders = vec![trusted.to_string_lossy().to_string()];
+
+        assert_eq!(
+            trust_for(Some(&trusted.join("a.md")), &folders),
+            TrustLevel::Trusted
+        );
+        assert_eq!(
+            trust_for(
+                Some(&trusted.join("..").join("other").join("b.md")),
+                &folders
+            ),
+            TrustLevel::Untrusted
+        );
+        assert_eq!(trust_for(None, &folders), TrustLevel::Untrusted);
+    }
+}
@@ -0,0 +1,581 @@
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{
+    BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind, SinkMatch,
+};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+/// Longest line returned in a result, in characters; longer lines are cut
+/// around the first match
+const MAX_LINE_CHARS: usize = 500;
+
+/// How to match the query and which files to search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression instead of literal text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Globs of files to search, relative to the root; all Markdown files
+    /// if empty
+    pub include: Vec<String>,
+    /// Globs of files and folders to skip, relative to the root
+    pub exclude: Vec<String>,
+    /// Skip files ignored by `.gitignore`, `.ignore` and hidden files
+    pub respect_ignore: bool,
+    /// Lines of context before and after each match
+    pub context_lines: usize,
+    /// Stop after this many matching lines
+    pub max_results: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: false,
+            whole_word: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            respect_ignore: true,
+            context_lines: 1,
+            max_results: 10_000,
+        }
+    }
+}
+
+/// Line that matched the query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    /// 1-based line number
+    pub line: u64,
+    /// 1-based column of the first match, in characters
+    pub column: usize,
+    pub text: String,
+    /// Matches in `text` as 0-based `[start, end)` character offsets
+    pub ranges: Vec<(usize, usize)>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Payload of the `search-result` event: the matches in one file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMatches {
+    pub search_id: u64,
+    pub path: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Payload of the `search-finished` event
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SearchSummary {
+    pub search_id: u64,
+    /// Files with at least one match
+    pub files: usize,
+    /// Matching lines
+    pub matches: usize,
+    /// Whether there were more than `max_results` matches
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// Builds the matcher for a query, reporting invalid patterns.
+pub fn build_matcher(query: &str, options: &SearchOptions) -> Result<RegexMatcher, String> {
+    if query.is_empty() {
+        return Err("Search query must not be empty".to_string());
+    }
+    RegexMatcherBuilder::new()
+        // `^` and `$` match at every line, also when a whole file is matched at once
+        .multi_line(true)
+        .case_insensitive(!options.case_sensitive)
+        .word(options.whole_word)
+        .fixed_strings(!options.regex)
+        .build(query)
+        .map_err(|e| e.to_string())
+}
+
+/// Builds the matcher and include/exclude globs of a search under `root`.
+fn prepare(
+    root: &Path,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<(RegexMatcher, Override), String> {
+    let matcher = build_matcher(query, options)?;
+    if !root.is_dir() {
+        return Err(format!("Not a folder: {}", root.display()));
+    }
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides.add(glob).map_err(|e| e.to_string())?;
+    }
+    for glob in &options.exclude {
+        overrides
+            .add(&format!("!{}", glob))
+            .map_err(|e| e.to_string())?;
+    }
+    let overrides = overrides.build().map_err(|e| e.to_string())?;
+    Ok((matcher, overrides))
+}
+
+/// Searches the files under `root`, calling `on_file` for each file with matches.
+///
+/// Files are searched in parallel, so `on_file` is called from several
+/// threads and results arrive in no particular order. The search stops
+/// early when `cancelled` is set.
+pub fn search(
+    root: &Path,
+    query: &str,
+    options: &SearchOptions,
+    extensions: &[String],
+    cancelled: &AtomicBool,
+    on_file: impl Fn(String, Vec<SearchMatch>) + Sync,
+) -> Result<SearchSummary, String> {
+    let (matcher, overrides) = prepare(root, query, options)?;
+    let markdown_only = options.include.is_empty();
+
+    let walker = WalkBuilder::new(root)
+        .standard_filters(options.respect_ignore)
+        // Also outside of git repositories
+        .require_git(false)
+        .overrides(overrides)
+        .build_parallel();
+
+    let files = AtomicUsize::new(0);
+    let matches = AtomicUsize::new(0);
+    let truncated = AtomicBool::new(false);
+    walker.run(|| {
+        let matcher = matcher.clone();
+        let mut searcher = SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .line_number(true)
+            .before_context(options.context_lines)
+            .after_context(options.context_lines)
+            .build();
+        let (on_file, files, matches, truncated) = (&on_file, &files, &matches, &truncated);
+        Box::new(move |entry| {
+            if cancelled.load(Ordering::Relaxed) || truncated.load(Ordering::Relaxed) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            let path = entry.path();
+            let is_file = entry.file_type().is_some_and(|t| t.is_file());
+            if !is_file
+                || (markdown_only && !crate::is_markdown_file(&path.to_string_lossy(), extensions))
+            {
+                return WalkState::Continue;
+            }
+
+            let mut sink = MatchSink {
+                matcher: &matcher,
+                matches: Vec::new(),
+                pending_before: Vec::new(),
+                cancelled,
+            };
+            if searcher.search_path(&matcher, path, &mut sink).is_err() {
+                return WalkState::Continue;
+            }
+            if sink.matches.is_empty() {
+                return WalkState::Continue;
+            }
+
+            let mut found = sink.matches;
+            let total = matches.fetch_add(found.len(), Ordering::Relaxed) + found.len();
+            if total > options.max_results {
+                found.truncate(found.len().saturating_sub(total - options.max_results));
+                truncated.store(true, Ordering::Relaxed);
+            }
+            if !found.is_empty() {
+                files.fetch_add(1, Ordering::Relaxed);
+                on_file(path.to_string_lossy().to_string(), found);
+            }
+            WalkState::Continue
+        })
+    });
+
+    Ok(SearchSummary {
+        search_id: 0,
+        files: files.into_inner(),
+        matches: matches.into_inner().min(options.max_results),
+        truncated: truncated.into_inner(),
+        cancelled: cancelled.load(Ordering::Relaxed),
+    })
+}
+
+/// Collects the matching lines of one file with their context.
+struct MatchSink<'a> {
+    matcher: &'a RegexMatcher,
+    matches: Vec<SearchMatch>,
+    /// Context lines seen since the last match
+    pending_before: Vec<String>,
+    cancelled: &'a AtomicBool,
+}
+
+impl Sink for MatchSink<'_> {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        let bytes = trim_line_ending(mat.bytes());
+        let mut byte_ranges = Vec::new();
+        self.matcher
+            .find_iter(bytes, |m| {
+                byte_ranges.push((m.start(), m.end()));
+                true
+            })
+            .map_err(std::io::Error::other)?;
+
+        let (text, ranges) = line_with_ranges(bytes, &byte_ranges);
+        self.matches.push(SearchMatch {
+            line: mat.line_number().unwrap_or_default(),
+            column: ranges.first().map_or(1, |range| range.0 + 1),
+            text,
+            ranges,
+            before: std::mem::take(&mut self.pending_before),
+            after: Vec::new(),
+        });
+        Ok(!self.cancelled.load(Ordering::Relaxed))
+    }
+
+    fn context(
+        &mut self,
+        _searcher: &Searcher,
+        ctx: &SinkContext<'_>,
+    ) -> Result<bool, Self::Error> {
+        let line = truncate_chars(&String::from_utf8_lossy(trim_line_ending(ctx.bytes())));
+        match (ctx.kind(), self.matches.last_mut()) {
+            (SinkContextKind::After, Some(last)) => last.after.push(line),
+            _ => self.pending_before.push(line),
+        }
+        Ok(true)
+    }
+
+    fn context_break(&mut self, _searcher: &Searcher) -> Result<bool, Self::Error> {
+        self.pending_before.clear();
+        Ok(true)
+    }
+}
+
+fn trim_line_ending(bytes: &[u8]) -> &[u8] {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes.strip_suffix(b"\r").unwrap_or(bytes)
+}
+
+/// Cuts a context line to [`MAX_LINE_CHARS`].
+fn truncate_chars(text: &str) -> String {
+    text.chars().take(MAX_LINE_CHARS).collect()
+}
+
+/// Decodes a matching line and converts byte ranges to character offsets.
+///
+/// Long lines are cut to [`MAX_LINE_CHARS`] starting a little before the
+/// first match, dropping ranges outside the kept part.
+fn line_with_ranges(bytes: &[u8], byte_ranges: &[(usize, usize)]) -> (String, Vec<(usize, usize)>) {
+    let text = String::from_utf8_lossy(bytes);
+    // Offsets of each character, plus the end of the line
+    let offsets: Vec<usize> = text
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let char_at = |byte: usize| offsets.partition_point(|&offset| offset < byte);
+    let ranges: Vec<(usize, usize)> = byte_ranges
+        .iter()
+        .map(|&(start, end)| (char_at(start), char_at(end)))
+        .collect();
+
+    let chars = offsets.len() - 1;
+    if chars <= MAX_LINE_CHARS {
+        return (text.into_owned(), ranges);
+    }
+    let first = ranges.first().map_or(0, |range| range.0);
+    let skip = first
+        .saturating_sub(MAX_LINE_CHARS / 5)
+        .min(chars - MAX_LINE_CHARS);
+    let text = text.chars().skip(skip).take(MAX_LINE_CHARS).collect();
+    let ranges = ranges
+        .into_iter()
+        .filter(|&(start, end)| start >= skip && end <= skip + MAX_LINE_CHARS)
+        .map(|(start, end)| (start - skip, end - skip))
+        .collect();
+    (text, ranges)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Running searches, by window, so a new search replaces the previous one
+#[derive(Default)]
+pub struct SearchState {
+    next_id: AtomicU64,
+    running: Mutex<HashMap<String, (u64, Arc<AtomicBool>)>>,
+}
+
+/// Cancels the running search of a window, if any.
+fn cancel_window_search(app: &AppHandle, window: &str) {
+    if let Some((_, cancelled)) = app
+        .state::<SearchState>()
+        .running
+        .lock()
+        .unwrap()
+        .remove(window)
+    {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Starts searching the files under `root` and returns the search id.
+///
+/// Files with matches are sent to the window as `search-result` events,
+/// followed by a `search-finished` event. A running search of the same
+/// window is cancelled first.
+#[tauri::command]
+pub fn search_workspace(
+    app: AppHandle,
+    window: WebviewWindow,
+    root: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<u64, String> {
+    let options = options.unwrap_or_default();
+    // Report invalid patterns, globs and folders right away rather than as an event
+    prepare(Path::new(&root), &query, &options)?;
+
+    let label = window.label().to_string();
+    cancel_window_search(&app, &label);
+    let state = app.state::<SearchState>();
+    let search_id = state.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state
+        .running
+        .lock()
+        .unwrap()
+        .insert(label.clone(), (search_id, cancelled.clone()));
+
+    let extensions = crate::settings::current(&app).markdown_extensions;
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = search(
+            Path::new(&root),
+            &query,
+            &options,
+            &extensions,
+            &cancelled,
+            |path, matches| {
+                let _ = app.emit_to(
+                    &label,
+                    "search-result",
+                    FileMatches {
+                        search_id,
+                        path,
+                        matches,
+                    },
+                );
+            },
+        );
+        let summary = match result {
+            Ok(summary) => SearchSummary {
+                search_id,
+                ..summary
+            },
+            Err(e) => {
+                eprintln!("[search] {}", e);
+                SearchSummary {
+                    search_id,
+                    cancelled: true,
+                    ..SearchSummary::default()
+                }
+            }
+        };
+
+        let state = app.state::<SearchState>();
+        let mut running = state.running.lock().unwrap();
+        if running.get(&label).is_some_and(|(id, _)| *id == search_id) {
+            running.remove(&label);
+        }
+        drop(running);
+        let _ = app.emit_to(&label, "search-finished", summary);
+    });
+    Ok(search_id)
+}
+
+/// Cancels the running search of the calling window.
+#[tauri::command]
+pub fn cancel_search(app: AppHandle, window: WebviewWindow) {
+    cancel_window_search(&app, window.label());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn extensions() -> Vec<String> {
+        crate::settings::Settings::default().markdown_extensions
+    }
+
+    /// Runs a search and returns the matches sorted by file name.
+    fn run(
+        root: &Path,
+        query: &str,
+        options: &SearchOptions,
+    ) -> (SearchSummary, Vec<(String, Vec<SearchMatch>)>) {
+        let results = Mutex::new(Vec::new());
+        let summary = search(
+            root,
+            query,
+            options,
+            &extensions(),
+            &AtomicBool::new(false),
+            |path, matches| {
+                let name = Path::new(&path)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                results.lock().unwrap().push((name, matches));
+            },
+        )
+        .unwrap();
+        let mut results = results.into_inner().unwrap();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        (summary, results)
+    }
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+        fs::create_dir_all(dir.path().join("build")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        fs::write(
+            dir.path().join("a.md"),
+            "# Notes\n\nThe todo list\nTODO: ship it\ntodos are done\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("docs/b.md"), "Nothing here\n").unwrap();
+        fs::write(dir.path().join("docs/c.markdown"), "a todo\n").unwrap();
+        fs::write(dir.path().join("build/out.md"), "todo\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "todo\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_search_literal_with_context() {
+        let dir = workspace();
+        let (summary, results) = run(dir.path(), "todo", &SearchOptions::default());
+
+        let files: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(files, vec!["a.md", "docs/c.markdown"]);
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.matches, 4);
+
+        let first = &results[0].1[0];
+        assert_eq!(first.line, 3);
+        assert_eq!(first.column, 5);
+        assert_eq!(first.text, "The todo list");
+        assert_eq!(first.ranges, vec![(4, 8)]);
+        assert_eq!(first.before, vec![""]);
+        assert_eq!(results[0].1[2].after, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_search_options() {
+        let dir = workspace();
+        let options = SearchOptions {
+            case_sensitive: true,
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+        let (summary, _) = run(dir.path(), "todo", &options);
+        assert_eq!(summary.matches, 2);
+
+        let options = SearchOptions {
+            regex: true,
+            include: vec!["*.md".to_string(), "*.txt".to_string()],
+            exclude: vec!["docs/**".to_string()],
+            ..SearchOptions::default()
+        };
+        let (_, results) = run(dir.path(), r"^todo\w*", &options);
+        let found: Vec<(&str, u64)> = results
+            .iter()
+            .flat_map(|(name, matches)| matches.iter().map(move |m| (name.as_str(), m.line)))
+            .collect();
+        assert_eq!(found, vec![("a.md", 4), ("a.md", 5), ("notes.txt", 1)]);
+
+        let options = SearchOptions {
+            respect_ignore: false,
+            ..SearchOptions::default()
+        };
+        let (summary, _) = run(dir.path(), "todo", &options);
+        assert_eq!(summary.files, 3);
+    }
+
+    #[test]
+    fn test_search_limits_and_errors() {
+        let dir = workspace();
+        let options = SearchOptions {
+            max_results: 2,
+            ..SearchOptions::default()
+        };
+        let (summary, results) = run(dir.path(), "todo", &options);
+        assert!(summary.truncated);
+        assert_eq!(summary.matches, 2);
+        assert_eq!(results.iter().map(|(_, m)| m.len()).sum::<usize>(), 2);
+
+        let cancelled = AtomicBool::new(true);
+        let summary = search(
+            dir.path(),
+            "todo",
+            &SearchOptions::default(),
+            &extensions(),
+            &cancelled,
+            |_, _| panic!("cancelled search reported a result"),
+        )
+        .unwrap();
+        assert!(summary.cancelled);
+
+        let regex = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert!(build_matcher("(unclosed", &regex).is_err());
+        assert!(build_matcher("(unclosed", &SearchOptions::default()).is_ok());
+        assert!(build_matcher("", &SearchOptions::default()).is_err());
+
+        // Checked before a search starts, not reported as a cancelled search
+        let bad_glob = SearchOptions {
+            include: vec!["{unclosed".to_string()],
+            ..SearchOptions::default()
+        };
+        assert!(prepare(dir.path(), "todo", &bad_glob).is_err());
+        assert!(prepare(
+            &dir.path().join("missing"),
+            "todo",
+            &SearchOptions::default()
+        )
+        .is_err());
+        assert!(prepare(dir.path(), "todo", &SearchOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_line_with_ranges() {
+        let (text, ranges) = line_with_ranges("héllo wörld".as_bytes(), &[(7, 13)]);
+        assert_eq!(text, "héllo wörld");
+        assert_eq!(ranges, vec![(6, 11)]);
+
+        let line = format!("{}match{}", "x".repeat(1000), "y".repeat(1000));
+        let (text, ranges) = line_with_ranges(line.as_bytes(), &[(1000, 1005)]);
+        assert_eq!(text.chars().count(), MAX_LINE_CHARS);
+        assert_eq!(ranges, vec![(100, 105)]);
+    }
+}
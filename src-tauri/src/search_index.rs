@@ -0,0 +1,687 @@
+use crate::{front_matter, markdown, workspace_watcher};
+use ignore::WalkBuilder;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, UNIX_EPOCH};
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::query::{AllQuery, QueryParser};
+use tantivy::schema::{Field, OwnedValue, Schema, Value, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Directory inside the app cache dir that holds one index per workspace
+const INDEX_DIR: &str = "index";
+
+/// Indexing threads and their combined memory budget
+const WRITER_THREADS: usize = 2;
+const WRITER_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Quiet time after a file change before the index is updated, so that a
+/// burst of changes, e.g. a `git checkout`, is committed at once
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Longest snippet returned with a hit, in characters
+const SNIPPET_CHARS: usize = 200;
+
+/// Fields of an indexed document
+struct Fields {
+    path: Field,
+    title: Field,
+    headings: Field,
+    body: Field,
+    tags: Field,
+    /// All front matter fields, searchable as e.g. `meta.author:ada`
+    meta: Field,
+    /// Modification time in nanoseconds, to skip files that haven't changed
+    modified: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        path: builder.add_text_field("path", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        headings: builder.add_text_field("headings", TEXT),
+        body: builder.add_text_field("body", TEXT | STORED),
+        tags: builder.add_text_field("tags", TEXT | STORED),
+        meta: builder.add_json_field("meta", TEXT),
+        modified: builder.add_u64_field("modified", STORED),
+    };
+    (builder.build(), fields)
+}
+
+/// Ranked result of [`WorkspaceIndex::query`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexHit {
+    pub path: String,
+    pub title: String,
+    /// BM25 relevance, higher is better
+    pub score: f32,
+    /// Part of the body around the best matches
+    pub snippet: String,
+    /// Matches in `snippet` as 0-based `[start, end)` character offsets
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// Payload of the `index-updated` event
+#[derive(Debug, Clone, Serialize)]
+struct IndexUpdated {
+    root: String,
+    documents: u64,
+}
+
+/// Full-text index of the Markdown files in a workspace
+pub struct WorkspaceIndex {
+    root: PathBuf,
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+    extensions: Vec<String>,
+    /// Indexed files with their modification time
+    files: Mutex<HashMap<PathBuf, u64>>,
+    /// Set when the workspace is closed, to cut a long [`WorkspaceIndex::sync`] short
+    stopped: AtomicBool,
+}
+
+impl WorkspaceIndex {
+    /// Opens the index stored in `dir`, creating it if needed.
+    ///
+    /// An index written with a different schema is rebuilt from scratch.
+    pub fn open(dir: &Path, root: &Path, extensions: Vec<String>) -> Result<Self, String> {
+        let (schema, fields) = schema();
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let index = match Index::open_in_dir(dir) {
+            Ok(index) if index.schema() == schema => index,
+            Ok(_) | Err(tantivy::TantivyError::SchemaError(_)) => {
+                fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                Index::create_in_dir(dir, schema).map_err(|e| e.to_string())?
+            }
+            Err(tantivy::TantivyError::OpenDirectoryError(_))
+            | Err(tantivy::TantivyError::OpenReadError(_)) => {
+                Index::create_in_dir(dir, schema).map_err(|e| e.to_string())?
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e| e.to_string())?;
+        let writer = index
+            .writer_with_num_threads(WRITER_THREADS, WRITER_MEMORY)
+            .map_err(|e| e.to_string())?;
+
+        let workspace = Self {
+            root: root.to_path_buf(),
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+            extensions,
+            files: Mutex::new(HashMap::new()),
+            stopped: AtomicBool::new(false),
+        };
+        *workspace.files.lock().unwrap() = workspace.stored_files()?;
+        Ok(workspace)
+    }
+
+    /// Reads the paths and modification times of all indexed documents.
+    fn stored_files(&self) -> Result<HashMap<PathBuf, u64>, String> {
+        let searcher = self.reader.searcher();
+        let addresses = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .map_err(|e| e.to_string())?;
+        let mut files = HashMap::new();
+        for address in addresses {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            let path = doc.get_first(self.fields.path).and_then(|v| v.as_str());
+            let modified = doc.get_first(self.fields.modified).and_then(|v| v.as_u64());
+            if let (Some(path), Some(modified)) = (path, modified) {
+                files.insert(PathBuf::from(path), modified);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    /// Brings the index up to date with the workspace on disk.
+    ///
+    /// Only files that were added, changed or removed since they were
+    /// indexed are processed. Returns whether anything changed.
+    pub fn sync(&self) -> Result<bool, String> {
+        let on_disk = markdown_files(&self.root, &self.extensions);
+        let mut writer = self.writer.lock().unwrap();
+        let mut files = self.files.lock().unwrap();
+
+        let removed: Vec<PathBuf> = files
+            .keys()
+            .filter(|path| !on_disk.contains_key(*path))
+            .cloned()
+            .collect();
+        let mut changed = !removed.is_empty();
+        for path in removed {
+            self.delete(&writer, &mut files, &path);
+        }
+        for (path, modified) in on_disk {
+            if self.stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            if files.get(&path) != Some(&modified) {
+                changed |= self.add(&writer, &mut files, &path).is_ok();
+            }
+        }
+        if changed {
+            self.commit(&mut writer)?;
+        }
+        Ok(changed)
+    }
+
+    /// Updates the index for paths reported by the file watcher.
+    ///
+    /// Paths may be files or folders that were created, changed, moved or
+    /// removed. Files named directly are always indexed again, since a
+    /// change doesn't necessarily move the modification time on. Returns
+    /// whether anything changed.
+    pub fn update_paths(&self, paths: &[PathBuf]) -> Result<bool, String> {
+        let mut writer = self.writer.lock().unwrap();
+        let mut files = self.files.lock().unwrap();
+        let mut changed = false;
+
+        for path in paths {
+            // Removed or moved away, including everything in a folder
+            let gone: Vec<PathBuf> = files
+                .keys()
+                .filter(|indexed| indexed.starts_with(path) && !indexed.exists())
+                .cloned()
+                .collect();
+            for indexed in gone {
+                self.delete(&writer, &mut files, &indexed);
+                changed = true;
+            }
+
+            if !is_visible(&self.root, path) {
+                continue;
+            }
+            if path.is_dir() {
+                for (path, modified) in markdown_files(path, &self.extensions) {
+                    if files.get(&path) != Some(&modified) {
+                        changed |= self.add(&writer, &mut files, &path).is_ok();
+                    }
+                }
+            } else if path.is_file()
+                && crate::is_markdown_file(&path.to_string_lossy(), &self.extensions)
+            {
+                changed |= self.add(&writer, &mut files, path).is_ok();
+            }
+        }
+
+        if changed {
+            self.commit(&mut writer)?;
+        }
+        Ok(changed)
+    }
+
+    /// Indexes a file, replacing its previous version.
+    fn add(
+        &self,
+        writer: &IndexWriter,
+        files: &mut HashMap<PathBuf, u64>,
+        path: &Path,
+    ) -> Result<(), String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let modified = modified_time(path).unwrap_or_default();
+        let path_str = path.to_string_lossy();
+        writer.delete_term(Term::from_field_text(self.fields.path, &path_str));
+
+        let parsed = front_matter::parse(&content);
+        let meta = parsed
+            .front_matter
+            .and_then(|front_matter| match front_matter.data {
+                JsonValue::Object(map) => Some(map),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let headings = markdown::headings(&content);
+
+        let mut doc = TantivyDocument::new();
+        doc.add_text(self.fields.path, &path_str);
+        doc.add_text(self.fields.title, document_title(&meta, &headings, path));
+        for heading in &headings {
+            doc.add_text(self.fields.headings, &heading.text);
+        }
+        doc.add_text(self.fields.body, &parsed.body);
+        for tag in tags(&meta) {
+            doc.add_text(self.fields.tags, tag);
+        }
+        doc.add_field_value(self.fields.meta, &OwnedValue::from(meta));
+        doc.add_u64(self.fields.modified, modified);
+
+        writer.add_document(doc).map_err(|e| e.to_string())?;
+        files.insert(path.to_path_buf(), modified);
+        Ok(())
+    }
+
+    fn delete(&self, writer: &IndexWriter, files: &mut HashMap<PathBuf, u64>, path: &Path) {
+        writer.delete_term(Term::from_field_text(
+            self.fields.path,
+            &path.to_string_lossy(),
+        ));
+        files.remove(path);
+    }
+
+    /// Commits pending changes and makes them visible to queries.
+    fn commit(&self, writer: &mut IndexWriter) -> Result<(), String> {
+        writer.commit().map_err(|e| e.to_string())?;
+        self.reader.reload().map_err(|e| e.to_string())
+    }
+
+    /// Finds the documents that best match `query`, best first.
+    ///
+    /// The query searches titles, headings, tags and bodies, with matches in
+    /// titles counting most. It supports the usual syntax: `"exact phrase"`,
+    /// `+required`, `-excluded`, `field:term` and `meta.<field>:term` for
+    /// front matter fields. Invalid syntax is searched as plain words.
+    pub fn query(&self, query: &str, limit: usize) -> Result<Vec<IndexHit>, String> {
+        let fields = &self.fields;
+        let mut parser = QueryParser::for_index(
+            &self.index,
+            vec![fields.title, fields.headings, fields.tags, fields.body],
+        );
+        parser.set_field_boost(fields.title, 3.0);
+        parser.set_field_boost(fields.headings, 2.0);
+        parser.set_field_boost(fields.tags, 2.0);
+        let (query, _errors) = parser.parse_query_lenient(query);
+
+        let searcher = self.reader.searcher();
+        let top = searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(|e| e.to_string())?;
+        let mut snippets =
+            SnippetGenerator::create(&searcher, &*query, fields.body).map_err(|e| e.to_string())?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        top.into_iter()
+            .map(|(score, address)| {
+                let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+                let text = |field| {
+                    doc.get_first(field)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let snippet = snippets.snippet_from_doc(&doc);
+                let fragment = snippet.fragment();
+                let char_at = |byte: usize| fragment[..byte].chars().count();
+                Ok(IndexHit {
+                    path: text(fields.path),
+                    title: text(fields.title),
+                    score,
+                    snippet: fragment.to_string(),
+                    highlights: snippet
+                        .highlighted()
+                        .iter()
+                        .map(|range| (char_at(range.start), char_at(range.end)))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Modification time of a file in nanoseconds since the Unix epoch.
+///
+/// Whole seconds would miss a second save within the same second.
+fn modified_time(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    u64::try_from(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos()).ok()
+}
+
+/// Lists the Markdown files under `dir` that aren't ignored, with their
+/// modification times.
+pub fn markdown_files(dir: &Path, extensions: &[String]) -> HashMap<PathBuf, u64> {
+    WalkBuilder::new(dir)
+        // Also outside of git repositories
+        .require_git(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| crate::is_markdown_file(&entry.path().to_string_lossy(), extensions))
+        .filter_map(|entry| {
+            let modified = modified_time(entry.path())?;
+            Some((entry.into_path(), modified))
+        })
+        .collect()
+}
+
+/// Checks whether a path inside `root` would be found by [`markdown_files`]
+/// when listing `root`.
+///
+/// Each folder on the way is listed, so that ignore rules like `private/`
+/// in a parent folder apply, which checking the path alone would miss.
+pub fn is_visible(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut dir = root.to_path_buf();
+    for component in relative.components() {
+        let child = dir.join(component);
+        let listed = WalkBuilder::new(&dir)
+            .require_git(false)
+            .max_depth(Some(1))
+            .build()
+            .filter_map(Result::ok)
+            .any(|entry| entry.path() == child);
+        if !listed {
+            return false;
+        }
+        dir = child;
+    }
+    true
+}
+
+/// Title of a document: the front matter title, its first top-level
+/// heading, or the file name.
+fn document_title(
+    meta: &serde_json::Map<String, JsonValue>,
+    headings: &[markdown::Heading],
+    path: &Path,
+) -> String {
+    meta.get("title")
+        .and_then(JsonValue::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            headings
+                .iter()
+                .find(|h| h.level == 1)
+                .map(|h| h.text.clone())
+        })
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// Tags from the front matter, as a list or a comma-separated string.
+fn tags(meta: &serde_json::Map<String, JsonValue>) -> Vec<&str> {
+    match meta.get("tags") {
+        Some(JsonValue::Array(tags)) => tags.iter().filter_map(JsonValue::as_str).collect(),
+        Some(JsonValue::String(tags)) => tags
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Name of the index among the subscribers of the workspace watcher
+const WATCH_SUBSCRIBER: &str = "index";
+
+/// Index that is kept up to date while its workspace is open
+struct OpenIndex {
+    index: Arc<WorkspaceIndex>,
+    updates: JoinHandle<()>,
+}
+
+impl OpenIndex {
+    /// Stops updating the index and waits for the update thread to finish,
+    /// so that its writer is released before the index is opened again.
+    fn stop(self, app: &AppHandle) {
+        self.index.stopped.store(true, Ordering::Relaxed);
+        // Ends the update thread's stream of changes
+        workspace_watcher::unsubscribe(app, &self.index.root, WATCH_SUBSCRIBER);
+        let _ = self.updates.join();
+    }
+}
+
+/// Index of the open workspace, if indexing is enabled
+#[derive(Default)]
+pub struct IndexState {
+    current: Mutex<Option<OpenIndex>>,
+}
+
+/// Directory for the index of `root` in the app cache dir
+fn index_dir(app: &AppHandle, root: &Path) -> Result<PathBuf, String> {
+    let key = crate::fs::content_hash(&root.to_string_lossy());
+    Ok(app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| e.to_string())?
+        .join(INDEX_DIR)
+        .join(&key[..16]))
+}
+
+/// Emits `index-updated` with the number of indexed documents.
+fn emit_updated(app: &AppHandle, index: &WorkspaceIndex) {
+    let _ = app.emit(
+        "index-updated",
+        IndexUpdated {
+            root: index.root.to_string_lossy().to_string(),
+            documents: index.len(),
+        },
+    );
+}
+
+/// Brings the index up to date, then applies changes reported by the
+/// watcher until the index is closed.
+fn run_updates(app: AppHandle, index: Arc<WorkspaceIndex>, changes: mpsc::Receiver<PathBuf>) {
+    match index.sync() {
+        Ok(_) => emit_updated(&app, &index),
+        Err(e) => eprintln!("[index] Failed to update {}: {}", index.root.display(), e),
+    }
+
+    while let Some(paths) = workspace_watcher::next_batch(&changes, DEBOUNCE) {
+        if index.stopped.load(Ordering::Relaxed) {
+            break;
+        }
+        match index.update_paths(&paths) {
+            Ok(true) => emit_updated(&app, &index),
+            Ok(false) => {}
+            Err(e) => eprintln!("[index] Failed to update {}: {}", index.root.display(), e),
+        }
+    }
+}
+
+/// Starts indexing a workspace and keeping the index up to date.
+///
+/// The index is stored in the app cache dir, so reopening a workspace only
+/// processes the files that changed in between. Emits `index-updated` when
+/// the index has caught up and after each update.
+#[tauri::command(async)]
+pub fn open_index(app: AppHandle, root: String) -> Result<(), String> {
+    let settings = crate::settings::current(&app);
+    if !settings.full_text_index {
+        return Err("Full-text indexing is turned off in the settings".to_string());
+    }
+    let root = PathBuf::from(root)
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+
+    let state = app.state::<IndexState>();
+    let mut current = state.current.lock().unwrap();
+    if current.as_ref().is_some_and(|open| open.index.root == root) {
+        return Ok(());
+    }
+    if let Some(previous) = current.take() {
+        previous.stop(&app);
+    }
+
+    let dir = index_dir(&app, &root)?;
+    let index = Arc::new(WorkspaceIndex::open(
+        &dir,
+        &root,
+        settings.markdown_extensions,
+    )?);
+    let changes = workspace_watcher::subscribe(&app, &root, WATCH_SUBSCRIBER)?;
+
+    let updates = {
+        let (app, index) = (app.clone(), index.clone());
+        std::thread::spawn(move || run_updates(app, index, changes))
+    };
+    *current = Some(OpenIndex { index, updates });
+    Ok(())
+}
+
+/// Stops keeping the workspace index up to date.
+#[tauri::command]
+pub fn close_index(app: AppHandle) {
+    let open = app.state::<IndexState>().current.lock().unwrap().take();
+    if let Some(open) = open {
+        open.stop(&app);
+    }
+}
+
+/// Searches the open workspace index, best matches first.
+#[tauri::command]
+pub fn query_index(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<IndexHit>, String> {
+    let index = app
+        .state::<IndexState>()
+        .current
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|open| open.index.clone())
+        .ok_or("No workspace index is open")?;
+    index.query(&query, limit.unwrap_or(50))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn extensions() -> Vec<String> {
+        crate::settings::Settings::default().markdown_extensions
+    }
+
+    fn paths(hits: &[IndexHit]) -> Vec<String> {
+        hits.iter()
+            .map(|hit| {
+                Path::new(&hit.path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_index_ranks_and_highlights() {
+        let workspace = tempdir().unwrap();
+        let cache = tempdir().unwrap();
+        let root = workspace.path();
+        fs::write(
+            root.join("rust.md"),
+            "---\ntitle: Rust notes\ntags: [language, systems]\nauthor: ada\n---\n# Ownership\n\nBorrowing rules in Rust.\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("cooking.md"),
+            "# Pasta\n\nBoil water. Someone mentioned rust on the old pan.\n",
+        )
+        .unwrap();
+        fs::write(root.join("ignored.txt"), "rust rust rust\n").unwrap();
+
+        let index = WorkspaceIndex::open(cache.path(), root, extensions()).unwrap();
+        assert!(index.sync().unwrap());
+        assert_eq!(index.len(), 2);
+
+        let hits = index.query("rust", 10).unwrap();
+        assert_eq!(paths(&hits), vec!["rust.md", "cooking.md"]);
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(hits[0].title, "Rust notes");
+
+        let snippet = &hits[1];
+        let (start, end) = snippet.highlights[0];
+        let highlighted: String = snippet
+            .snippet
+            .chars()
+            .skip(start)
+            .take(end - start)
+            .collect();
+        assert_eq!(highlighted, "rust");
+
+        assert_eq!(paths(&index.query("systems", 10).unwrap()), vec!["rust.md"]);
+        assert_eq!(
+            paths(&index.query("meta.author:ada", 10).unwrap()),
+            vec!["rust.md"]
+        );
+        assert_eq!(
+            paths(&index.query("ownership", 10).unwrap()),
+            vec!["rust.md"]
+        );
+    }
+
+    #[test]
+    fn test_index_updates_incrementally() {
+        let workspace = tempdir().unwrap();
+        let cache = tempdir().unwrap();
+        let root = workspace.path();
+        fs::create_dir_all(root.join("drafts")).unwrap();
+        fs::write(root.join("a.md"), "alpha\n").unwrap();
+        fs::write(root.join("drafts/b.md"), "beta\n").unwrap();
+
+        {
+            let index = WorkspaceIndex::open(cache.path(), root, extensions()).unwrap();
+            index.sync().unwrap();
+            assert_eq!(index.len(), 2);
+        }
+
+        // Reopened from the cache, nothing needs to be indexed again
+        let index = WorkspaceIndex::open(cache.path(), root, extensions()).unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(!index.sync().unwrap());
+
+        fs::write(root.join("c.md"), "gamma\n").unwrap();
+        fs::remove_dir_all(root.join("drafts")).unwrap();
+        assert!(index
+            .update_paths(&[root.join("c.md"), root.join("drafts")])
+            .unwrap());
+        assert_eq!(paths(&index.query("gamma", 10).unwrap()), vec!["c.md"]);
+        assert!(index.query("beta", 10).unwrap().is_empty());
+        assert_eq!(index.len(), 2);
+
+        // A rewrite that keeps the modification time is still picked up
+        let a = root.join("a.md");
+        let modified = fs::metadata(&a).unwrap().modified().unwrap();
+        fs::write(&a, "epsilon\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&a)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(index.update_paths(std::slice::from_ref(&a)).unwrap());
+        assert_eq!(paths(&index.query("epsilon", 10).unwrap()), vec!["a.md"]);
+
+        fs::write(root.join(".gitignore"), "private/\n").unwrap();
+        fs::create_dir_all(root.join("private")).unwrap();
+        fs::write(root.join("private/secret.md"), "delta\n").unwrap();
+        assert!(!index
+            .update_paths(&[root.join("private/secret.md"), root.join("private")])
+            .unwrap());
+    }
+}
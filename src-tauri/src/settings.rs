@@ -0,0 +1,395 @@
+use crate::outline::TocOptions;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Color theme of the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+/// Layout used for newly opened documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    Edit,
+    Preview,
+    Split,
+}
+
+/// User preferences, stored as `settings.json` in the app config directory.
+///
+/// Missing fields take their default value, so older files keep working
+/// when new settings are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    pub font_family: String,
+    pub font_size: u32,
+    pub tab_size: u32,
+    pub word_wrap: bool,
+    /// Seconds between automatic saves, `0` disables autosave
+    pub autosave_interval: u64,
+    pub default_view_mode: ViewMode,
+    pub max_recent_files: usize,
+    /// File extensions treated as Markdown, without the leading dot
+    pub markdown_extensions: Vec<String>,
+    /// Folders whose documents may use inline styles, classes and media in
+    /// their HTML; everything else is rendered with the untrusted policy
+    pub trusted_folders: Vec<String>,
+    /// Levels and list style of generated tables of contents
+    pub toc: TocOptions,
+    /// Regenerate the table of contents between `<!-- toc -->` markers when
+    /// a Markdown file is saved
+    pub update_toc_on_save: bool,
+    /// Keep a full-text index of the open workspace in the cache dir
+    pub full_text_index: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::System,
+            font_family: "system-ui".to_string(),
+            font_size: 14,
+            tab_size: 4,
+            word_wrap: true,
+            autosave_interval: 0,
+            default_view_mode: ViewMode::Split,
+            max_recent_files: crate::MAX_RECENT_FILES,
+            markdown_extensions: crate::MARKDOWN_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            trusted_folders: Vec::new(),
+            toc: TocOptions::default(),
+            update_toc_on_save: false,
+            full_text_index: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Checks that all values are within their allowed ranges.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.font_family.trim().is_empty() {
+            return Err("Font family must not be empty".to_string());
+        }
+        if !(6..=72).contains(&self.font_size) {
+            return Err(format!(
+                "Font size must be between 6 and 72, got {}",
+                self.font_size
+            ));
+        }
+        if !(1..=16).contains(&self.tab_size) {
+            return Err(format!(
+                "Tab size must be between 1 and 16, got {}",
+                self.tab_size
+            ));
+        }
+        if self.autosave_interval > 3600 {
+            return Err(format!(
+                "Autosave interval must be at most 3600 seconds, got {}",
+                self.autosave_interval
+            ));
+        }
+        if !(1..=100).contains(&self.max_recent_files) {
+            return Err(format!(
+                "Recent files limit must be between 1 and 100, got {}",
+                self.max_recent_files
+            ));
+        }
+        if self.markdown_extensions.is_empty() {
+            return Err("At least one Markdown extension is required".to_string());
+        }
+        for ext in &self.markdown_extensions {
+            let valid = !ext.is_empty()
+                && ext
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(format!("Invalid file extension: \"{}\"", ext));
+            }
+        }
+        self.toc.validate()?;
+        for folder in &self.trusted_folders {
+            if !Path::new(folder).is_absolute() {
+                return Err(format!(
+                    "Trusted folder must be an absolute path: {}",
+                    folder
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Cleans up user input: trims and lowercases extensions, strips leading
+    /// dots and drops duplicates.
+    fn normalize(mut self) -> Self {
+        let mut extensions: Vec<String> = Vec::new();
+        for ext in &self.markdown_extensions {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            if !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+        self.markdown_extensions = extensions;
+        self
+    }
+
+    /// Parses, normalizes and validates settings from JSON.
+    fn from_json(json: &str) -> Result<Self, String> {
+        let settings: Settings = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let settings = settings.normalize();
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Applies a partial update: top-level fields in `patch` replace the current values.
+    fn merged(&self, patch: serde_json::Value) -> Result<Self, String> {
+        let serde_json::Value::Object(patch) = patch else {
+            return Err("Settings update must be an object".to_string());
+        };
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        if let serde_json::Value::Object(fields) = &mut value {
+            for (key, field) in patch {
+                if !fields.contains_key(&key) {
+                    return Err(format!("Unknown setting: {}", key));
+                }
+                fields.insert(key, field);
+            }
+        }
+        Self::from_json(&value.to_string())
+    }
+}
+
+/// Settings shared by the backend, plus the watcher for external edits
+#[derive(Default)]
+pub struct SettingsState {
+    pub settings: Mutex<Settings>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+/// Path of the settings file in the app config directory
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_config_dir()
+        .map_err(|e| e.to_string())?
+        .join(SETTINGS_FILE))
+}
+
+/// Reads settings from disk.
+///
+/// A missing file yields the defaults; an invalid file is an error.
+fn read_settings(path: &Path) -> Result<Settings, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Settings::from_json(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Writes settings to disk as pretty-printed JSON.
+fn write_settings(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Returns a copy of the current settings.
+pub fn current(app: &AppHandle) -> Settings {
+    app.state::<SettingsState>()
+        .settings
+        .lock()
+        .unwrap()
+        .clone()
+}
+
+/// Loads settings into the managed state and starts watching the file.
+///
+/// An invalid settings file is reported and replaced by the defaults in
+/// memory; the file itself is left untouched so the user can fix it.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let settings = read_settings(&path).unwrap_or_else(|e| {
+        eprintln!("[settings] Invalid {}: {}", SETTINGS_FILE, e);
+        Settings::default()
+    });
+    *app.state::<SettingsState>().settings.lock().unwrap() = settings;
+    watch_settings(app, &path)
+}
+
+/// Watches the config directory and reloads settings and key bindings
+/// edited outside the app.
+///
+/// The directory is watched rather than the files so that editors which
+/// save by replacing a file, and files created after startup, are seen.
+fn watch_settings(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let dir = path.parent().ok_or("Settings path has no parent")?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let handle = app.clone();
+    let settings_file = path.to_path_buf();
+    let keybindings_file = dir.join(crate::keybindings::KEYBINDINGS_FILE);
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if event.paths.iter().any(|p| p == &settings_file) {
+            reload_settings(&handle, &settings_file);
+        }
+        if event.paths.iter().any(|p| p == &keybindings_file) {
+            crate::keybindings::reload_if_changed(&handle);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    *app.state::<SettingsState>().watcher.lock().unwrap() = Some(watcher);
+    Ok(())
+}
+
+/// Re-reads the settings file and emits `settings-changed` if it differs.
+///
+/// Writes made by [`update_settings`] already match the in-memory state,
+/// so they don't produce a second event.
+fn reload_settings(app: &AppHandle, path: &Path) {
+    let settings = match read_settings(path) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("[settings] Ignoring invalid {}: {}", SETTINGS_FILE, e);
+            return;
+        }
+    };
+
+    {
+        let state = app.state::<SettingsState>();
+        let mut current = state.settings.lock().unwrap();
+        if *current == settings {
+            return;
+        }
+        *current = settings.clone();
+    }
+    let _ = app.emit("settings-changed", &settings);
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the current settings.
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Settings {
+    current(&app)
+}
+
+/// Updates settings from a partial object, saves them and emits `settings-changed`.
+///
+/// Fields not present in `patch` keep their current value. Invalid values
+/// are rejected and nothing is saved.
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: serde_json::Value) -> Result<Settings, String> {
+    let path = settings_path(&app)?;
+    let settings = {
+        let state = app.state::<SettingsState>();
+        let mut current = state.settings.lock().unwrap();
+        let settings = current.merged(patch)?;
+        write_settings(&path, &settings)?;
+        *current = settings.clone();
+        settings
+    };
+    let _ = app.emit("settings-changed", &settings);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_default_settings_are_valid() {
+        assert!(Settings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings = Settings::from_json(r#"{ "tab_size": 2 }"#).unwrap();
+        assert_eq!(settings.tab_size, 2);
+        assert_eq!(settings.font_size, Settings::default().font_size);
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_range_values() {
+        assert!(Settings::from_json(r#"{ "font_size": 200 }"#).is_err());
+        assert!(Settings::from_json(r#"{ "tab_size": 0 }"#).is_err());
+        assert!(Settings::from_json(r#"{ "max_recent_files": 0 }"#).is_err());
+        assert!(Settings::from_json(r#"{ "markdown_extensions": [] }"#).is_err());
+        assert!(Settings::from_json(r#"{ "markdown_extensions": ["m d"] }"#).is_err());
+        assert!(Settings::from_json(r#"{ "theme": "sepia" }"#).is_err());
+        assert!(Settings::from_json(r#"{ "trusted_folders": ["notes"] }"#).is_err());
+        assert!(Settings::from_json(r#"{ "toc": { "min_level": 4, "max_level": 2 } }"#).is_err());
+    }
+
+    #[test]
+    fn test_normalize_extensions() {
+        let settings =
+            Settings::from_json(r#"{ "markdown_extensions": [".md", " mdx ", "md", "MD"] }"#)
+                .unwrap();
+        assert_eq!(settings.markdown_extensions, vec!["md", "mdx"]);
+    }
+
+    #[test]
+    fn test_merged_applies_partial_update() {
+        let settings = Settings::default()
+            .merged(json!({ "theme": "dark", "word_wrap": false }))
+            .unwrap();
+        assert_eq!(settings.theme, Theme::Dark);
+        assert!(!settings.word_wrap);
+        assert_eq!(settings.tab_size, Settings::default().tab_size);
+    }
+
+    #[test]
+    fn test_merged_rejects_invalid_updates() {
+        let settings = Settings::default();
+        assert!(settings.merged(json!({ "no_such_setting": 1 })).is_err());
+        assert!(settings.merged(json!({ "font_size": "large" })).is_err());
+        assert!(settings.merged(json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_read_and_write_settings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config").join(SETTINGS_FILE);
+
+        assert_eq!(read_settings(&path).unwrap(), Settings::default());
+
+        let settings = Settings {
+            default_view_mode: ViewMode::Preview,
+            ..Settings::default()
+        };
+        write_settings(&path, &settings).unwrap();
+        assert_eq!(read_settings(&path).unwrap(), settings);
+
+        fs::write(&path, "{ invalid").unwrap();
+        assert!(read_settings(&path).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use tokio::sync::oneshot;
+
+/// How long to wait for the frontend to report its dirty documents.
+///
+/// A webview that doesn't answer in time is assumed to be unresponsive and
+/// is allowed to close; its buffers are still covered by the draft journal.
+const DIRTY_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for the frontend to finish saving documents.
+///
+/// Generous because saving untitled documents goes through a "Save As" dialog.
+const SAVE_TIMEOUT: Duration = Duration::from_secs(300);
+
+const SAVE_LABEL: &str = "Save";
+const DONT_SAVE_LABEL: &str = "Don't Save";
+const CANCEL_LABEL: &str = "Cancel";
+
+/// Document with unsaved changes, as reported by the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirtyDocument {
+    pub id: String,
+    pub path: Option<String>,
+    pub name: String,
+}
+
+/// Payload of the `unsaved-changes-query` event
+#[derive(Debug, Clone, Serialize)]
+struct DirtyQuery {
+    request_id: u64,
+}
+
+/// Payload of the `save-documents-requested` event
+#[derive(Debug, Clone, Serialize)]
+struct SaveRequest {
+    request_id: u64,
+    ids: Vec<String>,
+}
+
+/// User's answer for a single dirty document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+impl SaveChoice {
+    fn from_result(result: &MessageDialogResult) -> Self {
+        match result {
+            MessageDialogResult::Yes => SaveChoice::Save,
+            MessageDialogResult::No => SaveChoice::Discard,
+            MessageDialogResult::Custom(label) if label == SAVE_LABEL => SaveChoice::Save,
+            MessageDialogResult::Custom(label) if label == DONT_SAVE_LABEL => SaveChoice::Discard,
+            _ => SaveChoice::Cancel,
+        }
+    }
+}
+
+/// Requests sent to the frontend that are waiting for a response command
+struct PendingRequests<T> {
+    next_id: AtomicU64,
+    senders: Mutex<HashMap<u64, oneshot::Sender<T>>>,
+}
+
+impl<T> Default for PendingRequests<T> {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            senders: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T> PendingRequests<T> {
+    /// Registers a new request, returning its id and the receiver for the response
+    fn register(&self) -> (u64, oneshot::Receiver<T>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.senders.lock().unwrap().insert(id, tx);
+        (id, rx)
+    }
+
+    /// Delivers a response; returns `false` if the request is unknown or timed out
+    fn resolve(&self, id: u64, value: T) -> bool {
+        match self.senders.lock().unwrap().remove(&id) {
+            Some(tx) => tx.send(value).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Drops a request that will not be answered
+    fn forget(&self, id: u64) {
+        self.senders.lock().unwrap().remove(&id);
+    }
+}
+
+/// State for the close/quit negotiation with the frontend
+#[derive(Default)]
+pub struct ShutdownState {
+    dirty_queries: PendingRequests<Vec<DirtyDocument>>,
+    save_requests: PendingRequests<Vec<String>>,
+    in_progress: AtomicBool,
+    /// Set once a quit was negotiated, so the resulting exit goes through
+    exiting: AtomicBool,
+    /// Windows whose frontend answers `unsaved-changes-query`
+    answering: Mutex<HashSet<String>>,
+}
+
+impl ShutdownState {
+    fn is_answering(&self, window: &str) -> bool {
+        self.answering.lock().unwrap().contains(window)
+    }
+
+    /// Forgets a window that was closed.
+    pub fn unregister(&self, window: &str) {
+        self.answering.lock().unwrap().remove(window);
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Announces that the calling window answers `unsaved-changes-query` events.
+///
+/// Windows that never call this are closed right away, without asking.
+#[tauri::command]
+pub fn register_unsaved_changes_handler(app: AppHandle, window: WebviewWindow) {
+    app.state::<ShutdownState>()
+        .answering
+        .lock()
+        .unwrap()
+        .insert(window.label().to_string());
+}
+
+/// Answers an `unsaved-changes-query` event with the window's dirty documents.
+#[tauri::command]
+pub fn report_dirty_documents(
+    app: AppHandle,
+    request_id: u64,
+    documents: Vec<DirtyDocument>,
+) -> Result<(), String> {
+    let state = app.state::<ShutdownState>();
+    if state.dirty_queries.resolve(request_id, documents) {
+        Ok(())
+    } else {
+        Err(format!("Unknown or expired request: {}", request_id))
+    }
+}
+
+/// Answers a `save-documents-requested` event with the ids that were saved.
+#[tauri::command]
+pub fn report_saved_documents(
+    app: AppHandle,
+    request_id: u64,
+    saved: Vec<String>,
+) -> Result<(), String> {
+    let state = app.state::<ShutdownState>();
+    if state.save_requests.resolve(request_id, saved) {
+        Ok(())
+    } else {
+        Err(format!("Unknown or expired request: {}", request_id))
+    }
+}
+
+// ============================================================================
+// Negotiation
+// ============================================================================
+
+/// Closes a window once its unsaved changes are resolved.
+///
+/// Called for `CloseRequested` (after preventing the default close) and for
+/// the "Close Window" menu item.
+pub fn request_close(app: &AppHandle, window: WebviewWindow) {
+    let handle = app.clone();
+    run_exclusive(app, async move {
+        if resolve_window(&handle, &window).await {
+            let _ = window.destroy();
+        }
+    });
+}
+
+/// Exits the application once unsaved changes in every window are resolved.
+pub fn request_quit(app: &AppHandle) {
+    let handle = app.clone();
+    run_exclusive(app, async move {
+        let mut windows: Vec<WebviewWindow> = handle.webview_windows().into_values().collect();
+        windows.sort_by(|a, b| a.label().cmp(b.label()));
+
+        for window in windows {
+            if !resolve_window(&handle, &window).await {
+                return;
+            }
+        }
+        crate::window_state::save_all(&handle);
+        crate::wait::finish_all(&handle);
+        handle
+            .state::<ShutdownState>()
+            .exiting
+            .store(true, Ordering::SeqCst);
+        handle.exit(0);
+    });
+}
+
+/// Holds back an exit that was not negotiated, e.g. one requested by the
+/// OS, and resolves unsaved changes in every window first.
+///
+/// Returns `true` if the exit must be prevented. Exits after the last
+/// window closed go through, as there is nothing left to resolve.
+pub fn intercept_exit(app: &AppHandle) -> bool {
+    let state = app.state::<ShutdownState>();
+    if state.exiting.load(Ordering::SeqCst) || app.webview_windows().is_empty() {
+        return false;
+    }
+    request_quit(app);
+    true
+}
+
+/// Runs a negotiation unless one is already in progress.
+///
+/// Repeated close/quit requests while dialogs are showing are ignored.
+fn run_exclusive<F>(app: &AppHandle, negotiation: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    let state = app.state::<ShutdownState>();
+    if state.in_progress.swap(true, Ordering::SeqCst) {
+        eprintln!("[shutdown] Negotiation already in progress");
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        negotiation.await;
+        app.state::<ShutdownState>()
+            .in_progress
+            .store(false, Ordering::SeqCst);
+    });
+}
+
+/// Resolves all unsaved changes in a window.
+///
+/// Returns `true` if the window may close, `false` if the user cancelled
+/// or a document failed to save.
+async fn resolve_window(app: &AppHandle, window: &WebviewWindow) -> bool {
+    let documents = query_dirty_documents(app, window).await;
+    if documents.is_empty() {
+        return true;
+    }
+
+    let _ = window.set_focus();
+
+    let mut to_save = Vec::new();
+    let mut to_discard = Vec::new();
+    for document in &documents {
+        match ask_save(app, window, document).await {
+            SaveChoice::Save => to_save.push(document.id.clone()),
+            SaveChoice::Discard => to_discard.push(document.id.clone()),
+            SaveChoice::Cancel => return false,
+        }
+    }
+
+    if !to_save.is_empty() {
+        let saved = save_documents(app, window, to_save.clone()).await;
+        if let Some(failed) = to_save.iter().find(|id| !saved.contains(id)) {
+            eprintln!(
+                "[shutdown] Document {} was not saved, aborting close",
+                failed
+            );
+            return false;
+        }
+    }
+
+    // Discarded buffers must not be offered for recovery on next launch,
+    // and a `--wait` caller should not use the file as if it were edited
+    if !to_discard.is_empty() {
+        crate::wait::mark_aborted(app, window.label());
+    }
+    for id in to_discard {
+        let _ = crate::drafts::discard_draft(app.clone(), id);
+    }
+
+    true
+}
+
+/// Asks the window's frontend which documents have unsaved changes.
+async fn query_dirty_documents(app: &AppHandle, window: &WebviewWindow) -> Vec<DirtyDocument> {
+    let state = app.state::<ShutdownState>();
+    if !state.is_answering(window.label()) {
+        return Vec::new();
+    }
+    let (request_id, rx) = state.dirty_queries.register();
+
+    if app
+        .emit_to(
+            window.label(),
+            "unsaved-changes-query",
+            DirtyQuery { request_id },
+        )
+        .is_err()
+    {
+        state.dirty_queries.forget(request_id);
+        return Vec::new();
+    }
+
+    match tokio::time::timeout(DIRTY_QUERY_TIMEOUT, rx).await {
+        Ok(Ok(documents)) => documents,
+        _ => {
+            eprintln!("[shutdown] No answer from window {}", window.label());
+            state.dirty_queries.forget(request_id);
+            Vec::new()
+        }
+    }
+}
+
+/// Asks the window's frontend to save documents, returning the ids that were saved.
+async fn save_documents(app: &AppHandle, window: &WebviewWindow, ids: Vec<String>) -> Vec<String> {
+    let state = app.state::<ShutdownState>();
+    let (request_id, rx) = state.save_requests.register();
+
+    if app
+        .emit_to(
+            window.label(),
+            "save-documents-requested",
+            SaveRequest { request_id, ids },
+        )
+        .is_err()
+    {
+        state.save_requests.forget(request_id);
+        return Vec::new();
+    }
+
+    match tokio::time::timeout(SAVE_TIMEOUT, rx).await {
+        Ok(Ok(saved)) => saved,
+        _ => {
+            state.save_requests.forget(request_id);
+            Vec::new()
+        }
+    }
+}
+
+/// Shows the native Save / Don't Save / Cancel dialog for a document.
+async fn ask_save(app: &AppHandle, window: &WebviewWindow, document: &DirtyDocument) -> SaveChoice {
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .message(format!(
+            "Do you want to save the changes you made to \"{}\"?\n\nYour changes will be lost if you don't save them.",
+            document.name
+        ))
+        .title("Unsaved Changes")
+        .kind(MessageDialogKind::Warning)
+        .parent(window)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            SAVE_LABEL.to_string(),
+            DONT_SAVE_LABEL.to_string(),
+            CANCEL_LABEL.to_string(),
+        ))
+        .show_with_result(move |result| {
+            let _ = tx.send(result);
+        });
+
+    rx.await
+        .map(|result| SaveChoice::from_result(&result))
+        .unwrap_or(SaveChoice::Cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_requests_resolve() {
+        let pending = PendingRequests::<Vec<String>>::default();
+        let (id, mut rx) = pending.register();
+
+        assert!(pending.resolve(id, vec!["doc-1".to_string()]));
+        assert_eq!(rx.try_recv().unwrap(), vec!["doc-1".to_string()]);
+        assert!(!pending.resolve(id, Vec::new()));
+    }
+
+    #[test]
+    fn test_pending_requests_unique_ids() {
+        let pending = PendingRequests::<()>::default();
+        let (first, _rx1) = pending.register();
+        let (second, _rx2) = pending.register();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_pending_requests_forget() {
+        let pending = PendingRequests::<()>::default();
+        let (id, _rx) = pending.register();
+        pending.forget(id);
+        assert!(!pending.resolve(id, ()));
+    }
+
+    #[test]
+    fn test_only_registered_windows_are_asked() {
+        let state = ShutdownState::default();
+        assert!(!state.is_answering("main"));
+        state.answering.lock().unwrap().insert("main".to_string());
+        assert!(state.is_answering("main"));
+        state.unregister("main");
+        assert!(!state.is_answering("main"));
+    }
+
+    #[test]
+    fn test_save_choice_from_result() {
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::Yes),
+            SaveChoice::Save
+        );
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::No),
+            SaveChoice::Discard
+        );
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::Cancel),
+            SaveChoice::Cancel
+        );
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::Custom(SAVE_LABEL.to_string())),
+            SaveChoice::Save
+        );
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::Custom(DONT_SAVE_LABEL.to_string())),
+            SaveChoice::Discard
+        );
+        assert_eq!(
+            SaveChoice::from_result(&MessageDialogResult::Custom(CANCEL_LABEL.to_string())),
+            SaveChoice::Cancel
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+/// Hidden flag that tells a launched copy which wait request it serves
+pub const WAIT_ID_FLAG: &str = "--wait-id";
+
+/// How long to wait for an instance to accept a request.
+///
+/// Generous because a cold start has to load the webview first.
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often to look for the instance's answer while waiting for it
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exit status of `--wait` when the document was closed normally
+const EXIT_DONE: i32 = 0;
+
+/// Exit status of `--wait` when the user aborted or the instance failed
+const EXIT_ABORTED: i32 = 1;
+
+/// Outcome of a wait request, as written to its status file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitStatus {
+    /// The instance accepted the request and the window is open
+    Pending,
+    /// The window was closed
+    Done,
+    /// The window was closed with its changes discarded
+    Aborted,
+}
+
+impl WaitStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WaitStatus::Pending => "pending",
+            WaitStatus::Done => "done",
+            WaitStatus::Aborted => "aborted",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "pending" => Some(WaitStatus::Pending),
+            "done" => Some(WaitStatus::Done),
+            "aborted" => Some(WaitStatus::Aborted),
+            _ => None,
+        }
+    }
+}
+
+/// Directory shared by waiting processes and the instance that serves them
+fn wait_dir() -> Result<PathBuf, String> {
+    crate::cli::private_dir("wait")
+}
+
+/// File the instance keeps locked while the request's window is open
+fn lock_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.lock", id))
+}
+
+/// File holding the request's [`WaitStatus`]
+fn status_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.status", id))
+}
+
+/// Reads the status of a request, `None` if the instance hasn't answered yet.
+fn read_status(dir: &Path, id: &str) -> Option<WaitStatus> {
+    fs::read_to_string(status_path(dir, id))
+        .ok()
+        .and_then(|s| WaitStatus::parse(&s))
+}
+
+/// Replaces the status file so readers never see a partial write.
+fn write_status(dir: &Path, id: &str, status: WaitStatus) -> Result<(), String> {
+    let path = status_path(dir, id);
+    let tmp = path.with_extension("status.tmp");
+    fs::write(&tmp, status.as_str()).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// Checks that a request id can't name a file outside the wait directory.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+// ============================================================================
+// Instance side
+// ============================================================================
+
+/// Request accepted by this instance, held until its documents or window close
+pub struct Waiter {
+    dir: PathBuf,
+    id: String,
+    lock: File,
+    aborted: bool,
+    /// Documents of the request that are still open
+    documents: Vec<String>,
+}
+
+impl Waiter {
+    /// Takes the request's lock and reports it as pending.
+    fn accept(dir: &Path, id: &str, documents: Vec<String>) -> Result<Self, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid wait request id: {}", id));
+        }
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path(dir, id))
+            .map_err(|e| e.to_string())?;
+        lock.lock().map_err(|e| e.to_string())?;
+        write_status(dir, id, WaitStatus::Pending)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            id: id.to_string(),
+            lock,
+            aborted: false,
+            documents,
+        })
+    }
+
+    /// Records that a document was closed; returns `true` once none are left.
+    ///
+    /// Requests without documents only complete when their window closes.
+    fn close_document(&mut self, path: &str, discarded: bool) -> bool {
+        let before = self.documents.len();
+        self.documents.retain(|document| document != path);
+        if self.documents.len() == before {
+            return false;
+        }
+        self.aborted |= discarded;
+        self.documents.is_empty()
+    }
+
+    /// Writes the final status and releases the lock, waking the waiting process.
+    fn finish(self) {
+        let status = if self.aborted {
+            WaitStatus::Aborted
+        } else {
+            WaitStatus::Done
+        };
+        if let Err(e) = write_status(&self.dir, &self.id, status) {
+            eprintln!("[wait] Failed to complete request {}: {}", self.id, e);
+        }
+        let _ = self.lock.unlock();
+    }
+}
+
+/// Wait requests accepted by this instance, by window label
+#[derive(Default)]
+pub struct WaitRegistry {
+    waiters: Mutex<HashMap<String, Vec<Waiter>>>,
+}
+
+/// Ties a wait request to a window, so it completes when its documents are
+/// closed or the window closes.
+pub fn register(app: &AppHandle, window: &str, id: &str, documents: Vec<String>) {
+    match wait_dir().and_then(|dir| Waiter::accept(&dir, id, documents)) {
+        Ok(waiter) => app
+            .state::<WaitRegistry>()
+            .waiters
+            .lock()
+            .unwrap()
+            .entry(window.to_string())
+            .or_default()
+            .push(waiter),
+        Err(e) => eprintln!("[wait] Failed to accept request {}: {}", id, e),
+    }
+}
+
+/// Marks the requests of a window as aborted, e.g. when its changes are discarded.
+pub fn mark_aborted(app: &AppHandle, window: &str) {
+    if let Some(waiters) = app
+        .state::<WaitRegistry>()
+        .waiters
+        .lock()
+        .unwrap()
+        .get_mut(window)
+    {
+        for waiter in waiters {
+            waiter.aborted = true;
+        }
+    }
+}
+
+/// Completes the requests of a window that was closed.
+pub fn finish_window(app: &AppHandle, window: &str) {
+    let waiters = app
+        .state::<WaitRegistry>()
+        .waiters
+        .lock()
+        .unwrap()
+        .remove(window);
+    for waiter in waiters.into_iter().flatten() {
+        waiter.finish();
+    }
+}
+
+/// Completes the requests of a window whose documents have all been closed.
+fn close_document(app: &AppHandle, window: &str, path: &str, discarded: bool) {
+    let mut finished = Vec::new();
+    {
+        let state = app.state::<WaitRegistry>();
+        let mut waiters = state.waiters.lock().unwrap();
+        let Some(window_waiters) = waiters.get_mut(window) else {
+            return;
+        };
+        let mut i = 0;
+        while i < window_waiters.len() {
+            if window_waiters[i].close_document(path, discarded) {
+                finished.push(window_waiters.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+    }
+    for waiter in finished {
+        waiter.finish();
+    }
+}
+
+/// Reports that the calling window closed a document, with `discarded` set
+/// if its unsaved changes were thrown away.
+#[tauri::command]
+pub fn document_closed(app: AppHandle, window: WebviewWindow, path: String, discarded: bool) {
+    close_document(&app, window.label(), &path, discarded);
+}
+
+/// Completes all requests before the application exits.
+pub fn finish_all(app: &AppHandle) {
+    let waiters: Vec<Waiter> = app
+        .state::<WaitRegistry>()
+        .waiters
+        .lock()
+        .unwrap()
+        .drain()
+        .flat_map(|(_, waiters)| waiters)
+        .collect();
+    for waiter in waiters {
+        waiter.finish();
+    }
+}
+
+// ============================================================================
+// Waiting process
+// ============================================================================
+
+/// Runs the launch in a separate process and blocks until its window is closed.
+///
+/// The copy either becomes the running instance or forwards the request to
+/// it; either way the instance answers through the wait directory. Returns
+/// the exit status for this process.
+pub fn launch_and_wait(args: Vec<OsString>) -> i32 {
+    let dir = match wait_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("mark-lens: {}", e);
+            return EXIT_ABORTED;
+        }
+    };
+    let id = crate::cli::unique_id();
+
+    // Ahead of the forwarded arguments, which may end with `--` and paths
+    let mut copy_args: Vec<OsString> = vec![WAIT_ID_FLAG.into(), id.clone().into()];
+    copy_args.extend(args);
+    let mut child = match crate::cli::spawn_copy(copy_args) {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("mark-lens: failed to launch: {}", e);
+            return EXIT_ABORTED;
+        }
+    };
+
+    let launch_failed = move || matches!(child.try_wait(), Ok(Some(status)) if !status.success());
+    match wait_for(&dir, &id, ACCEPT_TIMEOUT, launch_failed) {
+        Ok(WaitStatus::Done) => EXIT_DONE,
+        Ok(_) => {
+            eprintln!("mark-lens: editing was aborted");
+            EXIT_ABORTED
+        }
+        Err(e) => {
+            eprintln!("mark-lens: {}", e);
+            EXIT_ABORTED
+        }
+    }
+}
+
+/// Waits for an instance to accept request `id`, then for it to finish.
+///
+/// The instance holds the request's lock while the window is open, so
+/// taking the lock blocks until the window closes or the instance exits.
+/// An instance that exits without answering leaves the status pending.
+fn wait_for(
+    dir: &Path,
+    id: &str,
+    timeout: Duration,
+    mut launch_failed: impl FnMut() -> bool,
+) -> Result<WaitStatus, String> {
+    let deadline = Instant::now() + timeout;
+    while read_status(dir, id).is_none() {
+        if launch_failed() {
+            return Err("Mark Lens failed to start".to_string());
+        }
+        if Instant::now() >= deadline {
+            return Err("Mark Lens did not respond".to_string());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    let result = File::open(lock_path(dir, id))
+        .and_then(|lock| lock.lock())
+        .map_err(|e| e.to_string())
+        .map(|_| read_status(dir, id));
+    let _ = fs::remove_file(lock_path(dir, id));
+    let _ = fs::remove_file(status_path(dir, id));
+
+    match result? {
+        Some(WaitStatus::Pending) | None => Err("Mark Lens exited unexpectedly".to_string()),
+        Some(status) => Ok(status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_wait_status_round_trip() {
+        for status in [WaitStatus::Pending, WaitStatus::Done, WaitStatus::Aborted] {
+            assert_eq!(WaitStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(WaitStatus::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_accept_rejects_invalid_ids() {
+        let dir = tempdir().unwrap();
+        assert!(Waiter::accept(dir.path(), "../escape", Vec::new()).is_err());
+        assert!(Waiter::accept(dir.path(), "", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_wait_blocks_until_finished() {
+        let dir = tempdir().unwrap();
+        let mut waiter = Waiter::accept(dir.path(), "42-1", Vec::new()).unwrap();
+        waiter.aborted = true;
+
+        let finisher = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            waiter.finish();
+        });
+        let status = wait_for(dir.path(), "42-1", Duration::from_secs(5), || false);
+        finisher.join().unwrap();
+
+        assert_eq!(status, Ok(WaitStatus::Aborted));
+        assert!(!lock_path(dir.path(), "42-1").exists());
+        assert!(!status_path(dir.path(), "42-1").exists());
+    }
+
+    #[test]
+    fn test_wait_times_out_without_instance() {
+        let dir = tempdir().unwrap();
+        assert!(wait_for(dir.path(), "1-1", Duration::ZERO, || false).is_err());
+        assert!(wait_for(dir.path(), "1-1", Duration::from_secs(5), || true).is_err());
+    }
+
+    #[test]
+    fn test_waiter_completes_when_its_documents_are_closed() {
+        let dir = tempdir().unwrap();
+        let documents = vec!["/a.md".to_string(), "/b.md".to_string()];
+        let mut waiter = Waiter::accept(dir.path(), "7-1", documents).unwrap();
+
+        assert!(!waiter.close_document("/other.md", true));
+        assert!(!waiter.aborted);
+        assert!(!waiter.close_document("/a.md", false));
+        assert!(waiter.close_document("/b.md", true));
+        assert!(waiter.aborted);
+    }
+}
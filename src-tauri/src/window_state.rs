@@ -0,0 +1,451 @@
+use crate::windows::{WindowRegistry, MAIN_WINDOW};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+const WINDOW_STATE_FILE: &str = "window_state.json";
+
+/// Delay before writing geometry to disk, so a drag or resize is saved once
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Rectangle in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Bounds {
+    /// Area shared with another rectangle, in square pixels
+    fn overlap(&self, other: &Bounds) -> u64 {
+        let left = self.x.max(other.x) as i64;
+        let top = self.y.max(other.y) as i64;
+        let right = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let bottom = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+        if right <= left || bottom <= top {
+            0
+        } else {
+            ((right - left) * (bottom - top)) as u64
+        }
+    }
+}
+
+/// Saved position, size and state of a window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Outer position and size of the window when neither maximized nor fullscreen
+    pub bounds: Bounds,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    /// Name of the monitor the window was on
+    pub monitor: Option<String>,
+}
+
+/// Part of a monitor available to windows
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MonitorArea {
+    name: Option<String>,
+    bounds: Bounds,
+}
+
+/// Window geometry by [`state_key`], persisted to `window_state.json`
+#[derive(Default)]
+pub struct WindowStateStore {
+    windows: Mutex<HashMap<String, WindowGeometry>>,
+    generation: AtomicU64,
+}
+
+/// Fits saved geometry into the current monitor layout.
+///
+/// The window goes back to the monitor it was on if that monitor is still
+/// connected, otherwise to the monitor it overlaps most, otherwise to the
+/// first monitor. It is then shrunk to fit and moved fully inside.
+fn clamp_to_monitors(geometry: &WindowGeometry, monitors: &[MonitorArea]) -> WindowGeometry {
+    let named = geometry
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name.as_ref() == Some(name)));
+    let overlapping = monitors
+        .iter()
+        .map(|m| (m, m.bounds.overlap(&geometry.bounds)))
+        .filter(|(_, overlap)| *overlap > 0)
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(m, _)| m);
+    let Some(monitor) = named.or(overlapping).or(monitors.first()) else {
+        return geometry.clone();
+    };
+
+    let area = monitor.bounds;
+    let width = geometry.bounds.width.min(area.width);
+    let height = geometry.bounds.height.min(area.height);
+    let max_x = area.x + (area.width - width) as i32;
+    let max_y = area.y + (area.height - height) as i32;
+
+    WindowGeometry {
+        bounds: Bounds {
+            x: geometry.bounds.x.clamp(area.x, max_x),
+            y: geometry.bounds.y.clamp(area.y, max_y),
+            width,
+            height,
+        },
+        maximized: geometry.maximized,
+        fullscreen: geometry.fullscreen,
+        monitor: monitor.name.clone(),
+    }
+}
+
+/// Reads saved geometry; a missing or invalid file yields no entries.
+fn read_state(path: &Path) -> HashMap<String, WindowGeometry> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Writes geometry for all windows.
+fn write_state(path: &Path, windows: &HashMap<String, WindowGeometry>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(windows).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Loads saved geometry from the app data directory.
+pub fn load(app: &AppHandle) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let windows = read_state(&dir.join(WINDOW_STATE_FILE));
+    *app.state::<WindowStateStore>().windows.lock().unwrap() = windows;
+}
+
+/// Saves geometry for all windows to disk.
+fn save(app: &AppHandle) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let store = app.state::<WindowStateStore>();
+    let windows = store.windows.lock().unwrap();
+    if let Err(e) = write_state(&dir.join(WINDOW_STATE_FILE), &windows) {
+        eprintln!("[window_state] Failed to save: {}", e);
+    }
+}
+
+/// Lists the work areas of connected monitors.
+fn monitor_areas(window: &WebviewWindow) -> Vec<MonitorArea> {
+    window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let area = monitor.work_area();
+            MonitorArea {
+                name: monitor.name().cloned(),
+                bounds: Bounds {
+                    x: area.position.x,
+                    y: area.position.y,
+                    width: area.size.width,
+                    height: area.size.height,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Reads a window's current geometry.
+///
+/// While the window is maximized or fullscreen, the previously saved bounds
+/// are kept so that un-maximizing after a restart returns to them.
+fn capture(window: &WebviewWindow, previous: Option<&WindowGeometry>) -> Option<WindowGeometry> {
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+
+    let bounds = match previous {
+        Some(previous) if maximized || fullscreen => previous.bounds,
+        _ => {
+            let position = window.outer_position().ok()?;
+            let size = window.outer_size().ok()?;
+            Bounds {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            }
+        }
+    };
+
+    Some(WindowGeometry {
+        bounds,
+        maximized,
+        fullscreen,
+        monitor: window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned()),
+    })
+}
+
+/// Key a window's geometry is saved under.
+///
+/// Labels of extra windows are handed out anew each session, so those windows
+/// are keyed by their document instead; windows without one are not saved.
+fn state_key(label: &str, document: Option<String>) -> Option<String> {
+    if label == MAIN_WINDOW {
+        Some(MAIN_WINDOW.to_string())
+    } else {
+        document
+    }
+}
+
+/// Looks up the key for a window's geometry.
+fn window_key(app: &AppHandle, window: &WebviewWindow) -> Option<String> {
+    let document = app.state::<WindowRegistry>().document(window.label());
+    state_key(window.label(), document)
+}
+
+/// Records a window's geometry in memory; returns `true` if it changed.
+fn update(app: &AppHandle, window: &WebviewWindow) -> bool {
+    let Some(key) = window_key(app, window) else {
+        return false;
+    };
+    let store = app.state::<WindowStateStore>();
+    let mut windows = store.windows.lock().unwrap();
+    let previous = windows.get(&key);
+    match capture(window, previous) {
+        Some(geometry) if previous != Some(&geometry) => {
+            windows.insert(key, geometry);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Records a window's geometry after it moved or resized, saving it shortly after.
+///
+/// Saves are debounced: only the last of a burst of events writes to disk.
+pub fn track(app: &AppHandle, window: &WebviewWindow) {
+    if !update(app, window) {
+        return;
+    }
+
+    let generation = app
+        .state::<WindowStateStore>()
+        .generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        let store = app.state::<WindowStateStore>();
+        if store.generation.load(Ordering::SeqCst) == generation {
+            save(&app);
+        }
+    });
+}
+
+/// Records and saves the geometry of every open window, e.g. before quitting.
+pub fn save_all(app: &AppHandle) {
+    for window in app.webview_windows().values() {
+        update(app, window);
+    }
+    save(app);
+}
+
+/// Records and saves a window's geometry immediately, e.g. when it is closing.
+pub fn save_window(app: &AppHandle, window: &WebviewWindow) {
+    update(app, window);
+    save(app);
+}
+
+/// Inner size that gives a window the outer size of `bounds`, given its
+/// current outer and inner size.
+fn inner_size_for(
+    bounds: &Bounds,
+    outer: PhysicalSize<u32>,
+    inner: PhysicalSize<u32>,
+) -> PhysicalSize<u32> {
+    let frame_width = outer.width.saturating_sub(inner.width);
+    let frame_height = outer.height.saturating_sub(inner.height);
+    PhysicalSize::new(
+        bounds.width.saturating_sub(frame_width),
+        bounds.height.saturating_sub(frame_height),
+    )
+}
+
+/// Applies saved geometry to a window, clamped to the current monitors.
+///
+/// Returns `false` if nothing was saved for the window.
+pub fn restore(app: &AppHandle, window: &WebviewWindow) -> bool {
+    let Some(key) = window_key(app, window) else {
+        return false;
+    };
+    let saved = app
+        .state::<WindowStateStore>()
+        .windows
+        .lock()
+        .unwrap()
+        .get(&key)
+        .cloned();
+    let Some(saved) = saved else {
+        return false;
+    };
+
+    let geometry = clamp_to_monitors(&saved, &monitor_areas(window));
+    // `set_size` takes the inner size, so leave room for the window frame
+    if let (Ok(outer), Ok(inner)) = (window.outer_size(), window.inner_size()) {
+        let _ = window.set_size(inner_size_for(&geometry.bounds, outer, inner));
+    }
+    let _ = window.set_position(PhysicalPosition::new(geometry.bounds.x, geometry.bounds.y));
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+    if geometry.fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn bounds(x: i32, y: i32, width: u32, height: u32) -> Bounds {
+        Bounds {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn geometry(bounds: Bounds, monitor: Option<&str>) -> WindowGeometry {
+        WindowGeometry {
+            bounds,
+            maximized: false,
+            fullscreen: false,
+            monitor: monitor.map(str::to_string),
+        }
+    }
+
+    fn monitor(name: &str, bounds: Bounds) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_string()),
+            bounds,
+        }
+    }
+
+    #[test]
+    fn test_overlap() {
+        let a = bounds(0, 0, 100, 100);
+        assert_eq!(a.overlap(&bounds(50, 50, 100, 100)), 2500);
+        assert_eq!(a.overlap(&bounds(100, 0, 100, 100)), 0);
+        assert_eq!(a.overlap(&bounds(-500, -500, 10, 10)), 0);
+    }
+
+    #[test]
+    fn test_clamp_keeps_visible_window() {
+        let monitors = [monitor("laptop", bounds(0, 0, 1920, 1080))];
+        let saved = geometry(bounds(100, 100, 1200, 800), Some("laptop"));
+        assert_eq!(clamp_to_monitors(&saved, &monitors), saved);
+    }
+
+    #[test]
+    fn test_clamp_after_external_display_removed() {
+        // Window was on an external display to the right of the laptop
+        let monitors = [monitor("laptop", bounds(0, 0, 1440, 900))];
+        let saved = geometry(bounds(2200, 300, 1600, 1000), Some("external"));
+
+        let clamped = clamp_to_monitors(&saved, &monitors);
+        assert_eq!(clamped.bounds, bounds(0, 0, 1440, 900));
+        assert_eq!(clamped.monitor, Some("laptop".to_string()));
+    }
+
+    #[test]
+    fn test_clamp_prefers_named_monitor() {
+        let monitors = [
+            monitor("left", bounds(0, 0, 1920, 1080)),
+            monitor("right", bounds(1920, 0, 2560, 1440)),
+        ];
+        // The right monitor moved; the window follows it by name
+        let saved = geometry(bounds(100, 100, 1200, 800), Some("right"));
+        let clamped = clamp_to_monitors(&saved, &monitors);
+        assert_eq!(clamped.bounds, bounds(1920, 100, 1200, 800));
+    }
+
+    #[test]
+    fn test_clamp_uses_most_overlapping_monitor() {
+        let monitors = [
+            monitor("left", bounds(0, 0, 1920, 1080)),
+            monitor("right", bounds(1920, 0, 1920, 1080)),
+        ];
+        let saved = geometry(bounds(1700, 100, 1000, 800), None);
+        let clamped = clamp_to_monitors(&saved, &monitors);
+        assert_eq!(clamped.monitor, Some("right".to_string()));
+        assert_eq!(clamped.bounds, bounds(1920, 100, 1000, 800));
+    }
+
+    #[test]
+    fn test_clamp_without_monitors() {
+        let saved = geometry(bounds(-5000, -5000, 1200, 800), None);
+        assert_eq!(clamp_to_monitors(&saved, &[]), saved);
+    }
+
+    #[test]
+    fn test_state_key() {
+        assert_eq!(
+            state_key(MAIN_WINDOW, Some("/notes/a.md".to_string())),
+            Some(MAIN_WINDOW.to_string())
+        );
+        assert_eq!(
+            state_key("window-3", Some("/notes/a.md".to_string())),
+            Some("/notes/a.md".to_string())
+        );
+        assert_eq!(state_key("window-3", None), None);
+    }
+
+    #[test]
+    fn test_read_and_write_state() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(WINDOW_STATE_FILE);
+        assert!(read_state(&path).is_empty());
+
+        let mut windows = HashMap::new();
+        windows.insert(
+            "main".to_string(),
+            WindowGeometry {
+                maximized: true,
+                ..geometry(bounds(10, 20, 1200, 800), Some("laptop"))
+            },
+        );
+        write_state(&path, &windows).unwrap();
+        assert_eq!(read_state(&path), windows);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(read_state(&path).is_empty());
+    }
+
+    #[test]
+    fn test_inner_size_leaves_room_for_frame() {
+        let size = inner_size_for(
+            &bounds(0, 0, 1200, 800),
+            PhysicalSize::new(1016, 839),
+            PhysicalSize::new(1000, 800),
+        );
+        assert_eq!(size, PhysicalSize::new(1184, 761));
+    }
+}
@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+/// Label of the window created from `tauri.conf.json`
+pub const MAIN_WINDOW: &str = "main";
+
+/// Prefix for labels of windows created at runtime
+const WINDOW_LABEL_PREFIX: &str = "window-";
+
+/// Tracks open windows, the document each one shows, and which one has focus
+pub struct WindowRegistry {
+    next_id: AtomicU64,
+    documents: Mutex<HashMap<String, Option<String>>>,
+    focused: Mutex<Option<String>>,
+}
+
+impl Default for WindowRegistry {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            documents: Mutex::new(HashMap::new()),
+            focused: Mutex::new(None),
+        }
+    }
+}
+
+impl WindowRegistry {
+    /// Returns a label that no other window has used in this session
+    fn next_label(&self) -> String {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        format!("{}{}", WINDOW_LABEL_PREFIX, id)
+    }
+
+    /// Adds a window, optionally with the document it shows
+    pub fn register(&self, label: &str, path: Option<String>) {
+        self.documents
+            .lock()
+            .unwrap()
+            .insert(label.to_string(), path);
+    }
+
+    /// Removes a window after it was destroyed
+    pub fn unregister(&self, label: &str) {
+        self.documents.lock().unwrap().remove(label);
+        let mut focused = self.focused.lock().unwrap();
+        if focused.as_deref() == Some(label) {
+            *focused = None;
+        }
+    }
+
+    /// Records the document shown in a window
+    pub fn set_document(&self, label: &str, path: Option<String>) {
+        if let Some(document) = self.documents.lock().unwrap().get_mut(label) {
+            *document = path;
+        }
+    }
+
+    /// Returns the document shown in a window
+    pub fn document(&self, label: &str) -> Option<String> {
+        self.documents.lock().unwrap().get(label).cloned().flatten()
+    }
+
+    /// Returns the label of the window showing a document, if any
+    pub fn window_for_document(&self, path: &str) -> Option<String> {
+        let documents = self.documents.lock().unwrap();
+        let mut labels: Vec<&String> = documents
+            .iter()
+            .filter(|(_, document)| document.as_deref() == Some(path))
+            .map(|(label, _)| label)
+            .collect();
+        labels.sort();
+        labels.first().map(|label| label.to_string())
+    }
+
+    /// Records the window that most recently gained focus
+    pub fn set_focused(&self, label: &str) {
+        *self.focused.lock().unwrap() = Some(label.to_string());
+    }
+
+    /// Returns the window that most recently gained focus
+    pub fn focused(&self) -> Option<String> {
+        self.focused.lock().unwrap().clone()
+    }
+}
+
+/// Open window with the document it shows
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    pub label: String,
+    pub path: Option<String>,
+}
+
+/// Returns the window that menu commands and opened files should go to.
+///
+/// This is the most recently focused window, falling back to the main
+/// window and then to any open window.
+pub fn focused_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let focused = app.state::<WindowRegistry>().focused();
+    focused
+        .and_then(|label| app.get_webview_window(&label))
+        .or_else(|| app.get_webview_window(MAIN_WINDOW))
+        .or_else(|| {
+            let mut windows: Vec<WebviewWindow> = app.webview_windows().into_values().collect();
+            windows.sort_by(|a, b| a.label().cmp(b.label()));
+            windows.into_iter().next()
+        })
+}
+
+/// Emits an event to the focused window only.
+pub fn emit_to_focused<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Some(window) = focused_window(app) {
+        let _ = app.emit_to(window.label(), event, payload);
+    }
+}
+
+/// Creates a new editor window, optionally showing a document.
+///
+/// The window's frontend reads its document with `get_window_document`.
+pub fn create_window(app: &AppHandle, path: Option<String>) -> Result<WebviewWindow, String> {
+    let registry = app.state::<WindowRegistry>();
+    let label = registry.next_label();
+
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
+        .title("Mark Lens")
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .resizable(true)
+        .center()
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    registry.register(&label, path);
+    registry.set_focused(&label);
+    crate::setup_window_event_handlers(&window, app.clone());
+
+    crate::window_state::restore(app, &window);
+    window.show().map_err(|e| e.to_string())?;
+    Ok(window)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Opens a new window and returns its label.
+///
+/// If `path` is already shown in another window, that window is focused instead.
+#[tauri::command]
+pub fn new_window(app: AppHandle, path: Option<String>) -> Result<String, String> {
+    if let Some(path) = &path {
+        let existing = app.state::<WindowRegistry>().window_for_document(path);
+        if let Some(window) = existing.and_then(|label| app.get_webview_window(&label)) {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+            return Ok(window.label().to_string());
+        }
+    }
+
+    let window = create_window(&app, path)?;
+    Ok(window.label().to_string())
+}
+
+/// Returns the document the calling window should show.
+#[tauri::command]
+pub fn get_window_document(app: AppHandle, window: WebviewWindow) -> Option<String> {
+    app.state::<WindowRegistry>().document(window.label())
+}
+
+/// Records the document shown in the calling window.
+#[tauri::command]
+pub fn set_window_document(app: AppHandle, window: WebviewWindow, path: Option<String>) {
+    app.state::<WindowRegistry>()
+        .set_document(window.label(), path);
+}
+
+/// Lists open windows with their documents.
+#[tauri::command]
+pub fn list_windows(app: AppHandle) -> Vec<WindowInfo> {
+    let registry = app.state::<WindowRegistry>();
+    let mut windows: Vec<WindowInfo> = registry
+        .documents
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(label, path)| WindowInfo {
+            label: label.clone(),
+            path: path.clone(),
+        })
+        .collect();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_label_is_unique() {
+        let registry = WindowRegistry::default();
+        let first = registry.next_label();
+        let second = registry.next_label();
+        assert_ne!(first, second);
+        assert!(first.starts_with(WINDOW_LABEL_PREFIX));
+        assert_ne!(first, MAIN_WINDOW);
+    }
+
+    #[test]
+    fn test_register_and_set_document() {
+        let registry = WindowRegistry::default();
+        registry.register(MAIN_WINDOW, None);
+        assert_eq!(registry.document(MAIN_WINDOW), None);
+
+        registry.set_document(MAIN_WINDOW, Some("/a.md".to_string()));
+        assert_eq!(registry.document(MAIN_WINDOW), Some("/a.md".to_string()));
+
+        // Unknown windows are not added implicitly
+        registry.set_document("window-9", Some("/b.md".to_string()));
+        assert_eq!(registry.document("window-9"), None);
+    }
+
+    #[test]
+    fn test_window_for_document() {
+        let registry = WindowRegistry::default();
+        registry.register(MAIN_WINDOW, Some("/a.md".to_string()));
+        registry.register("window-1", Some("/b.md".to_string()));
+
+        assert_eq!(
+            registry.window_for_document("/b.md"),
+            Some("window-1".to_string())
+        );
+        assert_eq!(registry.window_for_document("/c.md"), None);
+    }
+
+    #[test]
+    fn test_unregister_clears_focus() {
+        let registry = WindowRegistry::default();
+        registry.register("window-1", None);
+        registry.set_focused("window-1");
+        assert_eq!(registry.focused(), Some("window-1".to_string()));
+
+        registry.unregister("window-1");
+        assert_eq!(registry.focused(), None);
+        assert_eq!(registry.window_for_document("/a.md"), None);
+    }
+}
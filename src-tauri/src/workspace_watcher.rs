@@ -0,0 +1,202 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Longest a batch of changes is held back while more keep arriving
+const MAX_BATCH_WAIT: Duration = Duration::from_secs(2);
+
+/// Most paths collected into one batch
+const MAX_BATCH_PATHS: usize = 1000;
+
+/// Receivers of a watcher's changes, by subscriber name
+type Subscribers = Arc<Mutex<HashMap<&'static str, mpsc::Sender<PathBuf>>>>;
+
+/// Recursive watcher of one workspace
+struct SharedWatcher {
+    _watcher: RecommendedWatcher,
+    subscribers: Subscribers,
+}
+
+/// Workspace watchers, one per root, shared by the features that follow
+/// changes in the workspace, e.g. the full-text index and quick open
+#[derive(Default)]
+pub struct WorkspaceWatchers {
+    watchers: Mutex<HashMap<PathBuf, SharedWatcher>>,
+}
+
+/// Sends each changed path to all subscribers, forgetting those that are gone.
+fn fan_out(subscribers: &Subscribers, paths: Vec<PathBuf>) {
+    let mut subscribers = subscribers.lock().unwrap();
+    for path in paths {
+        subscribers.retain(|_, sender| sender.send(path.clone()).is_ok());
+    }
+}
+
+/// Starts receiving the paths changed under `root` as `name`.
+///
+/// The workspace is watched once, however many features subscribe. The
+/// receiver disconnects once `name` unsubscribes.
+pub fn subscribe(
+    app: &AppHandle,
+    root: &Path,
+    name: &'static str,
+) -> Result<mpsc::Receiver<PathBuf>, String> {
+    let state = app.state::<WorkspaceWatchers>();
+    let mut watchers = state.watchers.lock().unwrap();
+    if !watchers.contains_key(root) {
+        let subscribers = Subscribers::default();
+        let targets = subscribers.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    fan_out(&targets, event.paths);
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|e| e.to_string())?;
+        watchers.insert(
+            root.to_path_buf(),
+            SharedWatcher {
+                _watcher: watcher,
+                subscribers,
+            },
+        );
+    }
+
+    let (sender, changes) = mpsc::channel();
+    watchers[root]
+        .subscribers
+        .lock()
+        .unwrap()
+        .insert(name, sender);
+    Ok(changes)
+}
+
+/// Waits for the next batch of changed paths, or `None` once the receiver
+/// is disconnected.
+///
+/// A batch ends after `debounce` without changes, but no later than
+/// `MAX_BATCH_WAIT` after its first change or at `MAX_BATCH_PATHS` paths,
+/// so a steady stream of changes is still applied.
+pub fn next_batch(changes: &mpsc::Receiver<PathBuf>, debounce: Duration) -> Option<Vec<PathBuf>> {
+    collect_batch(changes, debounce, MAX_BATCH_WAIT, MAX_BATCH_PATHS)
+}
+
+fn collect_batch(
+    changes: &mpsc::Receiver<PathBuf>,
+    debounce: Duration,
+    max_wait: Duration,
+    max_paths: usize,
+) -> Option<Vec<PathBuf>> {
+    let first = changes.recv().ok()?;
+    let deadline = Instant::now() + max_wait;
+    let mut paths = HashSet::from([first]);
+    while paths.len() < max_paths {
+        let timeout = debounce.min(deadline.saturating_duration_since(Instant::now()));
+        match changes.recv_timeout(timeout) {
+            Ok(path) => {
+                paths.insert(path);
+            }
+            Err(_) => break,
+        }
+    }
+    Some(paths.into_iter().collect())
+}
+
+/// Stops sending changes under `root` to `name`, and stops watching the
+/// workspace once nobody is subscribed.
+pub fn unsubscribe(app: &AppHandle, root: &Path, name: &'static str) {
+    let state = app.state::<WorkspaceWatchers>();
+    let mut watchers = state.watchers.lock().unwrap();
+    let unused = watchers.get(root).is_some_and(|shared| {
+        let mut subscribers = shared.subscribers.lock().unwrap();
+        subscribers.remove(name);
+        subscribers.is_empty()
+    });
+    if unused {
+        watchers.remove(root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fan_out_reaches_every_subscriber() {
+        let subscribers = Subscribers::default();
+        let (index_sender, index) = mpsc::channel();
+        let (quick_open_sender, quick_open) = mpsc::channel();
+        subscribers.lock().unwrap().insert("index", index_sender);
+        subscribers
+            .lock()
+            .unwrap()
+            .insert("quick_open", quick_open_sender);
+
+        fan_out(&subscribers, vec![PathBuf::from("/ws/a.md")]);
+        assert_eq!(index.try_recv().unwrap(), PathBuf::from("/ws/a.md"));
+        assert_eq!(quick_open.try_recv().unwrap(), PathBuf::from("/ws/a.md"));
+
+        // A subscriber that went away is dropped
+        drop(quick_open);
+        fan_out(&subscribers, vec![PathBuf::from("/ws/b.md")]);
+        assert_eq!(index.try_recv().unwrap(), PathBuf::from("/ws/b.md"));
+        assert_eq!(subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_collect_batch_merges_duplicates() {
+        let (sender, changes) = mpsc::channel();
+        for name in ["a.md", "b.md", "a.md"] {
+            sender.send(PathBuf::from(name)).unwrap();
+        }
+        let mut batch = collect_batch(
+            &changes,
+            Duration::from_millis(10),
+            Duration::from_secs(1),
+            100,
+        )
+        .unwrap();
+        batch.sort();
+        assert_eq!(batch, vec![PathBuf::from("a.md"), PathBuf::from("b.md")]);
+
+        drop(sender);
+        assert!(collect_batch(&changes, Duration::ZERO, Duration::ZERO, 100).is_none());
+    }
+
+    #[test]
+    fn test_collect_batch_is_capped() {
+        let (sender, changes) = mpsc::channel();
+        for i in 0..5 {
+            sender.send(PathBuf::from(format!("{i}.md"))).unwrap();
+        }
+        let batch = collect_batch(&changes, Duration::from_secs(1), Duration::from_secs(1), 3);
+        assert_eq!(batch.unwrap().len(), 3);
+
+        // A steady stream of changes ends the batch at the deadline
+        let writer = std::thread::spawn(move || {
+            for i in 0..50 {
+                if sender.send(PathBuf::from(format!("{i}.md"))).is_err() {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+        let started = Instant::now();
+        collect_batch(
+            &changes,
+            Duration::from_millis(100),
+            Duration::from_millis(100),
+            1000,
+        )
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_millis(400));
+        drop(changes);
+        writer.join().unwrap();
+    }
+}
@@ -1,35 +1,76 @@
 import { useEffect } from "react";
-import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 
 import "./App.css";
-import { Toolbar, Sidebar, MarkdownEditor } from "./components";
+import { Toolbar, FindBar, Sidebar, MarkdownEditor } from "./components";
 import { useEditorStore, useRecentFilesStore } from "./stores";
+import { useKeyboardShortcuts } from "./hooks";
 
 function App() {
   const setupFileWatcher = useEditorStore((state) => state.setupFileWatcher);
   const loadRecentFiles = useRecentFilesStore((state) => state.loadRecentFiles);
-  const openFileByPath = useEditorStore((state) => state.openFileByPath);
   const openStartupFiles = useEditorStore((state) => state.openStartupFiles);
+  const openWindowDocument = useEditorStore(
+    (state) => state.openWindowDocument,
+  );
+  const recoverDrafts = useEditorStore((state) => state.recoverDrafts);
+  const zoom = useEditorStore((state) => state.zoom);
+  const setupUnsavedChangesHandler = useEditorStore(
+    (state) => state.setupUnsavedChangesHandler,
+  );
+  const setupMenuCommandHandler = useEditorStore(
+    (state) => state.setupMenuCommandHandler,
+  );
+  const setupSearchHandler = useEditorStore(
+    (state) => state.setupSearchHandler,
+  );
 
   useEffect(() => {
     setupFileWatcher();
     loadRecentFiles();
+    openWindowDocument();
     openStartupFiles();
 
-    const unlisten = listen("add_to_startup_files", (event) => {
-      // why not work?
-      console.log("[App] Adding file to startup:", event.payload);
-      openStartupFiles();
-    });
+    // Requests are queued for this window; events only announce new ones
+    const currentWindow = getCurrentWebviewWindow();
+    const unlisteners = [
+      "add_to_startup_files",
+      "navigate-requested",
+      "workspace-open-requested",
+      "new-document-requested",
+    ].map((event) => currentWindow.listen(event, () => openStartupFiles()));
 
     return () => {
-      unlisten.then((unsub) => unsub());
+      unlisteners.forEach((unlisten) => unlisten.then((unsub) => unsub()));
     };
-  }, [setupFileWatcher, loadRecentFiles, openFileByPath]);
+  }, [
+    setupFileWatcher,
+    loadRecentFiles,
+    openStartupFiles,
+    openWindowDocument,
+  ]);
+
+  // Drafts of a previous session are offered once, in the main window
+  useEffect(() => {
+    if (getCurrentWebviewWindow().label === "main") {
+      recoverDrafts();
+    }
+  }, [recoverDrafts]);
+
+  useEffect(() => {
+    getCurrentWebview().setZoom(zoom).catch(console.error);
+  }, [zoom]);
+
+  useEffect(() => setupUnsavedChangesHandler(), [setupUnsavedChangesHandler]);
+  useEffect(() => setupMenuCommandHandler(), [setupMenuCommandHandler]);
+  useEffect(() => setupSearchHandler(), [setupSearchHandler]);
+  useKeyboardShortcuts();
 
   return (
     <div className="app">
       <Toolbar />
+      <FindBar />
       <div className="app-body">
         <Sidebar />
         <MarkdownEditor />
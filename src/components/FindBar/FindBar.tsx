@@ -0,0 +1,115 @@
+import React, { useCallback, useEffect, useRef, useState } from "react";
+import { useEditorStore } from "../../stores";
+import "./FindBar.css";
+
+export const FindBar: React.FC = () => {
+  const {
+    findBar,
+    findQuery,
+    setFindQuery,
+    findNext,
+    findPrevious,
+    replaceNext,
+    replaceAll,
+    closeFind,
+  } = useEditorStore();
+  const [replacement, setReplacement] = useState("");
+  const queryRef = useRef<HTMLInputElement>(null);
+
+  // Focus the query whenever the bar is opened or switches mode
+  useEffect(() => {
+    queryRef.current?.focus();
+    queryRef.current?.select();
+  }, [findBar]);
+
+  const handleQueryKeyDown = useCallback(
+    (e: React.KeyboardEvent<HTMLInputElement>) => {
+      if (e.key === "Enter") {
+        e.preventDefault();
+        if (e.shiftKey) {
+          findPrevious();
+        } else {
+          findNext();
+        }
+      } else if (e.key === "Escape") {
+        closeFind();
+      }
+    },
+    [findNext, findPrevious, closeFind],
+  );
+
+  const handleReplacementKeyDown = useCallback(
+    (e: React.KeyboardEvent<HTMLInputElement>) => {
+      if (e.key === "Enter") {
+        e.preventDefault();
+        replaceNext(replacement);
+      } else if (e.key === "Escape") {
+        closeFind();
+      }
+    },
+    [replaceNext, replacement, closeFind],
+  );
+
+  if (!findBar) return null;
+
+  return (
+    <div className="find-bar" role="search">
+      <div className="find-bar-row">
+        <input
+          ref={queryRef}
+          className="find-bar-input"
+          value={findQuery}
+          onChange={(e) => setFindQuery(e.target.value)}
+          onKeyDown={handleQueryKeyDown}
+          placeholder="Find"
+          aria-label="Find"
+        />
+        <button
+          className="find-bar-btn"
+          onClick={() => findPrevious()}
+          title="Previous match"
+        >
+          ↑
+        </button>
+        <button
+          className="find-bar-btn"
+          onClick={() => findNext()}
+          title="Next match"
+        >
+          ↓
+        </button>
+        <button
+          className="find-bar-btn"
+          onClick={() => closeFind()}
+          title="Close"
+        >
+          ✕
+        </button>
+      </div>
+      {findBar === "replace" && (
+        <div className="find-bar-row">
+          <input
+            className="find-bar-input"
+            value={replacement}
+            onChange={(e) => setReplacement(e.target.value)}
+            onKeyDown={handleReplacementKeyDown}
+            placeholder="Replace"
+            aria-label="Replace"
+          />
+          <button
+            className="find-bar-btn"
+            onClick={() => replaceNext(replacement)}
+          >
+            Replace
+          </button>
+          <button
+            className="find-bar-btn"
+            onClick={() => replaceAll(replacement)}
+          >
+            Replace All
+          </button>
+        </div>
+      )}
+    </div>
+  );
+};
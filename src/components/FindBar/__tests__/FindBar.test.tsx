@@ -0,0 +1,69 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { render, screen } from "@testing-library/react";
+import userEvent from "@testing-library/user-event";
+import { FindBar } from "../FindBar";
+import { useEditorStore } from "../../../stores";
+
+vi.mock("../../../stores", () => ({
+  useEditorStore: vi.fn(),
+}));
+
+describe("FindBar", () => {
+  const actions = {
+    setFindQuery: vi.fn(),
+    findNext: vi.fn(),
+    findPrevious: vi.fn(),
+    replaceNext: vi.fn(),
+    replaceAll: vi.fn(),
+    closeFind: vi.fn(),
+  };
+
+  const mockStore = (findBar: "find" | "replace" | null) => {
+    (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
+      findBar,
+      findQuery: "needle",
+      ...actions,
+    });
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("should render nothing when closed", () => {
+    mockStore(null);
+    const { container } = render(<FindBar />);
+    expect(container).toBeEmptyDOMElement();
+  });
+
+  it("should find the next and previous match with Enter", async () => {
+    mockStore("find");
+    const user = userEvent.setup();
+    render(<FindBar />);
+
+    await user.type(screen.getByLabelText("Find"), "{Enter}");
+    expect(actions.findNext).toHaveBeenCalled();
+    await user.type(screen.getByLabelText("Find"), "{Shift>}{Enter}{/Shift}");
+    expect(actions.findPrevious).toHaveBeenCalled();
+  });
+
+  it("should only show the replace field in replace mode", () => {
+    mockStore("find");
+    const { rerender } = render(<FindBar />);
+    expect(screen.queryByLabelText("Replace")).not.toBeInTheDocument();
+
+    mockStore("replace");
+    rerender(<FindBar />);
+    expect(screen.getByLabelText("Replace")).toBeInTheDocument();
+  });
+
+  it("should replace all matches with the replacement", async () => {
+    mockStore("replace");
+    const user = userEvent.setup();
+    render(<FindBar />);
+
+    await user.type(screen.getByLabelText("Replace"), "pin");
+    await user.click(screen.getByText("Replace All"));
+    expect(actions.replaceAll).toHaveBeenCalledWith("pin");
+  });
+});
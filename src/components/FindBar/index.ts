@@ -0,0 +1 @@
+export { FindBar } from "./FindBar";
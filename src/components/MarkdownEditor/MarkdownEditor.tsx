@@ -1,12 +1,21 @@
-import React, { useCallback, useMemo, useState } from "react";
+import React, { useCallback, useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { useEditorStore } from "../../stores";
-import { parseMarkdown, sanitizeHtml } from "../../lib/markdown";
 import "./MarkdownEditor.css";
 import CopyButton from "../CopyButton";
 
 export const MarkdownEditor: React.FC = () => {
-  const { currentFile, setContent, viewMode, splitRatio, setSplitRatio } =
-    useEditorStore();
+  const {
+    currentFile,
+    setContent,
+    viewMode,
+    splitRatio,
+    setSplitRatio,
+    selection,
+    selectionRequest,
+    setSelection,
+  } = useEditorStore();
+  const textareaRef = useRef<HTMLTextAreaElement>(null);
   const [isResizing, setIsResizing] = useState(false);
   const [copyFeedback, setCopyFeedback] = useState<"editor" | "preview" | null>(
     null,
@@ -19,11 +28,55 @@ export const MarkdownEditor: React.FC = () => {
     [setContent],
   );
 
-  const previewHtml = useMemo(() => {
-    if (!currentFile) return "";
-    const rawHtml = parseMarkdown(currentFile.content);
-    return sanitizeHtml(rawHtml);
-  }, [currentFile]);
+  const handleSelect = useCallback(
+    (e: React.SyntheticEvent<HTMLTextAreaElement>) => {
+      const { selectionStart, selectionEnd } = e.currentTarget;
+      setSelection({ start: selectionStart, end: selectionEnd });
+    },
+    [setSelection],
+  );
+
+  // Follow selections made by the store, e.g. find results
+  useEffect(() => {
+    const textarea = textareaRef.current;
+    if (!textarea || !selectionRequest) return;
+    // Keep typing in the find bar while stepping through matches
+    if (!document.activeElement?.closest(".find-bar")) {
+      textarea.focus();
+    }
+    textarea.setSelectionRange(selection.start, selection.end);
+    const line =
+      textarea.value.slice(0, selection.start).split("\n").length - 1;
+    const lineHeight =
+      parseFloat(getComputedStyle(textarea).lineHeight) || 20;
+    textarea.scrollTop = Math.max(
+      0,
+      line * lineHeight - textarea.clientHeight / 2,
+    );
+    // Only a new request moves the selection, not the user's own changes
+  }, [selectionRequest]);
+
+  const [previewHtml, setPreviewHtml] = useState("");
+  const content = currentFile?.content;
+  const path = currentFile?.path;
+  const previewVisible = viewMode === "preview" || viewMode === "split";
+
+  // Rendered and sanitized by the backend, like exports and the command line
+  useEffect(() => {
+    if (content === undefined || !previewVisible) return;
+    let stale = false;
+    invoke<{ html: string } | undefined>("render_markdown", {
+      content,
+      path: path || null,
+    })
+      .then((rendered) => {
+        if (!stale && rendered) setPreviewHtml(rendered.html);
+      })
+      .catch(console.error);
+    return () => {
+      stale = true;
+    };
+  }, [content, path, previewVisible]);
 
   const handleResizeStart = useCallback(() => {
     setIsResizing(true);
@@ -107,9 +160,12 @@ export const MarkdownEditor: React.FC = () => {
             <span className="markdown-editor-pane-title">Editor</span>
           </div>
           <textarea
+            ref={textareaRef}
             className="markdown-editor-textarea"
             value={currentFile.content}
             onChange={handleChange}
+            onSelect={handleSelect}
+            readOnly={!!currentFile.readOnly}
             placeholder="Write your markdown here..."
             spellCheck={false}
           />
@@ -132,7 +188,7 @@ export const MarkdownEditor: React.FC = () => {
           </div>
           <div
             className="markdown-editor-content"
-            contentEditable
+            contentEditable={!currentFile.readOnly}
             suppressContentEditableWarning
             onInput={handlePreviewInput}
             dangerouslySetInnerHTML={{ __html: previewHtml }}
@@ -154,9 +210,12 @@ export const MarkdownEditor: React.FC = () => {
             />
           </div>
           <textarea
+            ref={textareaRef}
             className="markdown-editor-textarea"
             value={currentFile.content}
             onChange={handleChange}
+            onSelect={handleSelect}
+            readOnly={!!currentFile.readOnly}
             placeholder="Write your markdown here..."
             spellCheck={false}
           />
@@ -173,7 +232,7 @@ export const MarkdownEditor: React.FC = () => {
           </div>
           <div
             className="markdown-editor-content"
-            contentEditable
+            contentEditable={!currentFile.readOnly}
             suppressContentEditableWarning
             onInput={handlePreviewInput}
             dangerouslySetInnerHTML={{ __html: previewHtml }}
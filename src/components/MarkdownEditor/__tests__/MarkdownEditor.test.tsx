@@ -1,6 +1,7 @@
 import { describe, it, expect, vi } from "vitest";
 import { render, screen } from "@testing-library/react";
 import userEvent from "@testing-library/user-event";
+import { invoke } from "@tauri-apps/api/core";
 import { MarkdownEditor } from "../MarkdownEditor";
 import { useEditorStore } from "../../../stores";
 
@@ -55,6 +56,7 @@ describe("MarkdownEditor", () => {
       },
       viewMode: "edit",
       setContent: mockSetContent,
+      setSelection: vi.fn(),
     });
 
     const user = userEvent.setup();
@@ -67,7 +69,8 @@ describe("MarkdownEditor", () => {
     expect(mockSetContent).toHaveBeenCalled();
   });
 
-  it("should render preview in preview mode", () => {
+  it("should render preview in preview mode", async () => {
+    vi.mocked(invoke).mockResolvedValueOnce({ html: "<h1>Hello World</h1>" });
     (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
       currentFile: {
         id: "1",
@@ -80,10 +83,15 @@ describe("MarkdownEditor", () => {
     });
 
     render(<MarkdownEditor />);
-    expect(screen.getByText("Hello World")).toBeInTheDocument();
+    expect(await screen.findByText("Hello World")).toBeInTheDocument();
+    expect(invoke).toHaveBeenCalledWith("render_markdown", {
+      content: "# Hello World",
+      path: "/test.md",
+    });
   });
 
-  it("should render both editor and preview in split mode", () => {
+  it("should render both editor and preview in split mode", async () => {
+    vi.mocked(invoke).mockResolvedValueOnce({ html: "<h1>Hello World</h1>" });
     (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
       currentFile: {
         id: "1",
@@ -97,7 +105,7 @@ describe("MarkdownEditor", () => {
 
     render(<MarkdownEditor />);
     expect(screen.getByPlaceholderText("Write your markdown here...")).toBeInTheDocument();
-    expect(screen.getByText("Hello World")).toBeInTheDocument();
+    expect(await screen.findByText("Hello World")).toBeInTheDocument();
   });
 
   it("should have correct CSS class for split mode", () => {
@@ -0,0 +1,85 @@
+import React, { useEffect, useState } from "react";
+import { useEditorStore } from "../../stores";
+import { getFileName } from "../../fs";
+import "./SearchPanel.css";
+
+export const SearchPanel: React.FC = () => {
+  const { search, searchWorkspace, clearSearch, navigateTo } =
+    useEditorStore();
+  const [query, setQuery] = useState("");
+
+  // Show the query of searches started elsewhere, e.g. from a link
+  useEffect(() => {
+    if (search) setQuery(search.query);
+  }, [search?.query]);
+
+  if (!search) return null;
+
+  const matchCount = search.results.reduce(
+    (count, file) => count + file.matches.length,
+    0,
+  );
+
+  return (
+    <section className="search-panel" aria-label="Search">
+      <div className="search-panel-header">
+        <input
+          className="search-panel-input"
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          onKeyDown={(e) => {
+            if (e.key === "Enter" && query) {
+              searchWorkspace(query, search.root);
+            } else if (e.key === "Escape") {
+              clearSearch();
+            }
+          }}
+          placeholder="Search"
+          aria-label="Search"
+        />
+        <button
+          className="search-panel-btn"
+          onClick={() => clearSearch()}
+          title="Close search"
+        >
+          ✕
+        </button>
+      </div>
+      <div className="search-panel-summary" title={search.root}>
+        {search.finished
+          ? `${matchCount}${search.truncated ? "+" : ""} matches in ` +
+            `${search.results.length} files`
+          : "Searching…"}
+      </div>
+      <ul className="search-panel-results">
+        {search.results.map((file) => (
+          <li key={file.path}>
+            <div className="search-panel-file" title={file.path}>
+              {getFileName(file.path)}
+            </div>
+            <ul>
+              {file.matches.map((match) => (
+                <li
+                  key={match.line}
+                  className="search-panel-match"
+                  onClick={() =>
+                    navigateTo({
+                      path: file.path,
+                      line: match.line,
+                      column: match.column,
+                      heading: null,
+                      readonly: false,
+                    })
+                  }
+                >
+                  <span className="search-panel-line">{match.line}</span>
+                  <span>{match.text.trim()}</span>
+                </li>
+              ))}
+            </ul>
+          </li>
+        ))}
+      </ul>
+    </section>
+  );
+};
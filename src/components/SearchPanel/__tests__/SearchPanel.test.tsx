@@ -0,0 +1,81 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { render, screen } from "@testing-library/react";
+import userEvent from "@testing-library/user-event";
+import { SearchPanel } from "../SearchPanel";
+import { useEditorStore } from "../../../stores";
+
+vi.mock("../../../stores", () => ({
+  useEditorStore: vi.fn(),
+}));
+
+describe("SearchPanel", () => {
+  const actions = {
+    searchWorkspace: vi.fn(),
+    clearSearch: vi.fn(),
+    navigateTo: vi.fn(),
+  };
+
+  const search = {
+    query: "todo",
+    root: "/notes",
+    id: 1,
+    results: [
+      {
+        search_id: 1,
+        path: "/notes/a.md",
+        matches: [
+          {
+            line: 3,
+            column: 5,
+            text: "- [ ] todo",
+            ranges: [[6, 10]],
+            before: [],
+            after: [],
+          },
+        ],
+      },
+    ],
+    finished: true,
+    truncated: false,
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("should render nothing without a search", () => {
+    (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
+      search: null,
+      ...actions,
+    });
+    const { container } = render(<SearchPanel />);
+    expect(container).toBeEmptyDOMElement();
+  });
+
+  it("should list the results and open a match", async () => {
+    (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
+      search,
+      ...actions,
+    });
+    render(<SearchPanel />);
+    expect(screen.getByText("1 matches in 1 files")).toBeInTheDocument();
+    expect(screen.getByText("a.md")).toBeInTheDocument();
+
+    await userEvent.click(screen.getByText("- [ ] todo"));
+    expect(actions.navigateTo).toHaveBeenCalledWith(
+      expect.objectContaining({ path: "/notes/a.md", line: 3, column: 5 }),
+    );
+  });
+
+  it("should search again with Enter", async () => {
+    (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
+      search,
+      ...actions,
+    });
+    render(<SearchPanel />);
+    const input = screen.getByLabelText("Search");
+    await userEvent.clear(input);
+    await userEvent.type(input, "done{Enter}");
+    expect(actions.searchWorkspace).toHaveBeenCalledWith("done", "/notes");
+  });
+});
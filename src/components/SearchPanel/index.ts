@@ -0,0 +1 @@
+export { SearchPanel } from "./SearchPanel";
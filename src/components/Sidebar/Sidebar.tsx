@@ -1,5 +1,7 @@
 import React, { useCallback, useState } from "react";
 import { useEditorStore, useRecentFilesStore } from "../../stores";
+import { SearchPanel } from "../SearchPanel";
+import { getFileName } from "../../fs";
 import "./Sidebar.css";
 
 export const Sidebar: React.FC = () => {
@@ -13,6 +15,9 @@ export const Sidebar: React.FC = () => {
     sidebarCollapsed,
     toggleSidebar,
     setSidebarWidth,
+    workspace,
+    workspaceFiles = [],
+    closeWorkspace,
   } = useEditorStore();
   const { recentFiles, clearRecentFiles } = useRecentFilesStore();
   const [showRecentMenu, setShowRecentMenu] = useState(false);
@@ -42,6 +47,10 @@ export const Sidebar: React.FC = () => {
     setShowRecentMenu(false);
   }, []);
 
+  const handleOpenWorkspaceFile = useCallback(async (path: string) => {
+    await useEditorStore.getState().openFileByPath(path);
+  }, []);
+
   const handleClearRecent = useCallback(async () => {
     await clearRecentFiles();
     setShowRecentMenu(false);
@@ -185,7 +194,38 @@ export const Sidebar: React.FC = () => {
               ))}
             </ul>
           )}
+          {workspace && (
+            <div className="workspace">
+              <div className="workspace-header" title={workspace}>
+                <span className="workspace-name">
+                  {getFileName(workspace.replace(/[\\/]+$/, "")) || workspace}
+                </span>
+                <button
+                  className="file-close-btn"
+                  onClick={closeWorkspace}
+                  title="Close folder"
+                >
+                  ×
+                </button>
+              </div>
+              <ul className="file-list">
+                {workspaceFiles.map((path) => (
+                  <li
+                    key={path}
+                    className={`file-item ${currentFile?.path === path ? "file-item-active" : ""}`}
+                    onClick={() => handleOpenWorkspaceFile(path)}
+                    title={path}
+                  >
+                    <span className="file-name">
+                      {path.slice(workspace.length).replace(/^[\\/]/, "")}
+                    </span>
+                  </li>
+                ))}
+              </ul>
+            </div>
+          )}
         </div>
+        <SearchPanel />
         <div
           className="sidebar-resize-handle"
           onMouseDown={handleResizeStart}
@@ -165,4 +165,19 @@ describe("Sidebar", () => {
     expect(mockSetCurrentFile).not.toHaveBeenCalled();
     expect(mockRemoveFile).toHaveBeenCalledWith(mockFiles[0].id);
   });
+
+  it("should list the files of the open workspace", () => {
+    (useEditorStore as unknown as ReturnType<typeof vi.fn>).mockReturnValue({
+      files: [],
+      currentFile: null,
+      workspace: "/notes",
+      workspaceFiles: ["/notes/a.md", "/notes/guides/b.md"],
+      closeWorkspace: vi.fn(),
+    });
+
+    render(<Sidebar />);
+    expect(screen.getByText("notes")).toBeInTheDocument();
+    expect(screen.getByText("a.md")).toBeInTheDocument();
+    expect(screen.getByText("guides/b.md")).toBeInTheDocument();
+  });
 });
@@ -10,7 +10,6 @@ export const Toolbar: React.FC = () => {
     setViewMode,
     saveFile,
     saveFileAs,
-    openFile,
     reloadFile,
   } = useEditorStore();
 
@@ -26,10 +25,6 @@ export const Toolbar: React.FC = () => {
     await saveFileAs();
   }, [saveFileAs]);
 
-  const handleOpen = useCallback(async () => {
-    await openFile();
-  }, [openFile]);
-
   const handleReload = useCallback(async () => {
     await reloadFile();
   }, [reloadFile]);
@@ -41,45 +36,6 @@ export const Toolbar: React.FC = () => {
     [setViewMode]
   );
 
-  const handleToggleFullscreen = useCallback(() => {
-    // Use Tauri window API for fullscreen
-    import("@tauri-apps/api/webviewWindow").then(({ WebviewWindow }) => {
-      const window = WebviewWindow.getCurrent();
-      window.isFullscreen().then((isFullscreen) => {
-        window.setFullscreen(!isFullscreen);
-      });
-    });
-  }, []);
-
-  // Keyboard shortcuts
-  useEffect(() => {
-    const handleKeyDown = (e: KeyboardEvent) => {
-      const isMac = navigator.platform.toUpperCase().indexOf("MAC") >= 0;
-      const modKey = isMac ? e.metaKey : e.ctrlKey;
-
-      if (modKey && e.key === "o") {
-        e.preventDefault();
-        handleOpen();
-      } else if (modKey && e.key === "s") {
-        e.preventDefault();
-        if (e.shiftKey) {
-          handleSaveAs();
-        } else {
-          handleSave();
-        }
-      } else if (modKey && e.key === "r") {
-        e.preventDefault();
-        handleReload();
-      } else if (modKey && e.key === "f") {
-        e.preventDefault();
-        handleToggleFullscreen();
-      }
-    };
-
-    window.addEventListener("keydown", handleKeyDown);
-    return () => window.removeEventListener("keydown", handleKeyDown);
-  }, [handleOpen, handleSave, handleSaveAs, handleReload, handleToggleFullscreen]);
-
   return (
     <header className="toolbar">
       <div className="toolbar-section">
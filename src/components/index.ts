@@ -1,3 +1,5 @@
+export { FindBar } from "./FindBar";
 export { MarkdownEditor } from "./MarkdownEditor";
+export { SearchPanel } from "./SearchPanel";
 export { Sidebar } from "./Sidebar";
 export { Toolbar } from "./Toolbar";
@@ -1,5 +1,13 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { readFile, writeFile, fileExists, checkAccess, listDir } from "../operations";
+import {
+  readFile,
+  writeFile,
+  fileExists,
+  checkAccess,
+  listDir,
+  isMarkdownFile,
+  getParentDir,
+} from "../operations";
 import { invoke } from "@tauri-apps/api/core";
 
 vi.mock("@tauri-apps/api/core", () => ({
@@ -201,4 +209,29 @@ describe("fs/operations", () => {
       expect(result.error).toBe("Directory not found");
     });
   });
+
+  describe("isMarkdownFile", () => {
+    it("should accept the default extensions in any case", () => {
+      expect(isMarkdownFile("/notes/README.md")).toBe(true);
+      expect(isMarkdownFile("/notes/Guide.MARKDOWN")).toBe(true);
+      expect(isMarkdownFile("/notes/readme.txt")).toBe(false);
+    });
+
+    it("should use the given extensions", () => {
+      expect(isMarkdownFile("/notes/page.mdx", ["md", "mdx"])).toBe(true);
+      expect(isMarkdownFile("/notes/page.markdown", ["md", "mdx"])).toBe(false);
+    });
+  });
+
+  describe("getParentDir", () => {
+    it("should return the folder of a path", () => {
+      expect(getParentDir("/notes/a.md")).toBe("/notes");
+      expect(getParentDir("C:\\notes\\a.md")).toBe("C:\\notes");
+      expect(getParentDir("/a.md")).toBe("/");
+    });
+
+    it("should return null for a bare name", () => {
+      expect(getParentDir("a.md")).toBeNull();
+    });
+  });
 });
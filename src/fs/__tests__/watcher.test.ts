@@ -6,7 +6,6 @@ import {
   isWatching,
   getActiveWatchersCount,
   setupGlobalFileChangeListener,
-  setupReloadFileListener,
   setupFileOpenListener,
 } from "../watcher";
 
@@ -123,30 +122,6 @@ describe("fs/watcher", () => {
     });
   });
 
-  describe("setupReloadFileListener", () => {
-    it("should set up reload file listener", async () => {
-      const onReload = vi.fn();
-
-      (listen as any).mockImplementation((event: string, cb: any) => {
-        if (event === "reload-file") {
-          setTimeout(() => cb({}), 0);
-        }
-        return Promise.resolve(vi.fn());
-      });
-
-      const cleanup = setupReloadFileListener(onReload) as (() => void);
-
-      expect(listen).toHaveBeenCalledWith("reload-file", expect.any(Function));
-
-      // Wait for async callback
-      await new Promise(resolve => setTimeout(resolve, 10));
-
-      expect(onReload).toHaveBeenCalled();
-
-      cleanup();
-    });
-  });
-
   describe("setupFileOpenListener", () => {
     it("should set up file open listener", async () => {
       const onFileOpen = vi.fn();
@@ -30,6 +30,8 @@ export {
   getFileExtension,
   isMarkdownFile,
   getFileName,
+  getParentDir,
+  MARKDOWN_EXTENSIONS,
 } from "./operations";
 
 // Watcher
@@ -40,6 +42,5 @@ export {
   isWatching,
   getActiveWatchersCount,
   setupGlobalFileChangeListener,
-  setupReloadFileListener,
   setupFileOpenListener,
 } from "./watcher";
@@ -73,12 +73,20 @@ export function getFileExtension(path: string): string {
   return ext ? ext.toLowerCase() : "";
 }
 
+/**
+ * Default Markdown file extensions, as in the backend settings
+ */
+export const MARKDOWN_EXTENSIONS = ["md", "markdown", "mdown", "mkd", "mkdn"];
+
 /**
  * Check if file is a markdown file
  */
-export function isMarkdownFile(path: string): boolean {
+export function isMarkdownFile(
+  path: string,
+  extensions: string[] = MARKDOWN_EXTENSIONS,
+): boolean {
   const ext = getFileExtension(path);
-  return ["md", "markdown", "mdown", "mkd", "mkdn"].includes(ext);
+  return extensions.some((known) => known.toLowerCase() === ext);
 }
 
 /**
@@ -87,3 +95,12 @@ export function isMarkdownFile(path: string): boolean {
 export function getFileName(path: string): string {
   return path.split("/").pop() || path.split("\\").pop() || path;
 }
+
+/**
+ * Get the folder containing a path, or null for a bare name
+ */
+export function getParentDir(path: string): string | null {
+  const index = Math.max(path.lastIndexOf("/"), path.lastIndexOf("\\"));
+  if (index < 0) return null;
+  return index === 0 ? path.slice(0, 1) : path.slice(0, index);
+}
@@ -25,12 +25,16 @@ export interface ReadFileResult {
   path: string;
   name: string;
   content: string;
+  hash?: string;
   success: boolean;
   error?: string;
 }
 
 export interface WriteFileResult {
   path: string;
+  hash?: string;
+  /** Content that was written, when it differs from the content sent */
+  content?: string | null;
   success: boolean;
   error?: string;
 }
@@ -109,25 +109,6 @@ export function setupGlobalFileChangeListener(
   };
 }
 
-/**
- * Set up reload-file event listener (from menu command)
- */
-export function setupReloadFileListener(onReload: () => void): () => void {
-  let unlisten: UnlistenFn | null = null;
-
-  listen("reload-file", () => {
-    onReload();
-  }).then((fn) => {
-    unlisten = fn;
-  });
-
-  return () => {
-    if (unlisten) {
-      unlisten();
-    }
-  };
-}
-
 /**
  * Set up file-open-requested event listener (from OS)
  */
@@ -1,44 +1,64 @@
-import { useEffect, useCallback } from "react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEditorStore } from "../stores";
+import {
+  parseAccelerator,
+  matchesShortcut,
+  type Shortcut,
+} from "../lib/keybindings";
+import type { KeybindingRegistry } from "../types";
 
-interface UseKeyboardShortcutsOptions {
-  onSave?: () => void;
-  onSaveAs?: () => void;
+interface Binding {
+  command: string;
+  shortcut: Shortcut;
 }
 
-export function useKeyboardShortcuts(options: UseKeyboardShortcutsOptions = {}) {
-  const { onSave, onSaveAs } = options;
-  const { saveFile, saveFileAs } = useEditorStore();
+/**
+ * Build the shortcut table from the backend's key bindings
+ */
+function toBindings(registry: KeybindingRegistry): Binding[] {
+  const isMac = navigator.platform.toUpperCase().indexOf("MAC") >= 0;
+  return registry.bindings.flatMap(({ command, accelerator }) => {
+    const shortcut = accelerator ? parseAccelerator(accelerator, isMac) : null;
+    return shortcut ? [{ command, shortcut }] : [];
+  });
+}
+
+/**
+ * Run commands for the key bindings from `keybindings.json`, following
+ * changes to the file
+ */
+export function useKeyboardShortcuts() {
+  const runCommand = useEditorStore((state) => state.runCommand);
+  const [bindings, setBindings] = useState<Binding[]>([]);
+
+  useEffect(() => {
+    invoke<KeybindingRegistry>("get_keybindings")
+      .then((registry) => setBindings(toBindings(registry)))
+      .catch(console.error);
+
+    const unlisten = listen<KeybindingRegistry>(
+      "keybindings-changed",
+      (event) => setBindings(toBindings(event.payload)),
+    );
+    return () => {
+      unlisten.then((unsub) => unsub());
+    };
+  }, []);
 
-  const handleKeyDown = useCallback(
-    (e: KeyboardEvent) => {
-      // Check for Ctrl/Cmd + S (Save)
-      if ((e.ctrlKey || e.metaKey) && e.key === "s") {
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      const binding = bindings.find((b) => matchesShortcut(e, b.shortcut));
+      if (binding) {
         e.preventDefault();
-        if (e.shiftKey) {
-          // Ctrl/Cmd + Shift + S (Save As)
-          if (onSaveAs) {
-            onSaveAs();
-          } else {
-            saveFileAs();
-          }
-        } else {
-          // Ctrl/Cmd + S (Save)
-          if (onSave) {
-            onSave();
-          } else {
-            saveFile();
-          }
-        }
+        runCommand(binding.command);
       }
-    },
-    [saveFile, saveFileAs, onSave, onSaveAs]
-  );
+    };
 
-  useEffect(() => {
     window.addEventListener("keydown", handleKeyDown);
     return () => {
       window.removeEventListener("keydown", handleKeyDown);
     };
-  }, [handleKeyDown]);
+  }, [bindings, runCommand]);
 }
@@ -0,0 +1,77 @@
+import { describe, it, expect } from "vitest";
+import {
+  findInText,
+  replaceAllInText,
+  offsetOfLine,
+  findHeadingLine,
+} from "../find";
+
+describe("findInText", () => {
+  const text = "Foo bar foo BAR";
+
+  it("should find the next match ignoring case", () => {
+    expect(findInText(text, "foo", 1)).toEqual({ start: 8, end: 11 });
+    expect(findInText(text, "bar", 0)).toEqual({ start: 4, end: 7 });
+  });
+
+  it("should find the previous match", () => {
+    expect(findInText(text, "foo", 8, true)).toEqual({ start: 0, end: 3 });
+  });
+
+  it("should wrap around the document", () => {
+    expect(findInText(text, "foo", 9)).toEqual({ start: 0, end: 3 });
+    expect(findInText(text, "foo", 0, true)).toEqual({ start: 8, end: 11 });
+  });
+
+  it("should match special characters literally", () => {
+    expect(findInText("a.b axb", "x", 0)).toEqual({ start: 5, end: 6 });
+    expect(findInText("see [1]", "[1]", 0)).toEqual({ start: 4, end: 7 });
+  });
+
+  it("should return null without a match or query", () => {
+    expect(findInText(text, "baz", 0)).toBeNull();
+    expect(findInText(text, "", 0)).toBeNull();
+  });
+});
+
+describe("replaceAllInText", () => {
+  it("should replace every match and count them", () => {
+    expect(replaceAllInText("a.b A.B axb", "a.b", "c")).toEqual({
+      text: "c c axb",
+      count: 2,
+    });
+  });
+
+  it("should insert replacements literally", () => {
+    expect(replaceAllInText("cost", "cost", "$&$1").text).toBe("$&$1");
+  });
+});
+
+describe("offsetOfLine", () => {
+  const text = "first\nsecond\nthird";
+
+  it("should return the offset of a line and column", () => {
+    expect(offsetOfLine(text, 1)).toBe(0);
+    expect(offsetOfLine(text, 2)).toBe(6);
+    expect(offsetOfLine(text, 3, 3)).toBe(15);
+  });
+
+  it("should clamp to the document", () => {
+    expect(offsetOfLine(text, 2, 99)).toBe(12);
+    expect(offsetOfLine(text, 99)).toBe(text.length);
+  });
+});
+
+describe("findHeadingLine", () => {
+  const text = "intro\n# Title\n\n## Install ##\ntext";
+
+  it("should return the line of a heading ignoring case", () => {
+    expect(findHeadingLine(text, "title")).toBe(2);
+    expect(findHeadingLine(text, " Install ")).toBe(4);
+  });
+
+  it("should return null for text that is not a heading", () => {
+    expect(findHeadingLine(text, "intro")).toBeNull();
+    expect(findHeadingLine(text, "Usage")).toBeNull();
+  });
+});
@@ -0,0 +1,70 @@
+import { describe, it, expect } from "vitest";
+import { parseAccelerator, matchesShortcut } from "../keybindings";
+
+function keyEvent(init: KeyboardEventInit): KeyboardEvent {
+  return new KeyboardEvent("keydown", init);
+}
+
+describe("parseAccelerator", () => {
+  it("should resolve CmdOrCtrl for the platform", () => {
+    expect(parseAccelerator("CmdOrCtrl+Shift+S", false)).toEqual({
+      ctrl: true,
+      alt: false,
+      shift: true,
+      meta: false,
+      key: "S",
+    });
+    expect(parseAccelerator("CmdOrCtrl+S", true)?.meta).toBe(true);
+  });
+
+  it("should parse named and punctuation keys", () => {
+    expect(parseAccelerator("F11", false)?.key).toBe("F11");
+    expect(parseAccelerator("Ctrl++", false)?.key).toBe("+");
+    expect(parseAccelerator("Ctrl+Esc", false)?.key).toBe("ESCAPE");
+    expect(parseAccelerator("CmdOrCtrl+\\", false)?.key).toBe("\\");
+  });
+
+  it("should reject unknown modifiers", () => {
+    expect(parseAccelerator("Hyper+K", false)).toBeNull();
+  });
+});
+
+describe("matchesShortcut", () => {
+  it("should require the exact modifiers", () => {
+    const save = parseAccelerator("CmdOrCtrl+S", false)!;
+    expect(
+      matchesShortcut(keyEvent({ key: "s", code: "KeyS", ctrlKey: true }), save),
+    ).toBe(true);
+    expect(
+      matchesShortcut(
+        keyEvent({ key: "S", code: "KeyS", ctrlKey: true, shiftKey: true }),
+        save,
+      ),
+    ).toBe(false);
+  });
+
+  it("should match letters by position", () => {
+    const replace = parseAccelerator("Cmd+Alt+F", true)!;
+    expect(
+      matchesShortcut(
+        keyEvent({ key: "ƒ", code: "KeyF", metaKey: true, altKey: true }),
+        replace,
+      ),
+    ).toBe(true);
+  });
+
+  it("should match other keys by name", () => {
+    expect(
+      matchesShortcut(
+        keyEvent({ key: "=", code: "Equal", ctrlKey: true }),
+        parseAccelerator("CmdOrCtrl+=", false)!,
+      ),
+    ).toBe(true);
+    expect(
+      matchesShortcut(
+        keyEvent({ key: "F11", code: "F11" }),
+        parseAccelerator("F11", false)!,
+      ),
+    ).toBe(true);
+  });
+});
@@ -0,0 +1,89 @@
+/**
+ * Range of text in a document, as textarea selection offsets
+ */
+export interface TextRange {
+  start: number;
+  end: number;
+}
+
+/**
+ * Case-insensitive pattern matching `query` literally
+ */
+function literalPattern(query: string): RegExp {
+  return new RegExp(query.replace(/[.*+?^${}()|[\]\\]/g, "\\$&"), "gi");
+}
+
+/**
+ * Find the next occurrence of `query` after `from`, or the previous one
+ * before it, wrapping around the document. Matching ignores case.
+ */
+export function findInText(
+  text: string,
+  query: string,
+  from: number,
+  backwards = false,
+): TextRange | null {
+  if (!query) return null;
+  const pattern = literalPattern(query);
+  const matches: TextRange[] = [];
+  for (let m = pattern.exec(text); m; m = pattern.exec(text)) {
+    matches.push({ start: m.index, end: m.index + m[0].length });
+  }
+  if (matches.length === 0) return null;
+
+  if (backwards) {
+    const before = matches.filter((m) => m.start < from);
+    return before.length > 0
+      ? before[before.length - 1]
+      : matches[matches.length - 1];
+  }
+  return matches.find((m) => m.start >= from) ?? matches[0];
+}
+
+/**
+ * Replace every occurrence of `query`, ignoring case
+ */
+export function replaceAllInText(
+  text: string,
+  query: string,
+  replacement: string,
+): { text: string; count: number } {
+  if (!query) return { text, count: 0 };
+  let count = 0;
+  const replaced = text.replace(literalPattern(query), () => {
+    count++;
+    return replacement;
+  });
+  return { text: replaced, count };
+}
+
+/**
+ * Offset of a 1-based line and column, clamped to the document
+ */
+export function offsetOfLine(text: string, line: number, column = 1): number {
+  let offset = 0;
+  for (let current = 1; current < line; current++) {
+    const next = text.indexOf("\n", offset);
+    if (next === -1) return text.length;
+    offset = next + 1;
+  }
+  const lineEnd = text.indexOf("\n", offset);
+  const lineLength = (lineEnd === -1 ? text.length : lineEnd) - offset;
+  return offset + Math.min(Math.max(column - 1, 0), lineLength);
+}
+
+/**
+ * 1-based line of the first ATX heading whose text matches `heading`,
+ * ignoring case, or null if there is none
+ */
+export function findHeadingLine(text: string, heading: string): number | null {
+  const wanted = heading.trim().toLowerCase();
+  const lines = text.split("\n");
+  for (let i = 0; i < lines.length; i++) {
+    const match = /^ {0,3}#{1,6}\s+(.*?)(?:\s+#+)?\s*$/.exec(lines[i]);
+    if (match && match[1].toLowerCase() === wanted) {
+      return i + 1;
+    }
+  }
+  return null;
+}
@@ -1 +1,3 @@
 export * from "./markdown";
+export * from "./find";
+export * from "./keybindings";
@@ -0,0 +1,100 @@
+/**
+ * Key combination parsed from a Tauri accelerator, e.g. "CmdOrCtrl+Shift+S"
+ */
+export interface Shortcut {
+  ctrl: boolean;
+  alt: boolean;
+  shift: boolean;
+  meta: boolean;
+  /** Upper-cased key, e.g. "S", "F11", "=" or "PAGEDOWN" */
+  key: string;
+}
+
+/** Accelerator key names that differ from `KeyboardEvent.key` */
+const KEY_ALIASES: Record<string, string> = {
+  ESC: "ESCAPE",
+  RETURN: "ENTER",
+  SPACE: " ",
+  PLUS: "+",
+  UP: "ARROWUP",
+  DOWN: "ARROWDOWN",
+  LEFT: "ARROWLEFT",
+  RIGHT: "ARROWRIGHT",
+};
+
+/**
+ * Parse an accelerator, resolving `CmdOrCtrl` for the platform.
+ * Returns null for modifiers this parser doesn't know.
+ */
+export function parseAccelerator(
+  accelerator: string,
+  isMac: boolean,
+): Shortcut | null {
+  // "Ctrl++" binds the plus key
+  const plus = accelerator.endsWith("++");
+  const parts = (plus ? accelerator.slice(0, -2) : accelerator).split("+");
+  const key = plus ? "+" : parts.pop();
+  if (!key) return null;
+
+  const shortcut: Shortcut = {
+    ctrl: false,
+    alt: false,
+    shift: false,
+    meta: false,
+    key: KEY_ALIASES[key.toUpperCase()] ?? key.toUpperCase(),
+  };
+  for (const modifier of parts) {
+    switch (modifier.trim().toUpperCase()) {
+      case "CTRL":
+      case "CONTROL":
+        shortcut.ctrl = true;
+        break;
+      case "ALT":
+      case "OPTION":
+        shortcut.alt = true;
+        break;
+      case "SHIFT":
+        shortcut.shift = true;
+        break;
+      case "CMD":
+      case "COMMAND":
+      case "SUPER":
+      case "META":
+        shortcut.meta = true;
+        break;
+      case "CMDORCTRL":
+      case "CMDORCONTROL":
+      case "COMMANDORCTRL":
+      case "COMMANDORCONTROL":
+        if (isMac) {
+          shortcut.meta = true;
+        } else {
+          shortcut.ctrl = true;
+        }
+        break;
+      default:
+        return null;
+    }
+  }
+  return shortcut;
+}
+
+/**
+ * Check whether a key event is the given shortcut
+ */
+export function matchesShortcut(e: KeyboardEvent, shortcut: Shortcut): boolean {
+  if (
+    e.ctrlKey !== shortcut.ctrl ||
+    e.altKey !== shortcut.alt ||
+    e.shiftKey !== shortcut.shift ||
+    e.metaKey !== shortcut.meta
+  ) {
+    return false;
+  }
+
+  // Letters and digits by position, as Alt and Shift change the character
+  const { key } = shortcut;
+  if (/^[A-Z]$/.test(key)) return e.code === `Key${key}`;
+  if (/^[0-9]$/.test(key)) return e.code === `Digit${key}`;
+  return e.key.toUpperCase() === key;
+}
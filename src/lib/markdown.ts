@@ -1,24 +1,3 @@
-import { marked } from "marked";
-
-/**
- * Parse markdown content to HTML
- */
-export function parseMarkdown(content: string): string {
-  return marked.parse(content) as string;
-}
-
-/**
- * Sanitize HTML to prevent XSS attacks
- */
-export function sanitizeHtml(html: string): string {
-  // Basic sanitization - in production, consider using DOMPurify
-  return html
-    .replace(/<script\b[^<]*(?:(?!<\/script>)<[^<]*)*<\/script>/gi, "")
-    .replace(/<iframe\b[^<]*(?:(?!<\/iframe>)<[^<]*)*<\/iframe>/gi, "")
-    .replace(/on\w+="[^"]*"/g, "")
-    .replace(/on\w+='[^']*'/g, "");
-}
-
 /**
  * Convert HTML to plain text
  */
@@ -1,4 +1,6 @@
-import { describe, it, expect, beforeEach, vi } from "vitest";
+import { describe, it, expect, beforeEach, afterEach, vi } from "vitest";
+import { invoke } from "@tauri-apps/api/core";
+import { ask, save } from "@tauri-apps/plugin-dialog";
 import { useEditorStore } from "../editorStore";
 import type { MarkdownFile } from "../../types";
 
@@ -21,7 +23,15 @@ describe("useEditorStore", () => {
       currentFile: null,
       files: [],
       isModified: false,
+      modifiedFiles: [],
+      findBar: null,
+      findQuery: "",
+      selection: { start: 0, end: 0 },
       viewMode: "split",
+      zoom: 1,
+      workspace: null,
+      workspaceFiles: [],
+      search: null,
     });
   });
 
@@ -71,6 +81,34 @@ describe("useEditorStore", () => {
     });
   });
 
+  describe("modifiedFiles", () => {
+    const otherFile: MarkdownFile = {
+      ...mockFile,
+      id: "other-id",
+      path: "/test/other.md",
+      name: "other.md",
+    };
+
+    it("should keep each file's modified state when switching", () => {
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Changed");
+      useEditorStore.getState().addFile(otherFile);
+      expect(useEditorStore.getState().isModified).toBe(false);
+
+      useEditorStore.getState().setCurrentFile(mockFile);
+      expect(useEditorStore.getState().isModified).toBe(true);
+      expect(useEditorStore.getState().modifiedFiles).toEqual([mockFile.id]);
+    });
+
+    it("should forget closed files", () => {
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Changed");
+      useEditorStore.getState().removeFile(mockFile.id);
+      expect(useEditorStore.getState().modifiedFiles).toEqual([]);
+      expect(useEditorStore.getState().isModified).toBe(false);
+    });
+  });
+
   describe("addFile", () => {
     it("should add file to files array", () => {
       useEditorStore.getState().addFile(mockFile);
@@ -152,4 +190,418 @@ describe("useEditorStore", () => {
       expect(useEditorStore.getState().isModified).toBe(false);
     });
   });
+
+  describe("saveFile", () => {
+    afterEach(() => {
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+    });
+
+    it("should adopt the content written by the backend", async () => {
+      vi.mocked(invoke).mockImplementation(async (command) =>
+        command === "write_file"
+          ? { path: mockFile.path, success: true, content: "# Regenerated" }
+          : undefined,
+      );
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Changed");
+
+      expect(await useEditorStore.getState().saveFile()).toBe(true);
+      const state = useEditorStore.getState();
+      expect(state.currentFile?.content).toBe("# Regenerated");
+      expect(state.files[0].content).toBe("# Regenerated");
+      expect(state.isModified).toBe(false);
+    });
+
+    it("should keep the content when the backend wrote it unchanged", async () => {
+      vi.mocked(invoke).mockImplementation(async (command) =>
+        command === "write_file"
+          ? { path: mockFile.path, success: true, content: null }
+          : undefined,
+      );
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Changed");
+
+      expect(await useEditorStore.getState().saveFile()).toBe(true);
+      expect(useEditorStore.getState().currentFile?.content).toBe("# Changed");
+    });
+  });
+
+  describe("drafts", () => {
+    afterEach(() => {
+      vi.useRealTimers();
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+      vi.mocked(ask).mockReset();
+    });
+
+    it("should journal a draft once typing pauses", () => {
+      vi.useFakeTimers();
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Draft");
+      useEditorStore.getState().setContent("# Draft 2");
+      expect(invoke).not.toHaveBeenCalledWith("save_draft", expect.anything());
+
+      vi.runAllTimers();
+      const calls = vi
+        .mocked(invoke)
+        .mock.calls.filter(([command]) => command === "save_draft");
+      expect(calls).toHaveLength(1);
+      expect(calls[0][1]).toMatchObject({
+        id: mockFile.id,
+        path: mockFile.path,
+        content: "# Draft 2",
+      });
+    });
+
+    it("should discard the draft when the file is closed", () => {
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().removeFile(mockFile.id);
+      expect(invoke).toHaveBeenCalledWith("discard_draft", { id: mockFile.id });
+    });
+
+    it("should report closed documents to the backend", () => {
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setContent("# Unsaved");
+      useEditorStore.getState().removeFile(mockFile.id);
+      expect(invoke).toHaveBeenCalledWith("document_closed", {
+        path: mockFile.path,
+        discarded: true,
+      });
+    });
+
+    it("should open recovered drafts and discard declined ones", async () => {
+      const draft = (id: string) => ({
+        id,
+        path: `/test/${id}.md`,
+        name: `${id}.md`,
+        content: `# ${id}`,
+        base_hash: null,
+        session: "old",
+        saved_at: 0,
+      });
+      vi.mocked(invoke).mockImplementation(async (command, args) => {
+        if (command === "get_recoverable_drafts") {
+          return ["kept", "dropped"].map((id) => ({
+            draft: draft(id),
+            disk_status: "unchanged",
+            diff: [],
+          }));
+        }
+        if (command === "restore_draft") {
+          return draft((args as { id: string }).id);
+        }
+        return undefined;
+      });
+      vi.mocked(ask).mockResolvedValueOnce(true).mockResolvedValueOnce(false);
+
+      await useEditorStore.getState().recoverDrafts();
+      const state = useEditorStore.getState();
+      expect(state.files.map((f) => f.id)).toEqual(["kept"]);
+      expect(state.currentFile?.content).toBe("# kept");
+      expect(state.isModified).toBe(true);
+      expect(invoke).toHaveBeenCalledWith("discard_draft", { id: "dropped" });
+    });
+  });
+
+  describe("exportHtml", () => {
+    afterEach(() => {
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+    });
+
+    it("should title the page from the front matter", async () => {
+      vi.mocked(save).mockResolvedValueOnce("/test/out.html");
+      vi.mocked(invoke).mockImplementation(async (command) => {
+        switch (command) {
+          case "render_markdown":
+            return { html: "<p>Body</p>", source_map: [] };
+          case "parse_front_matter":
+            return {
+              front_matter: { data: { title: "From front matter" } },
+              body: "",
+            };
+          case "write_file":
+            return { path: "/test/out.html", success: true };
+          default:
+            return undefined;
+        }
+      });
+      useEditorStore.getState().addFile(mockFile);
+      await useEditorStore.getState().exportHtml();
+
+      const write = vi
+        .mocked(invoke)
+        .mock.calls.find(([command]) => command === "write_file");
+      const { content } = write?.[1] as { content: string };
+      expect(content).toContain("<title>From front matter</title>");
+      expect(content).toContain("<p>Body</p>");
+    });
+  });
+
+  describe("newFile", () => {
+    it("should open an empty untitled document", () => {
+      const file = useEditorStore.getState().newFile();
+      const state = useEditorStore.getState();
+      expect(state.currentFile).toEqual(file);
+      expect(file.path).toBe("");
+      expect(file.name).toBe("Untitled.md");
+      expect(state.isModified).toBe(false);
+    });
+
+    it("should count initial content as unsaved", () => {
+      const file = useEditorStore.getState().newFile("# Notes", "notes.md");
+      expect(useEditorStore.getState().modifiedFiles).toEqual([file.id]);
+      expect(useEditorStore.getState().isModified).toBe(true);
+    });
+  });
+
+  describe("find", () => {
+    beforeEach(() => {
+      useEditorStore
+        .getState()
+        .addFile({ ...mockFile, content: "one two one two" });
+    });
+
+    it("should open the find bar and leave preview mode", () => {
+      useEditorStore.getState().setViewMode("preview");
+      useEditorStore.getState().openFind(true);
+      expect(useEditorStore.getState().findBar).toBe("replace");
+      expect(useEditorStore.getState().viewMode).toBe("split");
+    });
+
+    it("should step through matches in both directions", () => {
+      useEditorStore.getState().setFindQuery("two");
+      useEditorStore.getState().findNext();
+      expect(useEditorStore.getState().selection).toEqual({ start: 4, end: 7 });
+      useEditorStore.getState().findNext();
+      expect(useEditorStore.getState().selection).toEqual({
+        start: 12,
+        end: 15,
+      });
+      useEditorStore.getState().findPrevious();
+      expect(useEditorStore.getState().selection).toEqual({ start: 4, end: 7 });
+    });
+
+    it("should replace the selected match and select the next one", () => {
+      useEditorStore.getState().setFindQuery("one");
+      useEditorStore.getState().findNext();
+      useEditorStore.getState().replaceNext("1");
+      const state = useEditorStore.getState();
+      expect(state.currentFile?.content).toBe("1 two one two");
+      expect(state.selection).toEqual({ start: 6, end: 9 });
+      expect(state.isModified).toBe(true);
+    });
+
+    it("should replace all matches", () => {
+      useEditorStore.getState().setFindQuery("TWO");
+      useEditorStore.getState().replaceAll("2");
+      expect(useEditorStore.getState().currentFile?.content).toBe(
+        "one 2 one 2",
+      );
+    });
+  });
+
+  describe("zoom", () => {
+    it("should zoom in steps within limits and reset", () => {
+      useEditorStore.getState().zoomIn();
+      expect(useEditorStore.getState().zoom).toBe(1.1);
+      for (let i = 0; i < 50; i++) useEditorStore.getState().zoomOut();
+      expect(useEditorStore.getState().zoom).toBe(0.5);
+      useEditorStore.getState().zoomReset();
+      expect(useEditorStore.getState().zoom).toBe(1);
+    });
+  });
+
+  describe("runCommand", () => {
+    it("should run menu and key binding commands by id", () => {
+      useEditorStore.getState().runCommand("view_edit");
+      expect(useEditorStore.getState().viewMode).toBe("edit");
+      useEditorStore.getState().runCommand("replace");
+      expect(useEditorStore.getState().findBar).toBe("replace");
+      useEditorStore.getState().runCommand("zoom_in");
+      expect(useEditorStore.getState().zoom).toBe(1.1);
+    });
+  });
+
+  describe("window document", () => {
+    afterEach(() => {
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+    });
+
+    it("should report the active document to the backend", () => {
+      useEditorStore.getState().addFile(mockFile);
+      expect(invoke).toHaveBeenCalledWith("set_window_document", {
+        path: mockFile.path,
+      });
+
+      useEditorStore.getState().removeFile(mockFile.id);
+      expect(invoke).toHaveBeenCalledWith("set_window_document", {
+        path: null,
+      });
+    });
+
+    it("should take the files queued for this window", async () => {
+      await useEditorStore.getState().openStartupFiles();
+      expect(invoke).toHaveBeenCalledWith("take_startup_files");
+    });
+  });
+
+  describe("navigateTo", () => {
+    it("should move the cursor to a line and column", async () => {
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().setViewMode("preview");
+      await useEditorStore.getState().navigateTo({
+        path: mockFile.path,
+        line: 3,
+        column: 2,
+        heading: null,
+        readonly: false,
+      });
+      const state = useEditorStore.getState();
+      expect(state.selection).toEqual({ start: 9, end: 9 });
+      expect(state.viewMode).toBe("split");
+    });
+
+    it("should move the cursor to a heading", async () => {
+      const other = { ...mockFile, id: "other", path: "/test/other.md" };
+      useEditorStore.getState().addFile(mockFile);
+      useEditorStore.getState().addFile(other);
+      useEditorStore.getState().setSelection({ start: 5, end: 5 });
+      await useEditorStore.getState().navigateTo({
+        path: mockFile.path,
+        line: null,
+        column: null,
+        heading: "test",
+        readonly: false,
+      });
+      const state = useEditorStore.getState();
+      expect(state.currentFile?.id).toBe(mockFile.id);
+      expect(state.selection).toEqual({ start: 0, end: 0 });
+    });
+  });
+
+  describe("read-only files", () => {
+    it("should open files read-only when requested", async () => {
+      useEditorStore.getState().addFile(mockFile);
+      await useEditorStore.getState().navigateTo({
+        path: mockFile.path,
+        line: null,
+        column: null,
+        heading: null,
+        readonly: true,
+      });
+      expect(useEditorStore.getState().currentFile?.readOnly).toBe(true);
+
+      useEditorStore.getState().setContent("# Changed");
+      const state = useEditorStore.getState();
+      expect(state.currentFile?.content).toBe(mockFile.content);
+      expect(state.isModified).toBe(false);
+    });
+  });
+
+  describe("workspace", () => {
+    afterEach(() => {
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+    });
+
+    const entry = (path: string, isDirectory = false) => ({
+      path,
+      name: path.split("/").pop(),
+      is_directory: isDirectory,
+    });
+
+    it("should list the Markdown files of a folder opened at launch", async () => {
+      vi.mocked(invoke).mockImplementation(async (command, args) => {
+        const path = (args as { path?: string } | undefined)?.path;
+        if (command === "take_startup_files") {
+          return {
+            paths: [],
+            navigation: [],
+            workspace: "/notes",
+            untitled: [],
+          };
+        }
+        if (command === "list_dir" && path === "/notes") {
+          return {
+            path,
+            success: true,
+            entries: [
+              entry("/notes/b.md"),
+              entry("/notes/notes.txt"),
+              entry("/notes/.git", true),
+              entry("/notes/guides", true),
+            ],
+          };
+        }
+        if (command === "list_dir" && path === "/notes/guides") {
+          return {
+            path,
+            success: true,
+            entries: [entry("/notes/guides/a.md")],
+          };
+        }
+        return undefined;
+      });
+
+      await useEditorStore.getState().openStartupFiles();
+      const state = useEditorStore.getState();
+      expect(state.workspace).toBe("/notes");
+      expect(state.workspaceFiles).toEqual([
+        "/notes/b.md",
+        "/notes/guides/a.md",
+      ]);
+      expect(invoke).not.toHaveBeenCalledWith("list_dir", {
+        path: "/notes/.git",
+      });
+
+      useEditorStore.getState().closeWorkspace();
+      expect(useEditorStore.getState().workspace).toBeNull();
+    });
+  });
+
+  describe("openUntitled", () => {
+    it("should open the content named after the title", () => {
+      const file = useEditorStore
+        .getState()
+        .openUntitled({ content: "piped", title: "README" });
+      expect(file.name).toBe("README.md");
+      expect(file.path).toBe("");
+      expect(useEditorStore.getState().isModified).toBe(true);
+    });
+
+    it("should suggest the title when saving", async () => {
+      vi.mocked(save).mockResolvedValueOnce(null);
+      useEditorStore.getState().openUntitled({ content: "x", title: "README" });
+      expect(await useEditorStore.getState().saveFile()).toBe(false);
+      expect(save).toHaveBeenCalledWith(
+        expect.objectContaining({ defaultPath: "README.md" }),
+      );
+    });
+  });
+
+  describe("searchWorkspace", () => {
+    afterEach(() => {
+      vi.mocked(invoke).mockReset().mockResolvedValue(undefined);
+    });
+
+    it("should search the folder of the current file", async () => {
+      vi.mocked(invoke).mockImplementation(async (command) =>
+        command === "search_workspace" ? 7 : undefined,
+      );
+      useEditorStore.getState().addFile(mockFile);
+      await useEditorStore.getState().searchWorkspace("todo");
+
+      expect(invoke).toHaveBeenCalledWith("search_workspace", {
+        root: "/test",
+        query: "todo",
+      });
+      expect(useEditorStore.getState().search).toMatchObject({
+        query: "todo",
+        root: "/test",
+        id: 7,
+        finished: false,
+      });
+
+      useEditorStore.getState().clearSearch();
+      expect(useEditorStore.getState().search).toBeNull();
+    });
+  });
 });
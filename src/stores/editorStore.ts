@@ -1,17 +1,38 @@
 import { create } from "zustand";
 import { persist, createJSONStorage } from "zustand/middleware";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save, ask, message } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
-import type { MarkdownFile } from "../types";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import type {
+  MarkdownFile,
+  Draft,
+  RecoverableDraft,
+  KeybindingRegistry,
+  PendingLaunch,
+  ParsedDocument,
+  NavigateRequest,
+  UntitledDocument,
+  SearchFileMatches,
+  WorkspaceSearch,
+} from "../types";
 import { generateId } from "../lib/markdown";
+import {
+  findInText,
+  replaceAllInText,
+  offsetOfLine,
+  findHeadingLine,
+  type TextRange,
+} from "../lib/find";
 import { useRecentFilesStore } from "./recentFilesStore";
 import {
   readFile,
   writeFile,
   isMarkdownFile,
   getFileName,
+  getParentDir,
+  listDir,
+  MARKDOWN_EXTENSIONS,
   setupGlobalFileChangeListener,
-  setupReloadFileListener,
   setupFileOpenListener,
   watchFile,
   unwatchFile,
@@ -35,10 +56,196 @@ function updateWindowTitle(
   }
 }
 
+/** Document last reported to the backend for this window */
+let reportedDocument: string | null | undefined;
+
+/**
+ * Tell the backend which document the window shows, so opening it
+ * elsewhere focuses this window
+ */
+function reportWindowDocument(path: string | null) {
+  if (path === reportedDocument) return;
+  reportedDocument = path;
+  invoke("set_window_document", { path }).catch(console.error);
+}
+
+/** Pause in typing after which an edited buffer is journaled as a draft */
+const DRAFT_DELAY_MS = 1000;
+
+const draftTimers = new Map<string, ReturnType<typeof setTimeout>>();
+
+/**
+ * Journal a snapshot of an unsaved buffer once typing pauses,
+ * so it can be recovered after a crash
+ */
+function scheduleDraft(file: MarkdownFile) {
+  clearTimeout(draftTimers.get(file.id));
+  draftTimers.set(
+    file.id,
+    setTimeout(() => {
+      draftTimers.delete(file.id);
+      invoke("save_draft", {
+        id: file.id,
+        path: file.path || null,
+        name: file.name,
+        content: file.content,
+        baseHash: file.baseHash ?? null,
+      }).catch(console.error);
+    }, DRAFT_DELAY_MS),
+  );
+}
+
+/**
+ * Drop the draft of a buffer that was saved, reloaded or closed
+ */
+function discardDraft(id: string) {
+  clearTimeout(draftTimers.get(id));
+  draftTimers.delete(id);
+  invoke("discard_draft", { id }).catch(console.error);
+}
+
+/**
+ * Describe a draft left behind by a previous session
+ */
+function recoveryMessage({ draft, disk_status }: RecoverableDraft): string {
+  const message = `"${draft.name}" has unsaved changes from a previous session. Recover them?`;
+  switch (disk_status) {
+    case "modified":
+      return `${message}\n\nThe file was changed on disk since.`;
+    case "missing":
+      return `${message}\n\nThe file no longer exists.`;
+    default:
+      return message;
+  }
+}
+
+/**
+ * Markdown file extensions from the settings
+ */
+async function markdownExtensions(): Promise<string[]> {
+  try {
+    const settings = await invoke<{ markdown_extensions: string[] } | null>(
+      "get_settings",
+    );
+    return settings?.markdown_extensions ?? MARKDOWN_EXTENSIONS;
+  } catch (error) {
+    console.error("Failed to read settings:", error);
+    return MARKDOWN_EXTENSIONS;
+  }
+}
+
+/**
+ * Dialog filter for the Markdown extensions from the settings
+ */
+async function markdownFilter() {
+  // Dialog filters are case-sensitive on some platforms
+  const extensions = (await markdownExtensions()).flatMap((ext) => [
+    ext.toLowerCase(),
+    ext.toUpperCase(),
+  ]);
+  return { name: "Markdown", extensions: [...new Set(extensions)] };
+}
+
+/** Most files listed for a workspace, and how deep its folders are read */
+const MAX_WORKSPACE_FILES = 1000;
+const MAX_WORKSPACE_DEPTH = 4;
+
+/**
+ * Markdown files under a folder, sorted by path, skipping hidden entries
+ */
+async function workspaceFiles(root: string): Promise<string[]> {
+  const extensions = await markdownExtensions();
+  const files: string[] = [];
+  let folders = [root];
+  for (let depth = 0; depth < MAX_WORKSPACE_DEPTH && folders.length; depth++) {
+    const next: string[] = [];
+    for (const folder of folders) {
+      const result = await listDir(folder, {
+        filter: (entry) => !entry.name.startsWith("."),
+      });
+      if (!result.success) continue;
+      for (const entry of result.entries) {
+        if (entry.is_directory) {
+          next.push(entry.path);
+        } else if (isMarkdownFile(entry.path, extensions)) {
+          files.push(entry.path);
+        }
+      }
+    }
+    folders = next;
+  }
+  return files.sort().slice(0, MAX_WORKSPACE_FILES);
+}
+
+/** Zoom levels, as a factor of the default size */
+const MIN_ZOOM = 0.5;
+const MAX_ZOOM = 3;
+const ZOOM_STEP = 0.1;
+
+/**
+ * Escape text for use in HTML
+ */
+function escapeHtml(text: string): string {
+  return text
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;");
+}
+
+/**
+ * Wrap rendered Markdown in a standalone HTML document
+ */
+function htmlDocument(title: string, body: string): string {
+  return `<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>${escapeHtml(title)}</title>
+</head>
+<body>
+${body}
+</body>
+</html>
+`;
+}
+
+/**
+ * Readable name of a command id, e.g. "Toggle sidebar" for "toggle_sidebar"
+ */
+function commandLabel(command: string): string {
+  const words = command.replace(/_/g, " ");
+  return words.charAt(0).toUpperCase() + words.slice(1);
+}
+
+/**
+ * Add or remove a file id from the ids of modified files
+ */
+function markModified(ids: string[], id: string, modified: boolean): string[] {
+  const others = ids.filter((other) => other !== id);
+  return modified ? [...others, id] : others;
+}
+
 interface EditorState {
   currentFile: MarkdownFile | null;
   files: MarkdownFile[];
+  /** Whether the current file has unsaved changes */
   isModified: boolean;
+  /** Ids of all open files with unsaved changes */
+  modifiedFiles: string[];
+  /** Open find bar, if any */
+  findBar: "find" | "replace" | null;
+  findQuery: string;
+  /** Selection in the editor */
+  selection: TextRange;
+  /** Bumped when the store moves the selection, so the editor follows it */
+  selectionRequest: number;
+  zoom: number;
+  /** Folder opened as a workspace, and the Markdown files in it */
+  workspace: string | null;
+  workspaceFiles: string[];
+  /** Workspace search shown in the sidebar, if any */
+  search: WorkspaceSearch | null;
   viewMode: "edit" | "preview" | "split";
   sidebarWidth: number;
   sidebarCollapsed: boolean;
@@ -47,6 +254,7 @@ interface EditorState {
 
 interface EditorActions {
   setCurrentFile: (file: MarkdownFile | null) => void;
+  newFile: (content?: string, name?: string) => MarkdownFile;
   setContent: (content: string) => void;
   addFile: (file: MarkdownFile) => void;
   removeFile: (id: string) => void;
@@ -56,13 +264,41 @@ interface EditorActions {
   saveFile: () => Promise<boolean>;
   saveFileAs: () => Promise<MarkdownFile | null>;
   reloadFile: () => Promise<void>;
+  exportHtml: () => Promise<void>;
+  exportPdf: () => void;
+  openFind: (replace: boolean) => void;
+  closeFind: () => void;
+  setFindQuery: (query: string) => void;
+  findNext: () => void;
+  findPrevious: () => void;
+  replaceNext: (replacement: string) => void;
+  replaceAll: (replacement: string) => void;
+  setSelection: (selection: TextRange) => void;
+  selectRange: (selection: TextRange) => void;
+  zoomIn: () => void;
+  zoomOut: () => void;
+  zoomReset: () => void;
+  showKeyboardShortcuts: () => Promise<void>;
   setViewMode: (mode: "edit" | "preview" | "split") => void;
   markAsSaved: () => void;
   setupFileWatcher: () => void;
+  setupUnsavedChangesHandler: () => () => void;
+  setupMenuCommandHandler: () => () => void;
+  runCommand: (id: string) => void;
+  toggleFullscreen: () => Promise<void>;
   setSidebarWidth: (width: number) => void;
   toggleSidebar: () => void;
   setSplitRatio: (ratio: number) => void;
   openStartupFiles: () => Promise<void>;
+  openWindowDocument: () => Promise<void>;
+  navigateTo: (request: NavigateRequest) => Promise<void>;
+  openWorkspace: (path: string) => Promise<void>;
+  closeWorkspace: () => void;
+  openUntitled: (document: UntitledDocument) => MarkdownFile;
+  searchWorkspace: (query: string, root?: string | null) => Promise<void>;
+  clearSearch: () => void;
+  setupSearchHandler: () => () => void;
+  recoverDrafts: () => Promise<void>;
   /** Internal: trigger UI update (title bar) */
   _updateUI: () => void;
 }
@@ -71,6 +307,15 @@ const initialState: EditorState = {
   currentFile: null,
   files: [],
   isModified: false,
+  modifiedFiles: [],
+  findBar: null,
+  findQuery: "",
+  selection: { start: 0, end: 0 },
+  selectionRequest: 0,
+  zoom: 1,
+  workspace: null,
+  workspaceFiles: [],
+  search: null,
   viewMode: "split",
   sidebarWidth: 250,
   sidebarCollapsed: false,
@@ -87,8 +332,34 @@ export const useEditorStore = create<EditorState & EditorActions>()(
        * Always use this when currentFile or isModified changes
        */
       setCurrentFile: (file: MarkdownFile | null) => {
-        set({ currentFile: file, isModified: false });
+        set((state) => ({
+          currentFile: file,
+          isModified: !!file && state.modifiedFiles.includes(file.id),
+        }));
+        get()._updateUI();
+      },
+
+      newFile: (content = "", name = "Untitled.md") => {
+        const file: MarkdownFile = {
+          id: generateId(),
+          path: "",
+          name,
+          content,
+          lastModified: Date.now(),
+        };
+        // Content that was never saved counts as a change
+        set((state) => ({
+          files: [...state.files, file],
+          currentFile: file,
+          isModified: content !== "",
+          modifiedFiles: markModified(
+            state.modifiedFiles,
+            file.id,
+            content !== "",
+          ),
+        }));
         get()._updateUI();
+        return file;
       },
 
       /**
@@ -102,27 +373,162 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         const hasNamesake = namesakes.length > 1;
 
         updateWindowTitle(hasNamesake ? currentFile?.path : cn, isModified);
+        reportWindowDocument(currentFile?.path || null);
       },
 
+      /**
+       * Open the files, folder, untitled documents and positions the
+       * backend queued for this window
+       */
       openStartupFiles: async () => {
         try {
-          const paths = await invoke<string[]>("get_startup_files");
-          paths.forEach((path) => {
-            get().openFileByPath(path);
-          });
-          invoke("clear_startup_files").catch(console.error);
+          const pending = await invoke<PendingLaunch>("take_startup_files");
+          if (pending.workspace) {
+            await get().openWorkspace(pending.workspace);
+          }
+          for (const path of pending.paths) {
+            await get().openFileByPath(path);
+          }
+          pending.untitled.forEach((document) => get().openUntitled(document));
+          for (const request of pending.navigation) {
+            await get().navigateTo(request);
+          }
         } catch (error) {
           console.error("Failed to open startup files:", error);
         }
       },
 
+      /**
+       * Open the document a new window was created for
+       */
+      openWindowDocument: async () => {
+        try {
+          const path = await invoke<string | null>("get_window_document");
+          if (path) {
+            await get().openFileByPath(path);
+          }
+        } catch (error) {
+          console.error("Failed to open window document:", error);
+        }
+      },
+
+      /**
+       * Show a file at a line and column or heading, opening it if needed
+       */
+      navigateTo: async ({
+        path,
+        line,
+        column,
+        heading,
+        readonly,
+      }: NavigateRequest) => {
+        let file = get().files.find((f: MarkdownFile) => f.path === path);
+        if (!file) {
+          await get().openFileByPath(path);
+          file = get().files.find((f: MarkdownFile) => f.path === path);
+          if (!file) return;
+        } else if (get().currentFile?.id !== file.id) {
+          get().setCurrentFile(file);
+        }
+        if (readonly) {
+          get().updateFile(file.id, { readOnly: true });
+        }
+
+        const target =
+          line ?? (heading ? findHeadingLine(file.content, heading) : null);
+        if (target === null) return;
+        const offset = offsetOfLine(file.content, target, column ?? 1);
+        // The cursor is shown in the editor, which preview mode hides
+        set((state) => ({
+          viewMode: state.viewMode === "preview" ? "split" : state.viewMode,
+        }));
+        get().selectRange({ start: offset, end: offset });
+      },
+
+      /**
+       * Show a folder and its Markdown files in the sidebar
+       */
+      openWorkspace: async (path: string) => {
+        set({ workspace: path, workspaceFiles: [], sidebarCollapsed: false });
+        const files = await workspaceFiles(path);
+        // Another folder may have been opened meanwhile
+        if (get().workspace === path) {
+          set({ workspaceFiles: files });
+        }
+      },
+
+      closeWorkspace: () => {
+        set({ workspace: null, workspaceFiles: [] });
+      },
+
+      /**
+       * Open a document that has no file yet; saving it asks for a name
+       */
+      openUntitled: ({ content, title }: UntitledDocument) => {
+        const name = !title
+          ? "Untitled.md"
+          : isMarkdownFile(title)
+            ? title
+            : `${title}.md`;
+        return get().newFile(content, name);
+      },
+
+      recoverDrafts: async () => {
+        try {
+          const drafts = await invoke<RecoverableDraft[]>(
+            "get_recoverable_drafts",
+          );
+          for (const recoverable of drafts) {
+            const recover = await ask(recoveryMessage(recoverable), {
+              title: "Recover Unsaved Changes",
+              kind: "warning",
+              okLabel: "Recover",
+              cancelLabel: "Discard",
+            });
+            if (!recover) {
+              discardDraft(recoverable.draft.id);
+              continue;
+            }
+
+            const draft = await invoke<Draft>("restore_draft", {
+              id: recoverable.draft.id,
+            });
+            const file: MarkdownFile = {
+              id: draft.id,
+              path: draft.path ?? "",
+              name: draft.name,
+              content: draft.content,
+              baseHash: draft.base_hash ?? undefined,
+              lastModified: Date.now(),
+            };
+            // The recovered buffer replaces the file if it is already open
+            set((state) => ({
+              files: [
+                ...state.files.filter(
+                  (f: MarkdownFile) => !file.path || f.path !== file.path,
+                ),
+                file,
+              ],
+              currentFile: file,
+              isModified: true,
+              modifiedFiles: markModified(state.modifiedFiles, file.id, true),
+            }));
+            get()._updateUI();
+          }
+        } catch (error) {
+          console.error("Failed to recover drafts:", error);
+        }
+      },
+
       setContent: (content: string) => {
         const { currentFile } = get();
-        if (currentFile) {
-          set({
+        if (currentFile && !currentFile.readOnly) {
+          set((state) => ({
             currentFile: { ...currentFile, content },
             isModified: true,
-          });
+            modifiedFiles: markModified(state.modifiedFiles, currentFile.id, true),
+          }));
+          scheduleDraft({ ...currentFile, content });
           // Update the file in the files array
           const files = get().files.map((f: MarkdownFile) =>
             f.id === currentFile.id ? { ...f, content } : f,
@@ -143,7 +549,16 @@ export const useEditorStore = create<EditorState & EditorActions>()(
       },
 
       removeFile: (id: string) => {
-        const { currentFile, files } = get();
+        const { currentFile, files, modifiedFiles } = get();
+        discardDraft(id);
+        // Completes a `--wait` launch once all of its files are closed
+        const closed = files.find((f: MarkdownFile) => f.id === id);
+        if (closed?.path) {
+          invoke("document_closed", {
+            path: closed.path,
+            discarded: modifiedFiles.includes(id),
+          }).catch(console.error);
+        }
         const remainingFiles = files.filter((f: MarkdownFile) => f.id !== id);
 
         // Determine new currentFile
@@ -156,9 +571,13 @@ export const useEditorStore = create<EditorState & EditorActions>()(
           newCurrentFile = currentFile;
         }
 
+        const remainingModified = markModified(modifiedFiles, id, false);
         set({
           files: remainingFiles,
           currentFile: newCurrentFile,
+          modifiedFiles: remainingModified,
+          isModified:
+            !!newCurrentFile && remainingModified.includes(newCurrentFile.id),
         });
 
         // Trigger UI update through single source of truth
@@ -181,12 +600,7 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         try {
           const selected = await open({
             multiple: false,
-            filters: [
-              {
-                name: "Markdown",
-                extensions: ["md", "markdown", "mdown", "mkd", "mkdn"],
-              },
-            ],
+            filters: [await markdownFilter()],
           });
 
           if (selected === null) {
@@ -205,7 +619,7 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         console.log("[openFileByPath] Opening file:", path);
 
         // Validate file type
-        if (!isMarkdownFile(path)) {
+        if (!isMarkdownFile(path, await markdownExtensions())) {
           console.error("[openFileByPath] Not a markdown file:", path);
           return null;
         }
@@ -222,13 +636,14 @@ export const useEditorStore = create<EditorState & EditorActions>()(
           name: result.name || getFileName(path),
           content: result.content,
           lastModified: Date.now(),
+          baseHash: result.hash,
         };
 
         const { files } = get();
         const existingFile = files.find((f: MarkdownFile) => f.path === path);
 
         if (existingFile) {
-          set({ currentFile: existingFile, isModified: false });
+          get().setCurrentFile(existingFile);
         } else {
           set((state) => ({
             files: [...state.files, file],
@@ -260,6 +675,8 @@ export const useEditorStore = create<EditorState & EditorActions>()(
       saveFile: async () => {
         const { currentFile } = get();
         if (!currentFile) return false;
+        // Untitled documents need a name first
+        if (!currentFile.path) return (await get().saveFileAs()) !== null;
 
         const result = await writeFile(currentFile.path, currentFile.content);
         if (!result.success) {
@@ -267,7 +684,32 @@ export const useEditorStore = create<EditorState & EditorActions>()(
           return false;
         }
 
-        set({ isModified: false });
+        // Adopt what was written (e.g. a regenerated table of contents),
+        // unless the document was edited while saving
+        const latest = get().currentFile;
+        if (latest?.content === currentFile.content) {
+          const saved = {
+            ...latest,
+            content: result.content ?? latest.content,
+            baseHash: result.hash,
+          };
+          set({
+            currentFile: saved,
+            files: get().files.map((f: MarkdownFile) =>
+              f.id === saved.id ? saved : f,
+            ),
+          });
+          discardDraft(currentFile.id);
+          set((state) => ({
+            modifiedFiles: markModified(
+              state.modifiedFiles,
+              currentFile.id,
+              false,
+            ),
+            isModified: false,
+          }));
+        }
+
         // Trigger UI update through single source of truth
         get()._updateUI();
         return true;
@@ -278,19 +720,13 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         if (!currentFile) return null;
 
         try {
-          const selected = await open({
-            save: true,
-            filters: [
-              {
-                name: "Markdown",
-                extensions: ["md", "markdown", "mdown", "mkd", "mkdn"],
-              },
-            ],
+          // Untitled documents suggest their title as the file name
+          const path = await save({
+            filters: [await markdownFilter()],
             defaultPath: currentFile.name,
           });
 
-          if (selected) {
-            const path = Array.isArray(selected) ? selected[0] : selected;
+          if (path) {
             const result = await writeFile(path, currentFile.content);
 
             if (!result.success) {
@@ -304,18 +740,26 @@ export const useEditorStore = create<EditorState & EditorActions>()(
               id: generateId(),
               path,
               name,
+              content: result.content ?? currentFile.content,
+              baseHash: result.hash,
             };
+            discardDraft(currentFile.id);
 
             const { files } = get();
             const updatedFiles = files.filter(
               (f: MarkdownFile) => f.id !== currentFile.id,
             );
 
-            set({
+            set((state) => ({
               files: [...updatedFiles, newFile],
               currentFile: newFile,
               isModified: false,
-            });
+              modifiedFiles: markModified(
+                state.modifiedFiles,
+                currentFile.id,
+                false,
+              ),
+            }));
 
             // Trigger UI update through single source of truth
             get()._updateUI();
@@ -344,27 +788,215 @@ export const useEditorStore = create<EditorState & EditorActions>()(
 
         const { files } = get();
 
-        const updatedFile = { ...currentFile, content: result.content };
+        const updatedFile = {
+          ...currentFile,
+          content: result.content,
+          baseHash: result.hash,
+        };
+        discardDraft(currentFile.id);
         const updatedFiles = files.map((f: MarkdownFile) =>
           f.id === currentFile.id ? updatedFile : f,
         );
 
-        set({
+        set((state) => ({
           files: updatedFiles,
           currentFile: updatedFile,
           isModified: false,
-        });
+          modifiedFiles: markModified(
+            state.modifiedFiles,
+            currentFile.id,
+            false,
+          ),
+        }));
 
         // Trigger UI update through single source of truth
         get()._updateUI();
       },
 
+      exportHtml: async () => {
+        const { currentFile } = get();
+        if (!currentFile) return;
+
+        try {
+          const name = currentFile.name.replace(/\.[^.]*$/, "");
+          const path = await save({
+            filters: [{ name: "HTML", extensions: ["html", "htm"] }],
+            defaultPath: `${name}.html`,
+          });
+          if (!path) return;
+
+          const [{ html }, { front_matter }] = await Promise.all([
+            invoke<{ html: string }>("render_markdown", {
+              content: currentFile.content,
+              path: currentFile.path || null,
+            }),
+            invoke<ParsedDocument>("parse_front_matter", {
+              content: currentFile.content,
+            }),
+          ]);
+          // The page is titled like command-line exports
+          const title = front_matter?.data?.title;
+          const result = await writeFile(
+            path,
+            htmlDocument(typeof title === "string" ? title : name, html),
+          );
+          if (!result.success) {
+            console.error("Failed to export HTML:", result.error);
+          }
+        } catch (error) {
+          console.error("Failed to export HTML:", error);
+        }
+      },
+
+      /**
+       * Print the preview, which the system print dialog can save as PDF
+       */
+      exportPdf: () => {
+        if (!get().currentFile) return;
+        if (get().viewMode === "edit") {
+          set({ viewMode: "preview" });
+        }
+        // Let the preview render before printing
+        setTimeout(() => window.print(), 0);
+      },
+
+      openFind: (replace: boolean) => {
+        // Matches are shown in the editor, which preview mode hides
+        set((state) => ({
+          findBar: replace ? "replace" : "find",
+          viewMode: state.viewMode === "preview" ? "split" : state.viewMode,
+        }));
+      },
+
+      closeFind: () => {
+        set({ findBar: null });
+      },
+
+      setFindQuery: (query: string) => {
+        set({ findQuery: query });
+      },
+
+      findNext: () => {
+        const { currentFile, findQuery, selection } = get();
+        if (!findQuery) {
+          get().openFind(false);
+          return;
+        }
+        if (!currentFile) return;
+        const match = findInText(
+          currentFile.content,
+          findQuery,
+          selection.end,
+        );
+        if (match) get().selectRange(match);
+      },
+
+      findPrevious: () => {
+        const { currentFile, findQuery, selection } = get();
+        if (!findQuery) {
+          get().openFind(false);
+          return;
+        }
+        if (!currentFile) return;
+        const match = findInText(
+          currentFile.content,
+          findQuery,
+          selection.start,
+          true,
+        );
+        if (match) get().selectRange(match);
+      },
+
+      replaceNext: (replacement: string) => {
+        const { currentFile, findQuery, selection } = get();
+        if (!currentFile || !findQuery) return;
+
+        // Replace the selected match, then move on to the next one
+        const { content } = currentFile;
+        const selected = content.slice(selection.start, selection.end);
+        if (selected.toLowerCase() === findQuery.toLowerCase()) {
+          get().setContent(
+            content.slice(0, selection.start) +
+              replacement +
+              content.slice(selection.end),
+          );
+          const end = selection.start + replacement.length;
+          set({ selection: { start: end, end } });
+        }
+        get().findNext();
+      },
+
+      replaceAll: (replacement: string) => {
+        const { currentFile, findQuery } = get();
+        if (!currentFile || !findQuery) return;
+        const { text, count } = replaceAllInText(
+          currentFile.content,
+          findQuery,
+          replacement,
+        );
+        if (count > 0) get().setContent(text);
+      },
+
+      setSelection: (selection: TextRange) => {
+        set({ selection });
+      },
+
+      selectRange: (selection: TextRange) => {
+        set((state) => ({
+          selection,
+          selectionRequest: state.selectionRequest + 1,
+        }));
+      },
+
+      zoomIn: () => {
+        set((state) => ({
+          zoom: Math.min(
+            MAX_ZOOM,
+            Math.round((state.zoom + ZOOM_STEP) * 10) / 10,
+          ),
+        }));
+      },
+
+      zoomOut: () => {
+        set((state) => ({
+          zoom: Math.max(
+            MIN_ZOOM,
+            Math.round((state.zoom - ZOOM_STEP) * 10) / 10,
+          ),
+        }));
+      },
+
+      zoomReset: () => {
+        set({ zoom: 1 });
+      },
+
+      showKeyboardShortcuts: async () => {
+        try {
+          const { bindings } =
+            await invoke<KeybindingRegistry>("get_keybindings");
+          const lines = bindings
+            .filter((binding) => binding.accelerator)
+            .map(
+              (binding) =>
+                `${binding.accelerator}    ${commandLabel(binding.command)}`,
+            );
+          await message(lines.join("\n"), { title: "Keyboard Shortcuts" });
+        } catch (error) {
+          console.error("Failed to show keyboard shortcuts:", error);
+        }
+      },
+
       setViewMode: (mode: "edit" | "preview" | "split") => {
         set({ viewMode: mode });
       },
 
       markAsSaved: () => {
-        set({ isModified: false });
+        set((state) => ({
+          isModified: false,
+          modifiedFiles: state.currentFile
+            ? markModified(state.modifiedFiles, state.currentFile.id, false)
+            : state.modifiedFiles,
+        }));
       },
 
       setSidebarWidth: (width: number) => {
@@ -388,11 +1020,6 @@ export const useEditorStore = create<EditorState & EditorActions>()(
           }
         });
 
-        // Reload file from menu command
-        setupReloadFileListener(() => {
-          get().reloadFile();
-        });
-
         // Handle file open request from OS
         setupFileOpenListener(async (path) => {
           console.log("[file-open-requested] Path received:", path);
@@ -407,6 +1034,270 @@ export const useEditorStore = create<EditorState & EditorActions>()(
           }
         });
       },
+
+      /**
+       * Answer the backend's close/quit negotiation for this window:
+       * report unsaved documents and save the ones the user chose to keep
+       */
+      setupUnsavedChangesHandler: () => {
+        const currentWindow = getCurrentWebviewWindow();
+        const unlisteners = [
+          currentWindow.listen<{ request_id: number }>(
+            "unsaved-changes-query",
+            (event) => {
+              const { files, modifiedFiles } = get();
+              const documents = files
+                .filter((f: MarkdownFile) => modifiedFiles.includes(f.id))
+                .map((f: MarkdownFile) => ({
+                  id: f.id,
+                  path: f.path || null,
+                  name: f.name,
+                }));
+              invoke("report_dirty_documents", {
+                requestId: event.payload.request_id,
+                documents,
+              }).catch(console.error);
+            },
+          ),
+          currentWindow.listen<{ request_id: number; ids: string[] }>(
+            "save-documents-requested",
+            async (event) => {
+              const saved: string[] = [];
+              // Saving works on the current file, so show each one in turn
+              for (const id of event.payload.ids) {
+                const file = get().files.find((f: MarkdownFile) => f.id === id);
+                if (!file) continue;
+                get().setCurrentFile(file);
+                if (await get().saveFile()) {
+                  saved.push(id);
+                }
+              }
+              invoke("report_saved_documents", {
+                requestId: event.payload.request_id,
+                saved,
+              }).catch(console.error);
+            },
+          ),
+        ];
+
+        // Only ask the backend to wait for answers once both listeners are up
+        Promise.all(unlisteners)
+          .then(() => invoke("register_unsaved_changes_handler"))
+          .catch(console.error);
+
+        return () => {
+          unlisteners.forEach((unlisten) =>
+            unlisten.then((unsub) => unsub()),
+          );
+        };
+      },
+
+      /**
+       * Run a command from the menu or a key binding, by command id
+       */
+      runCommand: (id: string) => {
+        const state = get();
+        switch (id) {
+          case "new_file":
+            state.newFile();
+            break;
+          case "save":
+            state.saveFile();
+            break;
+          case "save_as":
+            state.saveFileAs();
+            break;
+          case "export_html":
+            state.exportHtml();
+            break;
+          case "export_pdf":
+            state.exportPdf();
+            break;
+          case "find":
+            state.openFind(false);
+            break;
+          case "find_next":
+            state.findNext();
+            break;
+          case "find_previous":
+            state.findPrevious();
+            break;
+          case "replace":
+            state.openFind(true);
+            break;
+          case "reload":
+            state.reloadFile();
+            break;
+          case "view_edit":
+            state.setViewMode("edit");
+            break;
+          case "view_preview":
+            state.setViewMode("preview");
+            break;
+          case "view_split":
+            state.setViewMode("split");
+            break;
+          case "toggle_sidebar":
+            state.toggleSidebar();
+            break;
+          case "zoom_in":
+            state.zoomIn();
+            break;
+          case "zoom_out":
+            state.zoomOut();
+            break;
+          case "zoom_reset":
+            state.zoomReset();
+            break;
+          case "keyboard_shortcuts":
+            state.showKeyboardShortcuts();
+            break;
+          case "open":
+            state.openFile();
+            break;
+          case "new_window":
+            invoke("new_window", { path: null }).catch(console.error);
+            break;
+          case "close_window":
+            // Goes through the close negotiation like the title bar button
+            getCurrentWebviewWindow().close().catch(console.error);
+            break;
+          case "toggle_fullscreen":
+            state.toggleFullscreen();
+            break;
+          case "minimize":
+            getCurrentWebviewWindow().minimize().catch(console.error);
+            break;
+          case "maximize":
+            getCurrentWebviewWindow().toggleMaximize().catch(console.error);
+            break;
+          default:
+            console.warn("[command] Unhandled command:", id);
+        }
+      },
+
+      toggleFullscreen: async () => {
+        try {
+          const window = getCurrentWebviewWindow();
+          await window.setFullscreen(!(await window.isFullscreen()));
+        } catch (error) {
+          console.error("Failed to toggle fullscreen:", error);
+        }
+      },
+
+      /**
+       * Search the Markdown files under `root`, by default the workspace or
+       * the folder of the current file; results arrive through
+       * `setupSearchHandler`
+       */
+      searchWorkspace: async (query: string, root?: string | null) => {
+        const folder =
+          root ||
+          get().workspace ||
+          getParentDir(get().currentFile?.path || "");
+        if (!folder) {
+          await message("Open a file or folder to search in.", {
+            title: "Search",
+          });
+          return;
+        }
+
+        set({
+          search: {
+            query,
+            root: folder,
+            id: null,
+            results: [],
+            finished: false,
+            truncated: false,
+          },
+        });
+        try {
+          const id = await invoke<number>("search_workspace", {
+            root: folder,
+            query,
+          });
+          // Results may arrive before the id; keep only this search's
+          set((state) =>
+            state.search?.query === query && state.search.root === folder
+              ? {
+                  search: {
+                    ...state.search,
+                    id,
+                    results: state.search.results.filter(
+                      (r: SearchFileMatches) => r.search_id === id,
+                    ),
+                  },
+                }
+              : {},
+          );
+        } catch (error) {
+          set({ search: null });
+          await message(String(error), { title: "Search", kind: "error" });
+        }
+      },
+
+      clearSearch: () => {
+        invoke("cancel_search").catch(console.error);
+        set({ search: null });
+      },
+
+      /**
+       * Run searches requested by links and collect their results
+       */
+      setupSearchHandler: () => {
+        const currentWindow = getCurrentWebviewWindow();
+        const isCurrent = (id: number) => {
+          const { search } = get();
+          return !!search && (search.id === null || search.id === id);
+        };
+        const unlisteners = [
+          currentWindow.listen<{ query: string; path: string | null }>(
+            "search-requested",
+            (event) =>
+              get().searchWorkspace(event.payload.query, event.payload.path),
+          ),
+          currentWindow.listen<SearchFileMatches>("search-result", (event) => {
+            if (!isCurrent(event.payload.search_id)) return;
+            set((state) => ({
+              search: state.search && {
+                ...state.search,
+                results: [...state.search.results, event.payload],
+              },
+            }));
+          }),
+          currentWindow.listen<{ search_id: number; truncated: boolean }>(
+            "search-finished",
+            (event) => {
+              if (!isCurrent(event.payload.search_id)) return;
+              set((state) => ({
+                search: state.search && {
+                  ...state.search,
+                  finished: true,
+                  truncated: event.payload.truncated,
+                },
+              }));
+            },
+          ),
+        ];
+
+        return () => {
+          unlisteners.forEach((unlisten) =>
+            unlisten.then((unsub) => unsub()),
+          );
+        };
+      },
+
+      setupMenuCommandHandler: () => {
+        const unlisten = getCurrentWebviewWindow().listen<{ id: string }>(
+          "menu-command",
+          (event) => get().runCommand(event.payload.id),
+        );
+
+        return () => {
+          unlisten.then((unsub) => unsub());
+        };
+      },
     }),
     {
       name: "editor-storage",
@@ -416,6 +1307,7 @@ export const useEditorStore = create<EditorState & EditorActions>()(
         sidebarWidth: state.sidebarWidth,
         sidebarCollapsed: state.sidebarCollapsed,
         splitRatio: state.splitRatio,
+        zoom: state.zoom,
       }),
     },
   ),
@@ -8,6 +8,8 @@ vi.mock("@tauri-apps/api/core", () => ({
 vi.mock("@tauri-apps/plugin-dialog", () => ({
   open: vi.fn(),
   save: vi.fn(),
+  ask: vi.fn(),
+  message: vi.fn(),
 }));
 
 vi.mock("@tauri-apps/api/event", () => ({
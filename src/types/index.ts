@@ -4,6 +4,10 @@ export interface MarkdownFile {
   name: string;
   content: string;
   lastModified?: number;
+  /** Fingerprint of the content on disk the buffer is based on */
+  baseHash?: string;
+  /** Opened read-only, e.g. with `--readonly` */
+  readOnly?: boolean;
 }
 
 export interface EditorState {
@@ -25,3 +29,119 @@ export interface RecentFile {
   name: string;
   lastOpened: number;
 }
+
+/**
+ * Snapshot of an unsaved buffer kept by the backend (snake_case)
+ */
+export interface Draft {
+  id: string;
+  path: string | null;
+  name: string;
+  content: string;
+  base_hash: string | null;
+  session: string;
+  saved_at: number;
+}
+
+export interface RecoverableDraft {
+  draft: Draft;
+  disk_status: "unchanged" | "modified" | "missing" | "untitled";
+  diff: { kind: "equal" | "insert" | "delete"; text: string }[];
+}
+
+/**
+ * Effective key binding for a command (snake_case, from the backend)
+ */
+export interface Keybinding {
+  command: string;
+  /** Accelerator in Tauri syntax, null if the command is unbound */
+  accelerator: string | null;
+  is_default: boolean;
+}
+
+export interface KeybindingRegistry {
+  bindings: Keybinding[];
+  issues: { command: string; message: string }[];
+}
+
+/**
+ * Position and mode for a file opened from the command line or a link
+ */
+export interface NavigateRequest {
+  path: string;
+  line: number | null;
+  column: number | null;
+  heading: string | null;
+  readonly: boolean;
+}
+
+/**
+ * Document without a file, e.g. from a template or stdin
+ */
+export interface UntitledDocument {
+  content: string;
+  /** Suggested name when saving */
+  title: string | null;
+}
+
+/**
+ * Launch requests queued for a window by the backend
+ */
+export interface PendingLaunch {
+  paths: string[];
+  navigation: NavigateRequest[];
+  workspace: string | null;
+  untitled: UntitledDocument[];
+}
+
+/**
+ * Line matching a workspace search (snake_case, from the backend)
+ */
+export interface SearchMatch {
+  /** 1-based line number */
+  line: number;
+  /** 1-based column of the first match */
+  column: number;
+  text: string;
+  /** Matches in `text` as [start, end) character offsets */
+  ranges: [number, number][];
+  before: string[];
+  after: string[];
+}
+
+/**
+ * Matches in one file, as sent with the `search-result` event
+ */
+export interface SearchFileMatches {
+  search_id: number;
+  path: string;
+  matches: SearchMatch[];
+}
+
+/**
+ * Workspace search shown in the sidebar
+ */
+export interface WorkspaceSearch {
+  query: string;
+  root: string;
+  /** Id of the running search, null until the backend has started it */
+  id: number | null;
+  results: SearchFileMatches[];
+  finished: boolean;
+  /** Whether there were more matches than the backend returns */
+  truncated: boolean;
+}
+
+/**
+ * Document split into front matter and body (snake_case, from the backend)
+ */
+export interface ParsedDocument {
+  front_matter: {
+    format: "yaml" | "toml" | "json";
+    /** Fields in their original order, null if the block is invalid */
+    data: Record<string, unknown> | null;
+    error: string | null;
+    lines: number;
+  } | null;
+  body: string;
+}